@@ -48,6 +48,11 @@ lazy_static! {
     };
 }
 
+/// stops one of our actors, messages sent to it afterwards end up as dead letters
+pub fn stop_actor(actor: &ActorRef<Protocol>) {
+    SYS.stop(actor);
+}
+
 /// convenience trait to build fake synchronous facades for actors
 pub trait AskSelf {
     /// adapter for synchronous code to interact with an actor
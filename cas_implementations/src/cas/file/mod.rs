@@ -1,5 +1,5 @@
 pub mod actor;
-use actor::{AskSelf, Protocol};
+use actor::{stop_actor, AskSelf, Protocol};
 use cas::file::actor::FilesystemStorageActor;
 use holochain_core_types::{
     cas::{
@@ -21,6 +21,12 @@ impl FilesystemStorage {
             actor: FilesystemStorageActor::new_ref(path)?,
         })
     }
+
+    /// Stops the actor behind this storage, e.g. when shutting down an instance.
+    /// All clones of this storage share the actor, none of them can be used afterwards.
+    pub fn stop(&self) {
+        stop_actor(&self.actor)
+    }
 }
 
 impl ContentAddressableStorage for FilesystemStorage {
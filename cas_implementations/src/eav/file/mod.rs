@@ -1,5 +1,5 @@
 pub mod actor;
-use actor::{AskSelf, Protocol};
+use actor::{stop_actor, AskSelf, Protocol};
 use eav::file::actor::EavFileStorageActor;
 use holochain_core_types::{
     eav::{Attribute, Entity, EntityAttributeValue, EntityAttributeValueStorage, Value},
@@ -19,6 +19,12 @@ impl EavFileStorage {
             actor: EavFileStorageActor::new_ref(&dir_path)?,
         })
     }
+
+    /// Stops the actor behind this storage, e.g. when shutting down an instance.
+    /// All clones of this storage share the actor, none of them can be used afterwards.
+    pub fn stop(&self) {
+        stop_actor(&self.actor)
+    }
 }
 
 impl EntityAttributeValueStorage for EavFileStorage {
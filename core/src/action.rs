@@ -21,7 +21,7 @@ use std::{
 /// The standard approach is to drop the ActionWrapper into the key of a state history HashMap and
 /// use the convenience unwrap_to! macro to extract the action data in a reducer.
 /// All reducer functions must accept an ActionWrapper so all dispatchers take an ActionWrapper.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ActionWrapper {
     action: Action,
     id: snowflake::ProcessUniqueId,
@@ -67,16 +67,34 @@ impl Hash for ActionWrapper {
 }
 
 /// All Actions for the Holochain Instance Store, according to Redux pattern.
-#[derive(Clone, PartialEq, Debug)]
+/// Actions serialize to JSON so that they can be recorded in a journal and replayed.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Action {
     /// entry to Commit
     /// MUST already have passed all callback checks
-    Commit(Entry),
+    Commit(#[serde(with = "serialize_entry")] Entry),
+    /// entries of a bundle to Commit in one go, in the order they were staged
+    /// either all of them end up on the source chain or none of them does
+    /// MUST already have passed all callback checks
+    CommitBundle(#[serde(with = "serialize_entries")] Vec<Entry>),
     /// GetEntry by address
     GetEntry(Address),
 
-    /// link to add
-    AddLink(Link),
+    /// mark an entry as MODIFIED in the DHT
+    /// key is the address of the updated entry, value the address of its new version
+    /// the new version MUST already be committed
+    UpdateEntry((Address, Address)),
+    /// mark an entry as DELETED in the DHT
+    /// key is the address of the removed entry, value the address of the deletion entry
+    /// the deletion entry MUST already be committed
+    RemoveEntry((Address, Address)),
+
+    /// link to add, together with the address of the chain header that committed it,
+    /// which tells apart the times the same link got added
+    AddLink((Link, Address)),
+    /// link to remove
+    /// the link is only retracted in the meta storage, its history is kept
+    RemoveLink(Link),
     /// get links from entry address and attribute-name
     //GetLinks(GetLinksArgs),
 
@@ -116,12 +134,80 @@ pub type AgentReduceFn = ReduceFn<AgentState>;
 pub type NucleusReduceFn = ReduceFn<NucleusState>;
 pub type ReduceFn<S> = fn(Arc<Context>, &mut S, &ActionWrapper);
 
+/// (De)serializes an Entry through its SerializedEntry, for `#[serde(with = "...")]`,
+/// as entries don't implement Serialize themselves.
+pub(crate) mod serialize_entry {
+    use holochain_core_types::entry::{Entry, SerializedEntry};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(entry: &Entry, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&SerializedEntry::from(entry.clone()), serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entry, D::Error> {
+        <SerializedEntry as Deserialize>::deserialize(deserializer).map(Entry::from)
+    }
+}
+
+/// (De)serializes a list of entries as SerializedEntry, for `#[serde(with = "...")]`.
+pub(crate) mod serialize_entries {
+    use holochain_core_types::entry::{Entry, SerializedEntry};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(entries: &[Entry], serializer: S) -> Result<S::Ok, S::Error> {
+        let entries: Vec<SerializedEntry> =
+            entries.iter().cloned().map(SerializedEntry::from).collect();
+        Serialize::serialize(&entries, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Entry>, D::Error> {
+        <Vec<SerializedEntry> as Deserialize>::deserialize(deserializer)
+            .map(|entries| entries.into_iter().map(Entry::from).collect())
+    }
+}
+
+/// (De)serializes a JsonString as the string of JSON it holds, for `#[serde(with = "...")]`,
+/// as JsonString must not be serialized itself.
+pub(crate) mod serialize_json_string {
+    use holochain_core_types::json::JsonString;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(json: &JsonString, serializer: S) -> Result<S::Ok, S::Error> {
+        String::from(json).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<JsonString, D::Error> {
+        String::deserialize(deserializer).map(JsonString::from)
+    }
+}
+
+/// (De)serializes the result of a zome function call, see serialize_json_string.
+pub(crate) mod serialize_zome_fn_result {
+    use holochain_core_types::{error::HolochainError, json::JsonString};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        result: &Result<JsonString, HolochainError>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        result.as_ref().map(String::from).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Result<JsonString, HolochainError>, D::Error> {
+        Result::<String, HolochainError>::deserialize(deserializer)
+            .map(|result| result.map(JsonString::from))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
     use action::{Action, ActionWrapper};
     use holochain_core_types::entry::{expected_entry_address, test_entry};
     use nucleus::tests::test_call_response;
+    use serde_json;
     use test_utils::calculate_hash;
 
     /// dummy action
@@ -199,4 +285,21 @@ pub mod tests {
         assert_ne!(calculate_hash(&aw1), calculate_hash(&aw2));
     }
 
+    #[test]
+    /// tests that action wrappers survive a JSON round trip with their id
+    fn action_wrapper_serialization_round_trip() {
+        for action_wrapper in vec![
+            test_action_wrapper_commit(),
+            ActionWrapper::new(Action::CommitBundle(vec![test_entry(), test_entry()])),
+            test_action_wrapper_get(),
+            test_action_wrapper_rzfr(),
+        ] {
+            let json = serde_json::to_string(&action_wrapper).unwrap();
+            let restored: ActionWrapper = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(action_wrapper.id(), restored.id());
+            assert_eq!(action_wrapper.action(), restored.action());
+        }
+    }
+
 }
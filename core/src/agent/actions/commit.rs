@@ -25,6 +25,23 @@ pub fn commit_entry(
     }
 }
 
+/// Commits the entries of a bundle in a single action, so that either all of them end up
+/// on the source chain or none of them does.
+///
+/// Returns a future that resolves to the addresses of the committed entries.
+pub fn commit_entries(
+    entries: Vec<Entry>,
+    action_channel: &SyncSender<ActionWrapper>,
+    context: &Arc<Context>,
+) -> CommitBundleFuture {
+    let action_wrapper = ActionWrapper::new(Action::CommitBundle(entries));
+    dispatch_action(action_channel, action_wrapper.clone());
+    CommitBundleFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    }
+}
+
 /// CommitFuture resolves to ActionResponse
 /// Tracks the state for a response to its ActionWrapper
 pub struct CommitFuture {
@@ -62,3 +79,40 @@ impl Future for CommitFuture {
         }
     }
 }
+
+/// CommitBundleFuture resolves to the addresses of all committed entries
+/// once both the source chain and the DHT have taken the bundle
+/// Tracks the state for a response to its ActionWrapper
+pub struct CommitBundleFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for CommitBundleFuture {
+    type Item = Vec<Address>;
+    type Error = HolochainError;
+
+    fn poll(
+        &mut self,
+        cx: &mut futures::task::Context<'_>,
+    ) -> Result<futures::Async<Vec<Address>>, Self::Error> {
+        cx.waker().wake();
+        let state = self.context.state().unwrap();
+        let dht_result = state
+            .dht()
+            .commit_bundle_actions()
+            .get(&self.action)
+            .cloned();
+        match (state.agent().actions().get(&self.action), dht_result) {
+            (Some(ActionResponse::CommitBundle(Err(error))), _) => Err(error.clone()),
+            (_, Some(Err(error))) => Err(error),
+            (Some(ActionResponse::CommitBundle(Ok(addresses))), Some(Ok(()))) => {
+                Ok(futures::Async::Ready(addresses.clone()))
+            }
+            (Some(ActionResponse::CommitBundle(_)), None) | (None, _) => {
+                Ok(futures::Async::Pending)
+            }
+            (Some(_), _) => unreachable!(),
+        }
+    }
+}
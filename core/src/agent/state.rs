@@ -1,6 +1,7 @@
 use action::{Action, ActionWrapper, AgentReduceFn};
 use agent::chain_store::ChainStore;
 use context::Context;
+use dht::dht_reducers::stage_entry;
 use holochain_cas_implementations::cas::file::FilesystemStorage;
 use holochain_core_types::{
     cas::{
@@ -118,6 +119,7 @@ impl AddressableContent for AgentStateSnapshot {
 // @see https://github.com/holochain/holochain-rust/issues/196
 pub enum ActionResponse {
     Commit(Result<Address, HolochainError>),
+    CommitBundle(Result<Vec<Address>, HolochainError>),
     GetEntry(Option<SerializedEntry>),
     GetLinks(Result<Vec<Address>, HolochainError>),
     LinkEntries(Result<SerializedEntry, HolochainError>),
@@ -185,6 +187,89 @@ fn reduce_commit_entry(
         .insert(action_wrapper.clone(), ActionResponse::Commit(result));
 }
 
+/// Creates the chain headers for the entries of a bundle as if they got committed one after
+/// the other, without storing anything yet.
+fn create_bundle_chain_headers(entries: &[Entry], agent_state: &AgentState) -> Vec<ChainHeader> {
+    let mut chain_headers: Vec<ChainHeader> = Vec::new();
+    for entry in entries {
+        let link = chain_headers
+            .last()
+            .or_else(|| agent_state.top_chain_header.as_ref())
+            .map(|chain_header| chain_header.address());
+        // staged headers are not in the chain store yet, so look through them first
+        let link_same_type = chain_headers
+            .iter()
+            .rev()
+            .find(|chain_header| chain_header.entry_type() == entry.entry_type())
+            .cloned()
+            .or_else(|| {
+                agent_state
+                    .chain()
+                    .iter_type(&agent_state.top_chain_header, &entry.entry_type())
+                    .nth(0)
+            })
+            .map(|chain_header| chain_header.address());
+        chain_headers.push(ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            // @TODO signatures
+            &Signature::from(""),
+            &link,
+            &link_same_type,
+            // @TODO timestamp
+            &Iso8601::from(""),
+        ));
+    }
+    chain_headers
+}
+
+/// Do a CommitBundle Action against an agent state.
+/// The whole bundle gets staged before anything is stored: every entry has to be one the DHT
+/// can hold, and the chain headers of all entries are created up front.
+/// Only then the entries and headers get stored, and the top chain header moves to the end
+/// of the bundle once all of them are, so a failing bundle never ends up on the chain.
+fn reduce_commit_bundle(
+    context: Arc<Context>,
+    state: &mut AgentState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let entries = unwrap_to!(action => Action::CommitBundle);
+    let chain_headers = create_bundle_chain_headers(entries, state);
+
+    fn response(
+        context: &Arc<Context>,
+        state: &mut AgentState,
+        entries: &[Entry],
+        chain_headers: &[ChainHeader],
+    ) -> Result<Vec<Address>, HolochainError> {
+        for entry in entries {
+            stage_entry(context, entry)?;
+        }
+        for (entry, chain_header) in entries.iter().zip(chain_headers) {
+            state.chain.content_storage().add(entry)?;
+            state.chain.content_storage().add(chain_header)?;
+        }
+        Ok(entries.iter().map(|entry| entry.address()).collect())
+    }
+    let result = response(&context, state, entries, &chain_headers);
+    if result.is_ok() {
+        if let Some(chain_header) = chain_headers.last() {
+            state.top_chain_header = Some(chain_header.clone());
+        }
+        #[allow(unused_must_use)]
+        context.state().map(|global_state_lock| {
+            let persis_lock = context.persister.clone();
+            let persister = &mut *persis_lock.lock().unwrap();
+            persister.save(global_state_lock.clone());
+        });
+    }
+
+    state
+        .actions
+        .insert(action_wrapper.clone(), ActionResponse::CommitBundle(result));
+}
+
 /// do a get action against an agent state
 /// intended for use inside the reducer, isolated for unit testing
 fn reduce_get_entry(
@@ -214,6 +299,7 @@ fn reduce_get_entry(
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<AgentReduceFn> {
     match action_wrapper.action() {
         Action::Commit(_) => Some(reduce_commit_entry),
+        Action::CommitBundle(_) => Some(reduce_commit_bundle),
         Action::GetEntry(_) => Some(reduce_get_entry),
         _ => None,
     }
@@ -239,22 +325,37 @@ pub fn reduce(
 #[cfg(test)]
 pub mod tests {
     extern crate tempfile;
+    extern crate test_utils;
     use super::{
-        reduce_commit_entry, reduce_get_entry, ActionResponse, AgentState, AgentStateSnapshot,
+        reduce_commit_bundle, reduce_commit_entry, reduce_get_entry, ActionResponse, AgentState,
+        AgentStateSnapshot,
+    };
+    use action::{
+        tests::{test_action_wrapper_commit, test_action_wrapper_get},
+        Action, ActionWrapper,
     };
-    use action::tests::{test_action_wrapper_commit, test_action_wrapper_get};
     use agent::chain_store::tests::test_chain_store;
+    use context::Context;
     use holochain_core_types::{
-        cas::content::AddressableContent,
+        cas::{content::AddressableContent, storage::ContentAddressableStorage},
         chain_header::test_chain_header,
-        entry::{expected_entry_address, test_entry, SerializedEntry},
+        entry::{expected_entry_address, test_entry, test_entry_b, Entry, SerializedEntry},
+        entry_type::EntryType,
         error::HolochainError,
-        json::JsonString,
+        json::{JsonString, RawString},
     };
-    use instance::tests::test_context;
+    use instance::tests::{test_context, test_instance_and_context};
     use serde_json;
     use std::{collections::HashMap, sync::Arc};
 
+    /// context of an instance with a DNA that defines the test entry types
+    fn test_context_with_dna() -> Arc<Context> {
+        let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (_instance, context) =
+            test_instance_and_context(dna).expect("Could not initialize test instance");
+        context
+    }
+
     /// dummy agent state
     pub fn test_agent_state() -> AgentState {
         AgentState::new(test_chain_store())
@@ -302,6 +403,64 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// test for reducing a bundle of entries
+    fn test_reduce_commit_bundle() {
+        let mut state = test_agent_state();
+        let action_wrapper =
+            ActionWrapper::new(Action::CommitBundle(vec![test_entry(), test_entry_b()]));
+
+        reduce_commit_bundle(test_context_with_dna(), &mut state, &action_wrapper);
+
+        assert_eq!(
+            state.actions().get(&action_wrapper),
+            Some(&ActionResponse::CommitBundle(Ok(vec![
+                test_entry().address(),
+                test_entry_b().address(),
+            ]))),
+        );
+        let addresses: Vec<_> = state
+            .chain()
+            .iter(&state.top_chain_header())
+            .map(|chain_header| chain_header.entry_address().clone())
+            .collect();
+        assert_eq!(
+            vec![test_entry_b().address(), test_entry().address()],
+            addresses
+        );
+    }
+
+    #[test]
+    /// test that a bundle with an entry that can not be committed stores none of its entries
+    fn test_reduce_commit_bundle_stores_nothing_on_failure() {
+        let mut state = test_agent_state();
+        let undefined_entry = Entry::new(
+            EntryType::App(String::from("undefinedEntryType")),
+            RawString::from("not in the DNA"),
+        );
+        let action_wrapper =
+            ActionWrapper::new(Action::CommitBundle(vec![test_entry(), undefined_entry]));
+
+        reduce_commit_bundle(test_context_with_dna(), &mut state, &action_wrapper);
+
+        assert_eq!(
+            state.actions().get(&action_wrapper),
+            Some(&ActionResponse::CommitBundle(Err(
+                HolochainError::ErrorGeneric(String::from(
+                    "Entry type undefinedEntryType is not defined in the DNA"
+                ))
+            ))),
+        );
+        assert_eq!(None, state.top_chain_header());
+        assert_eq!(
+            Ok(false),
+            state
+                .chain()
+                .content_storage()
+                .contains(&test_entry().address())
+        );
+    }
+
     #[test]
     /// test for reducing get entry
     fn test_reduce_get_entry() {
@@ -1,11 +1,16 @@
 use action::ActionWrapper;
+use executor::{Executor, ExecutorConfig};
 use holochain_agent::Agent;
 use holochain_core_types::error::HolochainError;
+use holochain_net::p2p_network::P2pNetwork;
 use instance::Observer;
+use journal::ActionJournal;
 use logger::Logger;
+use nucleus::ribosome::module_cache::ModuleCache;
 use persister::Persister;
 use state::State;
 use std::sync::{
+    atomic::{AtomicBool, Ordering},
     mpsc::{sync_channel, SyncSender},
     Arc, Mutex, RwLock, RwLockReadGuard,
 };
@@ -26,6 +31,17 @@ pub struct Context {
     pub observer_channel: SyncSender<Observer>,
     pub file_storage: FilesystemStorage,
     pub eav_storage: EavFileStorage,
+    /// Compiled WASM modules of the DNA's zomes, shared by all clones of this context
+    pub module_cache: Arc<Mutex<ModuleCache>>,
+    /// Worker pools running zome calls and validations, shared by all clones of this context
+    pub executor: Arc<Executor>,
+    network: Option<Arc<Mutex<P2pNetwork + Send>>>,
+    /// Records every action the instance processes, if set
+    journal: Option<Arc<Mutex<ActionJournal>>>,
+    /// Set while a journal gets replayed, reducers don't launch zome calls then
+    replaying: bool,
+    /// Set once zome calls get cancelled, shared by all clones of this context
+    zome_calls_cancelled: Arc<AtomicBool>,
 }
 
 impl Context {
@@ -51,6 +67,12 @@ impl Context {
             observer_channel: tx_observer,
             file_storage: cas,
             eav_storage: eav,
+            module_cache: Arc::new(Mutex::new(ModuleCache::new())),
+            executor: Arc::new(Executor::default()),
+            network: None,
+            journal: None,
+            replaying: false,
+            zome_calls_cancelled: Arc::new(AtomicBool::new(false)),
         })
     }
 
@@ -72,6 +94,12 @@ impl Context {
             observer_channel,
             file_storage: cas,
             eav_storage: eav,
+            module_cache: Arc::new(Mutex::new(ModuleCache::new())),
+            executor: Arc::new(Executor::default()),
+            network: None,
+            journal: None,
+            replaying: false,
+            zome_calls_cancelled: Arc::new(AtomicBool::new(false)),
         })
     }
     // helper function to make it easier to call the logger
@@ -91,6 +119,52 @@ impl Context {
             Some(ref s) => Some(s.read().unwrap()),
         }
     }
+
+    /// Connect the instance to a network so it can exchange messages with other agents.
+    /// Has to be set before the instance gets initialized.
+    pub fn set_network(&mut self, network: Arc<Mutex<P2pNetwork + Send>>) {
+        self.network = Some(network);
+    }
+
+    pub fn network(&self) -> Option<Arc<Mutex<P2pNetwork + Send>>> {
+        self.network.clone()
+    }
+
+    /// Record every action the instance processes in the given journal.
+    /// Has to be set before the instance gets initialized.
+    pub fn set_journal(&mut self, journal: ActionJournal) {
+        self.journal = Some(Arc::new(Mutex::new(journal)));
+    }
+
+    pub fn journal(&self) -> Option<Arc<Mutex<ActionJournal>>> {
+        self.journal.clone()
+    }
+
+    /// Mark the context as one a journal gets replayed with,
+    /// see journal::replay.
+    pub fn set_replaying(&mut self, replaying: bool) {
+        self.replaying = replaying;
+    }
+
+    pub fn replaying(&self) -> bool {
+        self.replaying
+    }
+
+    /// Abort all zome calls of the instance, the running ones as well as the ones to come.
+    /// They fail with HolochainError::ExecutionLimitExceeded the next time they get metered.
+    pub fn cancel_zome_calls(&self) {
+        self.zome_calls_cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn zome_calls_cancellation(&self) -> Arc<AtomicBool> {
+        self.zome_calls_cancelled.clone()
+    }
+
+    /// Replace the worker pools with ones of the given size.
+    /// Has to be set before the instance gets initialized.
+    pub fn set_executor_config(&mut self, config: ExecutorConfig) {
+        self.executor = Arc::new(Executor::new(config));
+    }
 }
 
 #[cfg(test)]
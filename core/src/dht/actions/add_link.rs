@@ -3,7 +3,7 @@ extern crate serde_json;
 use action::{Action, ActionWrapper};
 use context::Context;
 use futures::{Async, Future};
-use holochain_core_types::{error::HolochainError, links_entry::Link};
+use holochain_core_types::{cas::content::Address, error::HolochainError, links_entry::Link};
 use instance::dispatch_action;
 use std::sync::Arc;
 
@@ -13,10 +13,12 @@ use std::sync::Arc;
 /// The DHT reducer does make sure that it only adds links to a base that it has in its
 /// local storage and will return an error that the AddLinkFuture resolves to
 /// if that is not the case.
+/// The instance is the address of the chain header that committed the link,
+/// so a link that got removed can be added again.
 ///
 /// Returns a future that resolves to an Ok(()) or an Err(HolochainError).
-pub fn add_link(link: &Link, context: &Arc<Context>) -> AddLinkFuture {
-    let action_wrapper = ActionWrapper::new(Action::AddLink(link.clone()));
+pub fn add_link(link: &Link, instance: &Address, context: &Arc<Context>) -> AddLinkFuture {
+    let action_wrapper = ActionWrapper::new(Action::AddLink((link.clone(), instance.clone())));
     dispatch_action(&context.action_channel, action_wrapper.clone());
 
     AddLinkFuture {
@@ -77,8 +79,9 @@ mod tests {
 
         let target = base.clone();
         let link = Link::new(&base.address(), &target.address(), "test-tag");
+        let instance = Address::from("link-instance");
 
-        let result = block_on(add_link(&link, &context.clone()));
+        let result = block_on(add_link(&link, &instance, &context.clone()));
 
         assert!(result.is_ok(), "result = {:?}", result);
     }
@@ -90,8 +93,9 @@ mod tests {
         let base = test_entry();
         let target = base.clone();
         let link = Link::new(&base.address(), &target.address(), "test-tag");
+        let instance = Address::from("link-instance");
 
-        let result = block_on(add_link(&link, &context.clone()));
+        let result = block_on(add_link(&link, &instance, &context.clone()));
 
         assert!(result.is_err());
         assert_eq!(
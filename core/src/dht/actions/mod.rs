@@ -1 +1,4 @@
 pub mod add_link;
+pub mod remove_entry;
+pub mod remove_link;
+pub mod update_entry;
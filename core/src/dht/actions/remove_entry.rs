@@ -0,0 +1,123 @@
+extern crate futures;
+extern crate serde_json;
+use action::{Action, ActionWrapper};
+use context::Context;
+use futures::{Async, Future};
+use holochain_core_types::{cas::content::Address, error::HolochainError};
+use instance::dispatch_action;
+use std::sync::Arc;
+
+/// RemoveEntry Action Creator
+/// This action creator dispatches a RemoveEntry action which is consumed by the DHT reducer.
+/// It marks the entry at `removed_address` as DELETED and links it to the deletion entry
+/// at `deletion_address` which has to be committed before calling this.
+///
+/// Returns a future that resolves to an Ok(()) or an Err(HolochainError).
+pub fn remove_entry(
+    removed_address: Address,
+    deletion_address: Address,
+    context: &Arc<Context>,
+) -> RemoveEntryFuture {
+    let action_wrapper =
+        ActionWrapper::new(Action::RemoveEntry((removed_address, deletion_address)));
+    dispatch_action(&context.action_channel, action_wrapper.clone());
+
+    RemoveEntryFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    }
+}
+
+pub struct RemoveEntryFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for RemoveEntryFuture {
+    type Item = ();
+    type Error = HolochainError;
+
+    fn poll(
+        &mut self,
+        cx: &mut futures::task::Context<'_>,
+    ) -> Result<Async<Self::Item>, Self::Error> {
+        //
+        // TODO: connect the waker to state updates for performance reasons
+        // See: https://github.com/holochain/holochain-rust/issues/314
+        //
+        cx.waker().wake();
+        if let Some(state) = self.context.state() {
+            match state.dht().remove_entry_actions().get(&self.action) {
+                Some(Ok(())) => Ok(futures::Async::Ready(())),
+                Some(Err(e)) => Err(e.clone()),
+                None => Ok(futures::Async::Pending),
+            }
+        } else {
+            Ok(futures::Async::Pending)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nucleus;
+
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        crud_status::CrudStatus,
+        delete_entry::DeletionEntry,
+        entry::{Entry, ToEntry},
+    };
+
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_entry() -> Entry {
+        nucleus::actions::tests::test_entry_package_entry()
+    }
+
+    #[test]
+    fn can_remove_committed_entry() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let entry = test_entry();
+        let deletion_entry = DeletionEntry::new(entry.address()).to_entry();
+        nucleus::actions::tests::commit(entry.clone(), &context);
+        nucleus::actions::tests::commit(deletion_entry.clone(), &context);
+
+        let result = block_on(remove_entry(
+            entry.address(),
+            deletion_entry.address(),
+            &context.clone(),
+        ));
+
+        assert!(result.is_ok(), "result = {:?}", result);
+        assert_eq!(
+            Ok(CrudStatus::DELETED),
+            context
+                .state()
+                .unwrap()
+                .dht()
+                .get_crud_status(&entry.address())
+        );
+    }
+
+    #[test]
+    fn errors_when_entry_not_present() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let entry = test_entry();
+        let deletion_entry = DeletionEntry::new(entry.address()).to_entry();
+
+        let result = block_on(remove_entry(
+            entry.address(),
+            deletion_entry.address(),
+            &context.clone(),
+        ));
+
+        assert_eq!(
+            result.err().unwrap(),
+            HolochainError::ErrorGeneric(String::from("Entry not found"))
+        );
+    }
+}
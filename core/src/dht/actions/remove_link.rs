@@ -0,0 +1,148 @@
+extern crate futures;
+use action::{Action, ActionWrapper};
+use context::Context;
+use futures::{Async, Future};
+use holochain_core_types::{error::HolochainError, links_entry::Link};
+use instance::dispatch_action;
+use std::sync::Arc;
+
+/// RemoveLink Action Creator
+/// This action creator dispatches a RemoveLink action which is consumed by the DHT reducer.
+/// The DHT reducer does not delete the link but retracts it with a tombstone,
+/// so removed links are no longer returned by get_links while their history is kept.
+/// The RemoveLinkFuture resolves to an error if the link does not exist or
+/// has already been removed.
+///
+/// Returns a future that resolves to an Ok(()) or an Err(HolochainError).
+pub fn remove_link(link: &Link, context: &Arc<Context>) -> RemoveLinkFuture {
+    let action_wrapper = ActionWrapper::new(Action::RemoveLink(link.clone()));
+    dispatch_action(&context.action_channel, action_wrapper.clone());
+
+    RemoveLinkFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    }
+}
+
+pub struct RemoveLinkFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for RemoveLinkFuture {
+    type Item = ();
+    type Error = HolochainError;
+
+    fn poll(
+        &mut self,
+        cx: &mut futures::task::Context<'_>,
+    ) -> Result<Async<Self::Item>, Self::Error> {
+        //
+        // TODO: connect the waker to state updates for performance reasons
+        // See: https://github.com/holochain/holochain-rust/issues/314
+        //
+        cx.waker().wake();
+        if let Some(state) = self.context.state() {
+            match state.dht().remove_link_actions().get(&self.action) {
+                Some(Ok(())) => Ok(futures::Async::Ready(())),
+                Some(Err(e)) => Err(e.clone()),
+                None => Ok(futures::Async::Pending),
+            }
+        } else {
+            Ok(futures::Async::Pending)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dht::actions::add_link::add_link;
+    use nucleus;
+
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        entry::Entry,
+        links_entry::Link,
+    };
+
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_entry() -> Entry {
+        nucleus::actions::tests::test_entry_package_entry()
+    }
+
+    #[test]
+    fn can_remove_link() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let base = test_entry();
+        nucleus::actions::tests::commit(base.clone(), &context);
+
+        let link = Link::new(&base.address(), &base.address(), "test-tag");
+        let instance = Address::from("link-instance");
+        assert!(block_on(add_link(&link, &instance, &context.clone())).is_ok());
+
+        let result = block_on(remove_link(&link, &context.clone()));
+        assert!(result.is_ok(), "result = {:?}", result);
+
+        let links = context
+            .state()
+            .unwrap()
+            .dht()
+            .get_links(base.address(), "test-tag".into())
+            .unwrap();
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn can_add_link_again_after_removing() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let base = test_entry();
+        nucleus::actions::tests::commit(base.clone(), &context);
+
+        let link = Link::new(&base.address(), &base.address(), "test-tag");
+        let first = Address::from("first-instance");
+        let second = Address::from("second-instance");
+        assert!(block_on(add_link(&link, &first, &context.clone())).is_ok());
+        assert!(block_on(remove_link(&link, &context.clone())).is_ok());
+
+        // the removed instance stays removed...
+        assert_eq!(
+            block_on(add_link(&link, &first, &context.clone())).err(),
+            Some(HolochainError::ErrorGeneric(String::from(
+                "Link instance has been removed already"
+            )))
+        );
+        // ...but adding the link again creates a new, live instance
+        assert!(block_on(add_link(&link, &second, &context.clone())).is_ok());
+        let dht = context.state().unwrap().dht();
+        let links = dht.get_links(base.address(), "test-tag".into()).unwrap();
+        assert_eq!(1, links.len());
+
+        // which can be removed again
+        assert!(block_on(remove_link(&link, &context.clone())).is_ok());
+        let dht = context.state().unwrap().dht();
+        assert!(dht
+            .get_links(base.address(), "test-tag".into())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn errors_when_link_not_present() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let base = test_entry();
+        nucleus::actions::tests::commit(base.clone(), &context);
+        let link = Link::new(&base.address(), &base.address(), "test-tag");
+
+        let result = block_on(remove_link(&link, &context.clone()));
+
+        assert_eq!(
+            result.err().unwrap(),
+            HolochainError::ErrorGeneric(String::from("Link to remove not found"))
+        );
+    }
+}
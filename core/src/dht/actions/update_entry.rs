@@ -0,0 +1,126 @@
+extern crate futures;
+extern crate serde_json;
+use action::{Action, ActionWrapper};
+use context::Context;
+use futures::{Async, Future};
+use holochain_core_types::{cas::content::Address, error::HolochainError};
+use instance::dispatch_action;
+use std::sync::Arc;
+
+/// UpdateEntry Action Creator
+/// This action creator dispatches an UpdateEntry action which is consumed by the DHT reducer.
+/// It marks the entry at `old_address` as MODIFIED and links it to `new_address`
+/// so that lookups of the old address can follow the update chain.
+/// The new version has to be committed before calling this.
+///
+/// Returns a future that resolves to an Ok(()) or an Err(HolochainError).
+pub fn update_entry(
+    old_address: Address,
+    new_address: Address,
+    context: &Arc<Context>,
+) -> UpdateEntryFuture {
+    let action_wrapper = ActionWrapper::new(Action::UpdateEntry((old_address, new_address)));
+    dispatch_action(&context.action_channel, action_wrapper.clone());
+
+    UpdateEntryFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    }
+}
+
+pub struct UpdateEntryFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for UpdateEntryFuture {
+    type Item = ();
+    type Error = HolochainError;
+
+    fn poll(
+        &mut self,
+        cx: &mut futures::task::Context<'_>,
+    ) -> Result<Async<Self::Item>, Self::Error> {
+        //
+        // TODO: connect the waker to state updates for performance reasons
+        // See: https://github.com/holochain/holochain-rust/issues/314
+        //
+        cx.waker().wake();
+        if let Some(state) = self.context.state() {
+            match state.dht().update_entry_actions().get(&self.action) {
+                Some(Ok(())) => Ok(futures::Async::Ready(())),
+                Some(Err(e)) => Err(e.clone()),
+                None => Ok(futures::Async::Pending),
+            }
+        } else {
+            Ok(futures::Async::Pending)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nucleus;
+
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent, crud_status::CrudStatus, entry::Entry,
+        entry_type::EntryType,
+    };
+
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_entry() -> Entry {
+        nucleus::actions::tests::test_entry_package_entry()
+    }
+
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_entry_b() -> Entry {
+        Entry::new(
+            EntryType::App(String::from("package_entry")),
+            "updated test value",
+        )
+    }
+
+    #[test]
+    fn can_update_committed_entry() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let old_entry = test_entry();
+        let new_entry = test_entry_b();
+        nucleus::actions::tests::commit(old_entry.clone(), &context);
+        nucleus::actions::tests::commit(new_entry.clone(), &context);
+
+        let result = block_on(update_entry(
+            old_entry.address(),
+            new_entry.address(),
+            &context.clone(),
+        ));
+
+        assert!(result.is_ok(), "result = {:?}", result);
+        assert_eq!(
+            Ok(CrudStatus::MODIFIED),
+            context
+                .state()
+                .unwrap()
+                .dht()
+                .get_crud_status(&old_entry.address())
+        );
+    }
+
+    #[test]
+    fn errors_when_entry_not_present() {
+        let (_instance, context) = nucleus::actions::tests::instance();
+
+        let result = block_on(update_entry(
+            test_entry().address(),
+            test_entry_b().address(),
+            &context.clone(),
+        ));
+
+        assert_eq!(
+            result.err().unwrap(),
+            HolochainError::ErrorGeneric(String::from("Entry not found"))
+        );
+    }
+}
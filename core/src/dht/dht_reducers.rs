@@ -4,7 +4,11 @@ use action::{Action, ActionWrapper};
 use context::Context;
 use dht::dht_store::DhtStore;
 use holochain_core_types::{
-    cas::{content::AddressableContent, storage::ContentAddressableStorage},
+    cas::{
+        content::{Address, AddressableContent},
+        storage::ContentAddressableStorage,
+    },
+    crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
     eav::{EntityAttributeValue, EntityAttributeValueStorage},
     entry::Entry,
     error::HolochainError,
@@ -48,8 +52,12 @@ where
 {
     match action_wrapper.action() {
         Action::Commit(_) => Some(reduce_commit_entry),
+        Action::CommitBundle(_) => Some(reduce_commit_bundle),
         Action::GetEntry(_) => Some(reduce_get_entry_from_network),
+        Action::UpdateEntry(_) => Some(reduce_update_entry),
+        Action::RemoveEntry(_) => Some(reduce_remove_entry),
         Action::AddLink(_) => Some(reduce_add_link),
+        Action::RemoveLink(_) => Some(reduce_remove_link),
         //Action::GetLinks(_) => Some(reduce_get_links),
         _ => None,
     }
@@ -135,7 +143,94 @@ where
 {
     let action = action_wrapper.action();
     let entry = unwrap_to!(action => Action::Commit);
+    commit_entry(context, old_store, entry)
+}
+
+/// Checks whether the DHT can hold the given entry, without storing anything.
+/// Returns whether the entry gets published, which private entries are not.
+/// Fails for app entries of a type that the DNA does not define.
+pub(crate) fn stage_entry(context: &Arc<Context>, entry: &Entry) -> Result<bool, HolochainError> {
+    if entry.entry_type().is_sys() {
+        return Ok(entry.entry_type().can_publish());
+    }
+    let dna = context
+        .state()
+        .and_then(|state| state.nucleus().dna())
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(String::from("DNA is needed to commit an app entry"))
+        })?;
+    let entry_type_def = dna
+        .get_entry_type_def(&entry.entry_type().to_string())
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Entry type {} is not defined in the DNA",
+                entry.entry_type()
+            ))
+        })?;
+    Ok(entry_type_def.sharing.clone().can_publish())
+}
+
+/// Stages all entries of the bundle before holding any of them,
+/// so that a bundle with an entry the DHT can not hold leaves the store untouched.
+fn commit_bundle<CAS, EAVS>(
+    context: &Arc<Context>,
+    store: &mut DhtStore<CAS, EAVS>,
+    entries: &[Entry],
+) -> Result<(), HolochainError>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
+    let staged = entries
+        .iter()
+        .map(|entry| stage_entry(context, entry).map(|publish| (entry, publish)))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (entry, publish) in staged {
+        if !publish {
+            continue;
+        }
+        if !store.content_storage().contains(&entry.address())? {
+            store.content_storage_mut().add(entry)?;
+        }
+        // Note: System entry types are not published to the network
+        if !entry.entry_type().is_sys() {
+            store.network_mut().publish(entry);
+        }
+    }
+    Ok(())
+}
 
+//
+pub(crate) fn reduce_commit_bundle<CAS, EAVS>(
+    context: Arc<Context>,
+    old_store: &DhtStore<CAS, EAVS>,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore<CAS, EAVS>>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
+    let action = action_wrapper.action();
+    let entries = unwrap_to!(action => Action::CommitBundle);
+
+    let mut new_store = (*old_store).clone();
+    let result = commit_bundle(&context, &mut new_store, entries);
+    new_store
+        .commit_bundle_actions_mut()
+        .insert(action_wrapper.clone(), result);
+    Some(new_store)
+}
+
+//
+fn commit_entry<CAS, EAVS>(
+    context: Arc<Context>,
+    old_store: &DhtStore<CAS, EAVS>,
+    entry: &Entry,
+) -> Option<DhtStore<CAS, EAVS>>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
     // pre-condition: Must not already have entry in local storage
     if old_store
         .content_storage()
@@ -188,8 +283,50 @@ where
         })
 }
 
+/// Records the CRUD status of the entry at `address` together with a crud-link
+/// to `crud_link` in the store's meta storage.
+/// Only LIVE entries can be modified or removed, and only towards a LIVE entry,
+/// which keeps the chain of updates free of cycles.
+fn add_crud_meta<CAS, EAVS>(
+    store: &mut DhtStore<CAS, EAVS>,
+    address: &Address,
+    crud_link: &Address,
+    crud_status: CrudStatus,
+) -> Result<(), HolochainError>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
+    if !store.content_storage().contains(address)? {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "Entry not found",
+        )));
+    }
+    if address == crud_link {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "Entry can not replace itself",
+        )));
+    }
+    if store.get_crud_status(address)? != CrudStatus::LIVE {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "Entry has already been modified or removed",
+        )));
+    }
+    if store.get_crud_status(crud_link)? != CrudStatus::LIVE {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "Entry can not be replaced by a modified or removed entry",
+        )));
+    }
+
+    let status_eav =
+        EntityAttributeValue::new(address, &STATUS_NAME.to_string(), &crud_status.address());
+    store.meta_storage_mut().add_eav(&status_eav)?;
+    let link_eav = EntityAttributeValue::new(address, &LINK_NAME.to_string(), crud_link);
+    store.meta_storage_mut().add_eav(&link_eav)
+}
+
 //
-pub(crate) fn reduce_add_link<CAS, EAVS>(
+pub(crate) fn reduce_update_entry<CAS, EAVS>(
     _context: Arc<Context>,
     old_store: &DhtStore<CAS, EAVS>,
     action_wrapper: &ActionWrapper,
@@ -200,30 +337,92 @@ where
 {
     // Get Action's input data
     let action = action_wrapper.action();
-    let link = unwrap_to!(action => Action::AddLink);
+    let (old_address, new_address) = unwrap_to!(action => Action::UpdateEntry);
 
     let mut new_store = (*old_store).clone();
+    let result = add_crud_meta(
+        &mut new_store,
+        old_address,
+        new_address,
+        CrudStatus::MODIFIED,
+    );
+    new_store
+        .update_entry_actions_mut()
+        .insert(action_wrapper.clone(), result);
+    Some(new_store)
+}
 
-    if !old_store.content_storage().contains(link.base()).unwrap() {
-        new_store.add_link_actions_mut().insert(
-            action_wrapper.clone(),
-            Err(HolochainError::ErrorGeneric(String::from(
-                "Base for link not found",
-            ))),
-        );
-        return Some(new_store);
-    }
+//
+pub(crate) fn reduce_remove_entry<CAS, EAVS>(
+    _context: Arc<Context>,
+    old_store: &DhtStore<CAS, EAVS>,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore<CAS, EAVS>>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
+    // Get Action's input data
+    let action = action_wrapper.action();
+    let (removed_address, deletion_address) = unwrap_to!(action => Action::RemoveEntry);
 
-    let eav =
-        EntityAttributeValue::new(link.base(), &format!("link__{}", link.tag()), link.target());
+    let mut new_store = (*old_store).clone();
+    let result = add_crud_meta(
+        &mut new_store,
+        removed_address,
+        deletion_address,
+        CrudStatus::DELETED,
+    );
+    new_store
+        .remove_entry_actions_mut()
+        .insert(action_wrapper.clone(), result);
+    Some(new_store)
+}
+
+//
+pub(crate) fn reduce_add_link<CAS, EAVS>(
+    _context: Arc<Context>,
+    old_store: &DhtStore<CAS, EAVS>,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore<CAS, EAVS>>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
+    // Get Action's input data
+    let action = action_wrapper.action();
+    let (link, instance) = unwrap_to!(action => Action::AddLink);
 
-    let result = new_store.meta_storage_mut().add_eav(&eav);
+    let mut new_store = (*old_store).clone();
+    let result = new_store.add_link(link, instance);
     new_store
         .add_link_actions_mut()
         .insert(action_wrapper.clone(), result);
     Some(new_store)
 }
 
+//
+pub(crate) fn reduce_remove_link<CAS, EAVS>(
+    _context: Arc<Context>,
+    old_store: &DhtStore<CAS, EAVS>,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore<CAS, EAVS>>
+where
+    CAS: ContentAddressableStorage + Sized + Clone + PartialEq,
+    EAVS: EntityAttributeValueStorage + Sized + Clone + PartialEq,
+{
+    // Get Action's input data
+    let action = action_wrapper.action();
+    let link = unwrap_to!(action => Action::RemoveLink);
+
+    let mut new_store = (*old_store).clone();
+    let result = new_store.remove_link(link);
+    new_store
+        .remove_link_actions_mut()
+        .insert(action_wrapper.clone(), result);
+    Some(new_store)
+}
+
 #[allow(dead_code)]
 pub(crate) fn reduce_get_links<CAS, EAVS>(
     _context: Arc<Context>,
@@ -240,6 +439,7 @@ where
 
 #[cfg(test)]
 pub mod tests {
+    extern crate test_utils;
 
     use action::{Action, ActionWrapper};
     use dht::{
@@ -248,15 +448,30 @@ pub mod tests {
     };
     use holochain_cas_implementations::eav::file::EavFileStorage;
     use holochain_core_types::{
-        cas::{content::AddressableContent, storage::ContentAddressableStorage},
+        cas::{
+            content::{Address, AddressableContent},
+            storage::ContentAddressableStorage,
+        },
+        crud_status::CrudStatus,
         eav::EntityAttributeValueStorage,
-        entry::{test_entry, test_sys_entry, test_unpublishable_entry, Entry},
+        entry::{
+            test_entry, test_entry_b, test_entry_unique, test_sys_entry, test_unpublishable_entry,
+            Entry,
+        },
+        entry_type::EntryType,
+        error::HolochainError,
+        json::RawString,
         links_entry::Link,
     };
-    use instance::tests::test_context;
+    use instance::tests::{test_context, test_instance_and_context};
     use state::test_store;
     use std::sync::{Arc, RwLock};
 
+    /// address of the chain header that would have committed a test link
+    fn test_instance() -> Address {
+        Address::from("link-instance")
+    }
+
     #[test]
     fn commit_sys_entry_test() {
         let context = test_context("bob");
@@ -301,6 +516,44 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn commit_bundle_holds_nothing_if_it_can_not_hold_every_entry() {
+        let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (_instance, context) =
+            test_instance_and_context(dna).expect("Could not initialize test instance");
+        let entry = test_entry_unique();
+        let undefined_entry = Entry::new(
+            EntryType::App(String::from("undefinedEntryType")),
+            RawString::from("not in the DNA"),
+        );
+
+        let action = ActionWrapper::new(Action::CommitBundle(vec![entry.clone(), undefined_entry]));
+        let store = context.state().unwrap().dht();
+        let new_dht_store = reduce(Arc::clone(&context), store, &action);
+        assert_eq!(
+            Some(&Err(HolochainError::ErrorGeneric(String::from(
+                "Entry type undefinedEntryType is not defined in the DNA"
+            )))),
+            new_dht_store.commit_bundle_actions().get(&action)
+        );
+        assert_eq!(
+            Ok(false),
+            new_dht_store.content_storage().contains(&entry.address())
+        );
+
+        // without the undefined entry the bundle gets held
+        let action = ActionWrapper::new(Action::CommitBundle(vec![entry.clone()]));
+        let new_dht_store = reduce(Arc::clone(&context), new_dht_store, &action);
+        assert_eq!(
+            Some(&Ok(())),
+            new_dht_store.commit_bundle_actions().get(&action)
+        );
+        assert_eq!(
+            Ok(true),
+            new_dht_store.content_storage().contains(&entry.address())
+        );
+    }
+
     #[test]
     fn can_add_links() {
         let context = test_context("bob");
@@ -315,7 +568,7 @@ pub mod tests {
         let context = Arc::new(context);
 
         let link = Link::new(&entry.address(), &entry.address(), "test-tag");
-        let action = ActionWrapper::new(Action::AddLink(link.clone()));
+        let action = ActionWrapper::new(Action::AddLink((link.clone(), test_instance())));
 
         let new_dht_store: DhtStore<_, EavFileStorage>;
         {
@@ -350,7 +603,7 @@ pub mod tests {
         let context = Arc::new(context);
 
         let link = Link::new(&entry.address(), &entry.address(), "test-tag");
-        let action = ActionWrapper::new(Action::AddLink(link.clone()));
+        let action = ActionWrapper::new(Action::AddLink((link.clone(), test_instance())));
 
         let new_dht_store: DhtStore<_, EavFileStorage>;
         {
@@ -372,4 +625,191 @@ pub mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn can_remove_links() {
+        let context = test_context("bob");
+        let store = test_store(context.clone());
+        let entry = test_entry();
+
+        let locked_state = Arc::new(RwLock::new(store));
+
+        let mut context = (*context).clone();
+        context.set_state(locked_state.clone());
+        let _ = context.file_storage.add(&entry);
+        let context = Arc::new(context);
+
+        let link = Link::new(&entry.address(), &entry.address(), "test-tag");
+        let add = ActionWrapper::new(Action::AddLink((link.clone(), test_instance())));
+        let remove = ActionWrapper::new(Action::RemoveLink(link.clone()));
+
+        let new_dht_store: DhtStore<_, EavFileStorage>;
+        {
+            let state = locked_state.read().unwrap();
+
+            let added_store = reduce(Arc::clone(&context), state.dht(), &add);
+            new_dht_store = (*reduce(Arc::clone(&context), added_store, &remove)).clone();
+        }
+
+        let result = new_dht_store.remove_link_actions().get(&remove).unwrap();
+        assert!(result.is_ok(), "result = {:?}", result);
+
+        // removed links are hidden from get_links
+        let links = new_dht_store
+            .get_links(entry.address(), "test-tag".into())
+            .unwrap();
+        assert_eq!(links.len(), 0);
+
+        // but the link, its instance and the tombstone are still in the meta storage
+        let fetched = new_dht_store
+            .meta_storage()
+            .fetch_eav(None, None, Some(test_instance()))
+            .unwrap();
+        assert_eq!(fetched.len(), 2);
+        assert_eq!(
+            Ok(vec![(entry.address(), CrudStatus::DELETED)]),
+            new_dht_store.get_link_targets(entry.address(), "test-tag".into())
+        );
+
+        // a removed link can not be removed again...
+        let remove_again = ActionWrapper::new(Action::RemoveLink(link.clone()));
+        let newer_dht_store = reduce(Arc::clone(&context), Arc::new(new_dht_store), &remove_again);
+        assert!(newer_dht_store
+            .remove_link_actions()
+            .get(&remove_again)
+            .unwrap()
+            .is_err());
+
+        // ...but it can be added again, by a new instance
+        let add_again = ActionWrapper::new(Action::AddLink((
+            link.clone(),
+            Address::from("another-instance"),
+        )));
+        let newer_dht_store = reduce(Arc::clone(&context), newer_dht_store, &add_again);
+        let result = newer_dht_store.add_link_actions().get(&add_again).unwrap();
+        assert!(result.is_ok(), "result = {:?}", result);
+        assert_eq!(
+            Ok(vec![(entry.address(), CrudStatus::LIVE)]),
+            newer_dht_store.get_link_targets(entry.address(), "test-tag".into())
+        );
+    }
+
+    #[test]
+    fn can_update_entry() {
+        let context = test_context("bob");
+        let store = test_store(context.clone());
+        let entry = test_entry();
+        let new_entry = test_entry_b();
+
+        let locked_state = Arc::new(RwLock::new(store));
+
+        let mut context = (*context).clone();
+        context.set_state(locked_state.clone());
+        let _ = context.file_storage.add(&entry);
+        let _ = context.file_storage.add(&new_entry);
+        let context = Arc::new(context);
+
+        let action =
+            ActionWrapper::new(Action::UpdateEntry((entry.address(), new_entry.address())));
+
+        let new_dht_store: DhtStore<_, EavFileStorage>;
+        {
+            let state = locked_state.read().unwrap();
+
+            new_dht_store = (*reduce(Arc::clone(&context), state.dht(), &action)).clone();
+        }
+
+        let result = new_dht_store.update_entry_actions().get(&action).unwrap();
+        assert!(result.is_ok(), "result = {:?}", result);
+        assert_eq!(
+            Ok(CrudStatus::MODIFIED),
+            new_dht_store.get_crud_status(&entry.address())
+        );
+        assert_eq!(
+            Ok(Some(new_entry.address())),
+            new_dht_store.get_crud_link(&entry.address())
+        );
+        assert_eq!(
+            Ok(CrudStatus::LIVE),
+            new_dht_store.get_crud_status(&new_entry.address())
+        );
+    }
+
+    #[test]
+    fn can_remove_entry() {
+        let context = test_context("bob");
+        let store = test_store(context.clone());
+        let entry = test_entry();
+        let deletion_entry = test_entry_b();
+
+        let locked_state = Arc::new(RwLock::new(store));
+
+        let mut context = (*context).clone();
+        context.set_state(locked_state.clone());
+        let _ = context.file_storage.add(&entry);
+        let _ = context.file_storage.add(&deletion_entry);
+        let context = Arc::new(context);
+
+        let action = ActionWrapper::new(Action::RemoveEntry((
+            entry.address(),
+            deletion_entry.address(),
+        )));
+
+        let new_dht_store: DhtStore<_, EavFileStorage>;
+        {
+            let state = locked_state.read().unwrap();
+
+            new_dht_store = (*reduce(Arc::clone(&context), state.dht(), &action)).clone();
+        }
+
+        let result = new_dht_store.remove_entry_actions().get(&action).unwrap();
+        assert!(result.is_ok(), "result = {:?}", result);
+        assert_eq!(
+            Ok(CrudStatus::DELETED),
+            new_dht_store.get_crud_status(&entry.address())
+        );
+
+        // a removed entry can not be updated anymore
+        let update = ActionWrapper::new(Action::UpdateEntry((
+            entry.address(),
+            deletion_entry.address(),
+        )));
+        let newer_dht_store = reduce(Arc::clone(&context), Arc::new(new_dht_store), &update);
+        let result = newer_dht_store.update_entry_actions().get(&update).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn does_not_update_missing_entry() {
+        let context = test_context("bob");
+        let store = test_store(context.clone());
+        let entry = test_entry();
+        let new_entry = test_entry_b();
+
+        let locked_state = Arc::new(RwLock::new(store));
+
+        let mut context = (*context).clone();
+        context.set_state(locked_state.clone());
+        let context = Arc::new(context);
+
+        let action =
+            ActionWrapper::new(Action::UpdateEntry((entry.address(), new_entry.address())));
+
+        let new_dht_store: DhtStore<_, EavFileStorage>;
+        {
+            let state = locked_state.read().unwrap();
+
+            new_dht_store = (*reduce(Arc::clone(&context), state.dht(), &action)).clone();
+        }
+
+        let result = new_dht_store.update_entry_actions().get(&action).unwrap();
+        assert_eq!(
+            &Err(HolochainError::ErrorGeneric(String::from("Entry not found"))),
+            result
+        );
+        assert_eq!(
+            Ok(CrudStatus::LIVE),
+            new_dht_store.get_crud_status(&entry.address())
+        );
+    }
+
 }
@@ -4,10 +4,12 @@ use holochain_core_types::{
         content::{Address, AddressableContent, Content},
         storage::ContentAddressableStorage,
     },
+    crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
     eav::{EntityAttributeValue, EntityAttributeValueStorage},
+    entry::ToEntry,
     error::HolochainError,
     hash::HashString,
-    links_entry::Link,
+    links_entry::{Link, LinkActionKind, LinkEntry},
 };
 use std::collections::{HashMap, HashSet};
 
@@ -30,6 +32,25 @@ impl Network {
     }
 }
 
+/// EAV attribute under which links with the given tag are stored
+fn link_attribute(tag: &str) -> String {
+    format!("link__{}", tag)
+}
+
+/// EAV attribute under which every time a link got added is stored
+const LINK_INSTANCE_NAME: &str = "link_instance";
+
+/// EAV attribute under which the tombstones of removed link instances are stored
+const REMOVED_LINK_INSTANCE_NAME: &str = "removed_link_instance";
+
+/// Address of the LinkEntry that adds the given link.
+/// It is the same for every time the link gets added, so it keys the instances of the link.
+fn link_address(link: &Link) -> Address {
+    LinkEntry::from_link(LinkActionKind::ADD, link)
+        .to_entry()
+        .address()
+}
+
 /// The state-slice for the DHT.
 /// Holds the agent's local shard and interacts with the network module
 #[derive(Clone, Debug, PartialEq)]
@@ -44,7 +65,11 @@ where
     // Placeholder network module
     network: Network,
 
+    commit_bundle_actions: HashMap<ActionWrapper, Result<(), HolochainError>>,
     add_link_actions: HashMap<ActionWrapper, Result<(), HolochainError>>,
+    remove_link_actions: HashMap<ActionWrapper, Result<(), HolochainError>>,
+    update_entry_actions: HashMap<ActionWrapper, Result<(), HolochainError>>,
+    remove_entry_actions: HashMap<ActionWrapper, Result<(), HolochainError>>,
 }
 
 impl<CAS, EAVS> DhtStore<CAS, EAVS>
@@ -60,28 +85,197 @@ where
             content_storage,
             meta_storage,
             network,
+            commit_bundle_actions: HashMap::new(),
             add_link_actions: HashMap::new(),
+            remove_link_actions: HashMap::new(),
+            update_entry_actions: HashMap::new(),
+            remove_entry_actions: HashMap::new(),
         }
     }
 
     // Linking
     // =======
-    pub fn add_link(&mut self, _link: &Link) -> Result<(), HolochainError> {
-        // FIXME
-        Err(HolochainError::NotImplemented)
+    /// Adds the link as EAV to the meta storage, together with the given instance,
+    /// the address of the chain header that committed the LinkEntry adding the link.
+    /// The base of the link has to be in the local content storage.
+    /// A link that got removed can be added again, as a new instance.
+    pub fn add_link(&mut self, link: &Link, instance: &Address) -> Result<(), HolochainError> {
+        if !self.content_storage.contains(link.base())? {
+            return Err(HolochainError::ErrorGeneric(String::from(
+                "Base for link not found",
+            )));
+        }
+        if self
+            .fetch_link_instances(link, REMOVED_LINK_INSTANCE_NAME)?
+            .contains(instance)
+        {
+            return Err(HolochainError::ErrorGeneric(String::from(
+                "Link instance has been removed already",
+            )));
+        }
+        let eav =
+            EntityAttributeValue::new(link.base(), &link_attribute(link.tag()), link.target());
+        self.meta_storage.add_eav(&eav)?;
+        let instance_eav = EntityAttributeValue::new(
+            &link_address(link),
+            &LINK_INSTANCE_NAME.to_string(),
+            instance,
+        );
+        self.meta_storage.add_eav(&instance_eav)
     }
 
-    pub fn remove_link(&mut self) {
-        // FIXME
+    /// Retracts a link by adding a tombstone EAV for each of its live instances.
+    /// The meta storage is append only, so the instances and their tombstones stay around
+    /// for auditing, but removed links are no longer returned by get_links.
+    pub fn remove_link(&mut self, link: &Link) -> Result<(), HolochainError> {
+        let instances = self.get_live_link_instances(link)?;
+        if instances.is_empty() {
+            return Err(HolochainError::ErrorGeneric(String::from(
+                "Link to remove not found",
+            )));
+        }
+        for instance in instances {
+            let tombstone = EntityAttributeValue::new(
+                &link_address(link),
+                &REMOVED_LINK_INSTANCE_NAME.to_string(),
+                &instance,
+            );
+            self.meta_storage.add_eav(&tombstone)?;
+        }
+        Ok(())
     }
 
+    fn fetch_link_instances(
+        &self,
+        link: &Link,
+        attribute: &str,
+    ) -> Result<HashSet<Address>, HolochainError> {
+        Ok(self
+            .meta_storage
+            .fetch_eav(Some(link_address(link)), Some(attribute.to_string()), None)?
+            .iter()
+            .map(|eav| eav.value())
+            .collect())
+    }
+
+    /// Returns the instances of the link that have not been removed.
+    fn get_live_link_instances(&self, link: &Link) -> Result<HashSet<Address>, HolochainError> {
+        let removed = self.fetch_link_instances(link, REMOVED_LINK_INSTANCE_NAME)?;
+        Ok(self
+            .fetch_link_instances(link, LINK_INSTANCE_NAME)?
+            .into_iter()
+            .filter(|instance| !removed.contains(instance))
+            .collect())
+    }
+
+    fn is_link_live(
+        &self,
+        base: &Address,
+        tag: &str,
+        target: &Address,
+    ) -> Result<bool, HolochainError> {
+        Ok(!self
+            .get_live_link_instances(&Link::new(base, target, tag))?
+            .is_empty())
+    }
+
+    /// Returns the EAVs of all links from the given address with the given tag
+    /// that have not been removed.
     pub fn get_links(
         &self,
         address: HashString,
         tag: String,
     ) -> Result<HashSet<EntityAttributeValue>, HolochainError> {
-        self.meta_storage
-            .fetch_eav(Some(address), Some(format!("link__{}", tag)), None)
+        let eavs =
+            self.meta_storage
+                .fetch_eav(Some(address.clone()), Some(link_attribute(&tag)), None)?;
+        let mut links = HashSet::new();
+        for eav in eavs {
+            if self.is_link_live(&address, &tag, &eav.value())? {
+                links.insert(eav);
+            }
+        }
+        Ok(links)
+    }
+
+    /// Returns the targets of all links from the given address with the given tag,
+    /// ordered by address, together with the status of the link.
+    /// Removed links are included with the status DELETED.
+    pub fn get_link_targets(
+        &self,
+        address: HashString,
+        tag: String,
+    ) -> Result<Vec<(Address, CrudStatus)>, HolochainError> {
+        let eavs =
+            self.meta_storage
+                .fetch_eav(Some(address.clone()), Some(link_attribute(&tag)), None)?;
+        let mut targets = Vec::new();
+        for eav in eavs {
+            let target = eav.value();
+            let status = if self.is_link_live(&address, &tag, &target)? {
+                CrudStatus::LIVE
+            } else {
+                CrudStatus::DELETED
+            };
+            targets.push((target, status));
+        }
+        targets.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(targets)
+    }
+
+    // CRUD
+    // ====
+    /// Returns the CRUD status of the entry at the given address as recorded in the meta storage.
+    /// Entries without any status metadata are LIVE.
+    pub fn get_crud_status(&self, address: &Address) -> Result<CrudStatus, HolochainError> {
+        let statuses: HashSet<Address> = self
+            .meta_storage
+            .fetch_eav(Some(address.clone()), Some(STATUS_NAME.to_string()), None)?
+            .iter()
+            .map(|eav| eav.value())
+            .collect();
+        // DELETED wins over MODIFIED, an entry that got removed can not be followed anymore
+        if statuses.contains(&CrudStatus::DELETED.address()) {
+            Ok(CrudStatus::DELETED)
+        } else if statuses.contains(&CrudStatus::MODIFIED.address()) {
+            Ok(CrudStatus::MODIFIED)
+        } else {
+            Ok(CrudStatus::LIVE)
+        }
+    }
+
+    /// Returns the address the crud-link of the entry at the given address points to, if any.
+    /// For a MODIFIED entry that is the address of its new version.
+    pub fn get_crud_link(&self, address: &Address) -> Result<Option<Address>, HolochainError> {
+        Ok(self
+            .meta_storage
+            .fetch_eav(Some(address.clone()), Some(LINK_NAME.to_string()), None)?
+            .iter()
+            .map(|eav| eav.value())
+            .next())
+    }
+
+    /// Follows the crud-links of MODIFIED entries starting at the given address.
+    /// Returns the address of the latest version of the entry, whatever its status.
+    pub fn get_latest_version(&self, address: &Address) -> Result<Address, HolochainError> {
+        let mut latest = address.clone();
+        while self.get_crud_status(&latest)? == CrudStatus::MODIFIED {
+            match self.get_crud_link(&latest)? {
+                Some(new_address) => latest = new_address,
+                None => break,
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Returns the address of the latest version of the entry, or None if it has been removed.
+    pub fn get_latest_address(&self, address: &Address) -> Result<Option<Address>, HolochainError> {
+        let latest = self.get_latest_version(address)?;
+        if self.get_crud_status(&latest)? == CrudStatus::DELETED {
+            Ok(None)
+        } else {
+            Ok(Some(latest))
+        }
     }
 
     // Getters (for reducers)
@@ -104,6 +298,14 @@ where
     pub(crate) fn network_mut(&mut self) -> &mut Network {
         &mut self.network
     }
+    pub fn commit_bundle_actions(&self) -> &HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &self.commit_bundle_actions
+    }
+    pub(crate) fn commit_bundle_actions_mut(
+        &mut self,
+    ) -> &mut HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &mut self.commit_bundle_actions
+    }
     pub fn add_link_actions(&self) -> &HashMap<ActionWrapper, Result<(), HolochainError>> {
         &self.add_link_actions
     }
@@ -112,4 +314,28 @@ where
     ) -> &mut HashMap<ActionWrapper, Result<(), HolochainError>> {
         &mut self.add_link_actions
     }
+    pub fn remove_link_actions(&self) -> &HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &self.remove_link_actions
+    }
+    pub(crate) fn remove_link_actions_mut(
+        &mut self,
+    ) -> &mut HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &mut self.remove_link_actions
+    }
+    pub fn update_entry_actions(&self) -> &HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &self.update_entry_actions
+    }
+    pub(crate) fn update_entry_actions_mut(
+        &mut self,
+    ) -> &mut HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &mut self.update_entry_actions
+    }
+    pub fn remove_entry_actions(&self) -> &HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &self.remove_entry_actions
+    }
+    pub(crate) fn remove_entry_actions_mut(
+        &mut self,
+    ) -> &mut HashMap<ActionWrapper, Result<(), HolochainError>> {
+        &mut self.remove_entry_actions
+    }
 }
@@ -0,0 +1,342 @@
+//! Bounded worker pools that run zome calls, callbacks and validations,
+//! instead of spawning a new OS thread for each of them.
+//!
+//! There are two pools so that zome calls waiting for the validation of their commits
+//! can't take away the workers those validations need.
+//! Each pool starts workers on demand up to its size and queues jobs while all of them
+//! are busy. Once the queue is full further jobs are rejected with
+//! HolochainError::WorkerPoolBusy so that callers can back off.
+
+use holochain_core_types::error::HolochainError;
+use std::{
+    collections::VecDeque,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+};
+
+/// Configuration of the worker pools of an instance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutorConfig {
+    /// maximum number of zome calls and genesis callbacks running at the same time
+    pub zome_call_workers: usize,
+    /// maximum number of validations and validation package builds running at the same time
+    pub validation_workers: usize,
+    /// maximum number of jobs waiting for a worker, per pool
+    pub max_queue_depth: usize,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        ExecutorConfig {
+            zome_call_workers: 4,
+            validation_workers: 4,
+            max_queue_depth: 100,
+        }
+    }
+}
+
+/// The pools of an Executor
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Pool {
+    /// zome function calls and genesis callbacks
+    ZomeCalls,
+    /// validation callbacks and validation package builds
+    Validation,
+}
+
+impl fmt::Display for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pool::ZomeCalls => write!(f, "zome call"),
+            Pool::Validation => write!(f, "validation"),
+        }
+    }
+}
+
+/// Snapshot of the load of a pool
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PoolMetrics {
+    pub max_workers: usize,
+    pub max_queue_depth: usize,
+    /// workers started so far, busy or idle
+    pub workers: usize,
+    /// workers waiting for a job
+    pub idle_workers: usize,
+    /// jobs waiting for a worker
+    pub queued: usize,
+    /// jobs run to the end, including those that panicked
+    pub completed: u64,
+    /// jobs rejected because the queue was full
+    pub rejected: u64,
+}
+
+/// Boxed FnOnce that can be called, Box<FnOnce()> can't be on stable Rust yet.
+trait Job: Send {
+    fn run(self: Box<Self>);
+}
+
+impl<F: FnOnce() + Send> Job for F {
+    fn run(self: Box<Self>) {
+        (*self)()
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    queue: VecDeque<Box<dyn Job>>,
+    workers: usize,
+    idle_workers: usize,
+    completed: u64,
+    rejected: u64,
+    shutdown: bool,
+}
+
+struct WorkerPool {
+    pool: Pool,
+    max_workers: usize,
+    max_queue_depth: usize,
+    shared: Arc<(Mutex<PoolState>, Condvar)>,
+}
+
+impl WorkerPool {
+    fn new(pool: Pool, max_workers: usize, max_queue_depth: usize) -> Self {
+        WorkerPool {
+            pool,
+            // a pool without workers would never run anything
+            max_workers: max_workers.max(1),
+            max_queue_depth,
+            shared: Arc::new((Mutex::new(PoolState::default()), Condvar::new())),
+        }
+    }
+
+    fn spawn(&self, job: Box<dyn Job>) -> Result<(), HolochainError> {
+        let (ref lock, ref condvar) = *self.shared;
+        let mut state = lock.lock().expect("pool state should not be poisoned");
+
+        if state.idle_workers == 0 && state.workers < self.max_workers {
+            state.workers += 1;
+            state.idle_workers += 1;
+            let shared = self.shared.clone();
+            thread::spawn(move || work(&shared));
+        } else if waiting(&state) >= self.max_queue_depth {
+            state.rejected += 1;
+            return Err(HolochainError::WorkerPoolBusy(format!(
+                "the {} pool is busy, {} jobs are waiting already",
+                self.pool,
+                waiting(&state)
+            )));
+        }
+
+        state.queue.push_back(job);
+        condvar.notify_one();
+        Ok(())
+    }
+
+    fn metrics(&self) -> PoolMetrics {
+        let (ref lock, _) = *self.shared;
+        let state = lock.lock().expect("pool state should not be poisoned");
+        PoolMetrics {
+            max_workers: self.max_workers,
+            max_queue_depth: self.max_queue_depth,
+            workers: state.workers,
+            idle_workers: state.idle_workers,
+            queued: waiting(&state),
+            completed: state.completed,
+            rejected: state.rejected,
+        }
+    }
+}
+
+impl Drop for WorkerPool {
+    /// Lets the workers run the jobs that are queued already and stop after that.
+    fn drop(&mut self) {
+        let (ref lock, ref condvar) = *self.shared;
+        if let Ok(mut state) = lock.lock() {
+            state.shutdown = true;
+        }
+        condvar.notify_all();
+    }
+}
+
+/// Number of queued jobs, without those that idle workers are about to pick up
+fn waiting(state: &PoolState) -> usize {
+    state.queue.len().saturating_sub(state.idle_workers)
+}
+
+/// Worker loop: takes jobs from the queue until the pool shuts down
+fn work(shared: &Arc<(Mutex<PoolState>, Condvar)>) {
+    let (ref lock, ref condvar) = **shared;
+    loop {
+        let job = {
+            let mut state = lock.lock().expect("pool state should not be poisoned");
+            loop {
+                if let Some(job) = state.queue.pop_front() {
+                    state.idle_workers -= 1;
+                    break job;
+                }
+                if state.shutdown {
+                    state.workers -= 1;
+                    state.idle_workers -= 1;
+                    return;
+                }
+                state = condvar
+                    .wait(state)
+                    .expect("pool state should not be poisoned");
+            }
+        };
+
+        // a panicking job must not take the worker down with it
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| job.run()));
+
+        let mut state = lock.lock().expect("pool state should not be poisoned");
+        state.completed += 1;
+        state.idle_workers += 1;
+    }
+}
+
+/// Runs the jobs of an instance on bounded worker pools, shared by all clones of its context.
+pub struct Executor {
+    zome_calls: WorkerPool,
+    validation: WorkerPool,
+}
+
+impl Executor {
+    pub fn new(config: ExecutorConfig) -> Self {
+        Executor {
+            zome_calls: WorkerPool::new(
+                Pool::ZomeCalls,
+                config.zome_call_workers,
+                config.max_queue_depth,
+            ),
+            validation: WorkerPool::new(
+                Pool::Validation,
+                config.validation_workers,
+                config.max_queue_depth,
+            ),
+        }
+    }
+
+    fn worker_pool(&self, pool: Pool) -> &WorkerPool {
+        match pool {
+            Pool::ZomeCalls => &self.zome_calls,
+            Pool::Validation => &self.validation,
+        }
+    }
+
+    /// Schedules a job on the given pool.
+    /// Fails with HolochainError::WorkerPoolBusy if the queue of the pool is full.
+    pub fn spawn<F>(&self, pool: Pool, job: F) -> Result<(), HolochainError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.worker_pool(pool).spawn(Box::new(job))
+    }
+
+    pub fn metrics(&self, pool: Pool) -> PoolMetrics {
+        self.worker_pool(pool).metrics()
+    }
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Executor::new(ExecutorConfig::default())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::{
+        sync::mpsc::{channel, Receiver, Sender},
+        time::Duration,
+    };
+
+    /// job that runs until something is sent on the returned channel
+    fn blocking_job() -> (impl FnOnce() + Send + 'static, Sender<()>) {
+        let (sender, receiver): (Sender<()>, Receiver<()>) = channel();
+        (
+            move || {
+                let _ = receiver.recv();
+            },
+            sender,
+        )
+    }
+
+    fn wait_for_completed(executor: &Executor, pool: Pool, completed: u64) {
+        for _ in 0..100 {
+            if executor.metrics(pool).completed >= completed {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("jobs did not complete: {:?}", executor.metrics(pool));
+    }
+
+    #[test]
+    fn runs_jobs_on_pool() {
+        let executor = Executor::default();
+        let (sender, receiver) = channel();
+
+        executor
+            .spawn(Pool::ZomeCalls, move || sender.send(42).unwrap())
+            .expect("job should be scheduled");
+
+        assert_eq!(Ok(42), receiver.recv_timeout(Duration::from_secs(1)));
+        wait_for_completed(&executor, Pool::ZomeCalls, 1);
+        assert_eq!(0, executor.metrics(Pool::Validation).workers);
+    }
+
+    #[test]
+    fn rejects_jobs_when_queue_is_full() {
+        let executor = Executor::new(ExecutorConfig {
+            zome_call_workers: 1,
+            validation_workers: 1,
+            max_queue_depth: 1,
+        });
+        let (running, release_running) = blocking_job();
+        let (queued, release_queued) = blocking_job();
+
+        executor.spawn(Pool::ZomeCalls, running).unwrap();
+        executor.spawn(Pool::ZomeCalls, queued).unwrap();
+        assert_eq!(
+            Err(HolochainError::WorkerPoolBusy(
+                "the zome call pool is busy, 1 jobs are waiting already".to_string()
+            )),
+            executor.spawn(Pool::ZomeCalls, || ())
+        );
+        // the other pool is not affected
+        assert_eq!(Ok(()), executor.spawn(Pool::Validation, || ()));
+
+        let metrics = executor.metrics(Pool::ZomeCalls);
+        assert_eq!(1, metrics.workers);
+        assert_eq!(1, metrics.queued);
+        assert_eq!(1, metrics.rejected);
+
+        release_running.send(()).unwrap();
+        release_queued.send(()).unwrap();
+        wait_for_completed(&executor, Pool::ZomeCalls, 2);
+        assert_eq!(0, executor.metrics(Pool::ZomeCalls).queued);
+    }
+
+    #[test]
+    fn keeps_working_after_panic() {
+        let executor = Executor::new(ExecutorConfig {
+            zome_call_workers: 1,
+            validation_workers: 1,
+            max_queue_depth: 10,
+        });
+        let (sender, receiver) = channel();
+
+        executor
+            .spawn(Pool::Validation, || panic!("job panicked"))
+            .unwrap();
+        executor
+            .spawn(Pool::Validation, move || sender.send(()).unwrap())
+            .unwrap();
+
+        assert_eq!(Ok(()), receiver.recv_timeout(Duration::from_secs(1)));
+        assert_eq!(1, executor.metrics(Pool::Validation).workers);
+    }
+}
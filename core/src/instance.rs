@@ -1,17 +1,22 @@
 use action::ActionWrapper;
 use context::Context;
+use holochain_core_types::error::HolochainError;
 use state::State;
 use std::{
     sync::{
-        mpsc::{sync_channel, Receiver, SyncSender},
-        Arc, RwLock, RwLockReadGuard,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender},
+        Arc, Mutex, RwLock, RwLockReadGuard,
     },
-    thread,
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
 pub const RECV_DEFAULT_TIMEOUT_MS: Duration = Duration::from_millis(10000);
 
+/// How often the action loop checks whether it got stopped while no actions come in
+const ACTION_LOOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 /// Object representing a Holochain instance, i.e. a running holochain (DNA + DHT + source-chain)
 /// Holds the Event loop and processes it with the redux pattern.
 #[derive(Clone)]
@@ -20,6 +25,10 @@ pub struct Instance {
     state: Arc<RwLock<State>>,
     action_channel: SyncSender<ActionWrapper>,
     observer_channel: SyncSender<Observer>,
+    /// Set to stop the action loop
+    kill_switch: Arc<AtomicBool>,
+    /// Thread of the running action loop, if any
+    action_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
 }
 
 type ClosureType = Box<FnMut(&State) -> bool + Send>;
@@ -55,13 +64,29 @@ impl Instance {
         dispatch_action(&self.action_channel, action_wrapper)
     }
 
-    /// Stack an Action in the Event Queue and block until is has been processed.
-    ///
-    /// # Panics
-    ///
-    /// Panics if called before `start_action_loop`.
-    pub fn dispatch_and_wait(&mut self, action_wrapper: ActionWrapper) {
-        dispatch_action_and_wait(&self.action_channel, &self.observer_channel, action_wrapper);
+    /// Stack an Action in the Event Queue and block until is has been processed,
+    /// but not longer than RECV_DEFAULT_TIMEOUT_MS.
+    /// Fails if called before `start_action_loop`.
+    pub fn dispatch_and_wait(
+        &mut self,
+        action_wrapper: ActionWrapper,
+    ) -> Result<(), HolochainError> {
+        dispatch_action_and_wait(&self.action_channel, &self.observer_channel, action_wrapper)
+    }
+
+    /// Stack an Action in the Event Queue and block until is has been processed,
+    /// but not longer than the given timeout.
+    pub fn dispatch_and_wait_with_timeout(
+        &mut self,
+        action_wrapper: ActionWrapper,
+        timeout: Duration,
+    ) -> Result<(), HolochainError> {
+        dispatch_action_and_wait_with_timeout(
+            &self.action_channel,
+            &self.observer_channel,
+            action_wrapper,
+            timeout,
+        )
     }
 
     /// Stack an action in the Event Queue and create an Observer on it with the specified closure
@@ -104,21 +129,58 @@ impl Instance {
     /// Start the Event Loop on a seperate thread
     pub fn start_action_loop(&mut self, context: Arc<Context>) {
         let (rx_action, rx_observer) = self.initialize_channels();
+        self.kill_switch = Arc::new(AtomicBool::new(false));
 
         let sync_self = self.clone();
         let sub_context = self.initialize_context(context);
+        let kill_switch = self.kill_switch.clone();
 
-        thread::spawn(move || {
+        let action_thread = thread::spawn(move || {
             let mut state_observers: Vec<Observer> = Vec::new();
-            for action_wrapper in rx_action {
-                state_observers = sync_self.process_action(
-                    action_wrapper,
-                    state_observers,
-                    &rx_observer,
-                    &sub_context,
-                );
+            // every clone of the context holds a sender, so the channel alone never disconnects
+            while !kill_switch.load(Ordering::SeqCst) {
+                match rx_action.recv_timeout(ACTION_LOOP_POLL_INTERVAL) {
+                    Ok(action_wrapper) => {
+                        state_observers = sync_self.process_action(
+                            action_wrapper,
+                            state_observers,
+                            &rx_observer,
+                            &sub_context,
+                        );
+                    }
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
             }
         });
+        *self
+            .action_thread
+            .lock()
+            .expect("owners of the action thread Mutex shouldn't panic") = Some(action_thread);
+    }
+
+    /// Stops the Event Loop and blocks until its thread has finished.
+    /// Actions still queued are dropped together with all observers,
+    /// so everybody waiting on an observer gets an error instead of a result.
+    /// Does nothing if the loop is not running.
+    ///
+    /// # Panics
+    ///
+    /// Must not be called from within a reducer or observer,
+    /// as the loop thread would wait for itself.
+    pub fn stop_action_loop(&mut self) -> Result<(), HolochainError> {
+        self.kill_switch.store(true, Ordering::SeqCst);
+        let maybe_action_thread = self
+            .action_thread
+            .lock()
+            .expect("owners of the action thread Mutex shouldn't panic")
+            .take();
+        match maybe_action_thread {
+            Some(action_thread) => action_thread
+                .join()
+                .map_err(|_| HolochainError::ErrorGeneric(String::from("action loop panicked"))),
+            None => Ok(()),
+        }
     }
 
     /// Calls the reducers for an action and calls the observers with the new state
@@ -142,7 +204,7 @@ impl Instance {
                     .expect("owners of the state RwLock shouldn't panic");
 
                 // Create new state by reducing the action on old state
-                new_state = state.reduce(context.clone(), action_wrapper);
+                new_state = state.reduce(context.clone(), action_wrapper.clone());
             }
 
             // Get write lock
@@ -155,6 +217,18 @@ impl Instance {
             *state = new_state;
         }
 
+        // Record the action together with the agent's response to it
+        if let Some(journal) = context.journal() {
+            let result = self.state().agent().actions().get(&action_wrapper).cloned();
+            let recorded = journal
+                .lock()
+                .expect("owners of the journal Mutex shouldn't panic")
+                .record(&action_wrapper, result);
+            if let Err(error) = recorded {
+                let _ = context.log(&format!("could not record action in journal: {}", error));
+            }
+        }
+
         // Add new observers
         state_observers.extend(rx_observer.try_iter());
 
@@ -184,6 +258,8 @@ impl Instance {
             state: Arc::new(RwLock::new(State::new(context))),
             action_channel: tx_action,
             observer_channel: tx_observer,
+            kill_switch: Arc::new(AtomicBool::new(false)),
+            action_thread: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -194,6 +270,8 @@ impl Instance {
             state: Arc::new(RwLock::new(state)),
             action_channel: tx_action,
             observer_channel: tx_observer,
+            kill_switch: Arc::new(AtomicBool::new(false)),
+            action_thread: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -210,38 +288,109 @@ impl Instance {
     }
 }*/
 
-/// Send Action to Instance's Event Queue and block until is has been processed.
-///
-/// # Panics
-///
-/// Panics if the channels passed are disconnected.
+/// Send Action to Instance's Event Queue and block until is has been processed,
+/// but not longer than RECV_DEFAULT_TIMEOUT_MS.
+/// Fails with HolochainError::Timeout if the action did not get processed in time,
+/// and with HolochainError::ErrorGeneric if the channels passed are disconnected.
 pub fn dispatch_action_and_wait(
     action_channel: &SyncSender<ActionWrapper>,
     observer_channel: &SyncSender<Observer>,
     action_wrapper: ActionWrapper,
-) {
+) -> Result<(), HolochainError> {
+    dispatch_action_and_wait_with_timeout(
+        action_channel,
+        observer_channel,
+        action_wrapper,
+        RECV_DEFAULT_TIMEOUT_MS,
+    )
+}
+
+/// Send Action to Instance's Event Queue and block until is has been processed,
+/// but not longer than the given timeout.
+/// Fails with HolochainError::Timeout if the action did not get processed in time.
+pub fn dispatch_action_and_wait_with_timeout(
+    action_channel: &SyncSender<ActionWrapper>,
+    observer_channel: &SyncSender<Observer>,
+    action_wrapper: ActionWrapper,
+    timeout: Duration,
+) -> Result<(), HolochainError> {
+    let observer_action_wrapper = action_wrapper.clone();
+    dispatch_action_and_wait_for(
+        action_channel,
+        observer_channel,
+        action_wrapper,
+        Some(timeout),
+        move |state: &State| {
+            if state.history.contains(&observer_action_wrapper) {
+                Some(())
+            } else {
+                None
+            }
+        },
+    )
+}
+
+/// Send Action to the Event Queue and block until the given closure finds what it is
+/// waiting for in the State, i.e. returns Some(value), and return that value.
+/// Waits for ever if no timeout is given, otherwise fails with HolochainError::Timeout
+/// once the timeout has passed.
+/// An observer whose waiter gave up is removed with the next action that gets processed,
+/// so observers of results nobody waits for anymore don't pile up in the action loop.
+/// Fails with HolochainError::ErrorGeneric if the channels are disconnected,
+/// e.g. because the action loop is not running.
+pub fn dispatch_action_and_wait_for<T, F>(
+    action_channel: &SyncSender<ActionWrapper>,
+    observer_channel: &SyncSender<Observer>,
+    action_wrapper: ActionWrapper,
+    timeout: Option<Duration>,
+    mut sense: F,
+) -> Result<T, HolochainError>
+where
+    T: 'static + Send,
+    F: 'static + FnMut(&State) -> Option<T> + Send,
+{
     // Create blocking channel
-    let (sender, receiver) = sync_channel::<()>(1);
+    let (sender, receiver) = sync_channel::<T>(1);
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let observer_cancelled = cancelled.clone();
 
     // Create blocking observer
-    let observer_action_wrapper = action_wrapper.clone();
-    let closure = move |state: &State| {
-        if state.history.contains(&observer_action_wrapper) {
-            sender
-                .send(())
-                // the channel stays connected until the first message has been sent
-                // if this fails that means that it was called after having returned done=true
-                .expect("observer called after done");
-            true
-        } else {
-            false
-        }
+    let observer = Observer {
+        sensor: Box::new(move |state: &State| {
+            if observer_cancelled.load(Ordering::SeqCst) {
+                return true;
+            }
+            match sense(state) {
+                Some(value) => {
+                    // sending only fails if the waiter gave up in the meantime
+                    let _ = sender.send(value);
+                    true
+                }
+                None => false,
+            }
+        }),
     };
 
-    dispatch_action_with_observer(&action_channel, &observer_channel, action_wrapper, closure);
-
-    // Block until Observer has sensed the completion of the Action
-    receiver.recv().expect(DISPATCH_WITHOUT_CHANNELS);
+    let disconnected = || HolochainError::ErrorGeneric(DISPATCH_WITHOUT_CHANNELS.to_string());
+    observer_channel
+        .send(observer)
+        .map_err(|_| disconnected())?;
+    action_channel
+        .send(action_wrapper)
+        .map_err(|_| disconnected())?;
+
+    // Block until Observer has sensed what it is waiting for
+    let result = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout).map_err(|error| match error {
+            RecvTimeoutError::Timeout => HolochainError::Timeout,
+            RecvTimeoutError::Disconnected => disconnected(),
+        }),
+        None => receiver.recv().map_err(|_| disconnected()),
+    };
+    if result.is_err() {
+        cancelled.store(true, Ordering::SeqCst);
+    }
+    result
 }
 
 /// Send Action to the Event Queue and create an Observer for it with the specified closure
@@ -429,13 +578,21 @@ pub mod tests {
     /// create a test instance
     #[cfg_attr(tarpaulin, skip)]
     pub fn test_instance_and_context(dna: Dna) -> Result<(Instance, Arc<Context>), String> {
+        test_instance_with_context(dna, test_context("jane"))
+    }
+
+    /// create a test instance running with the given context
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_instance_with_context(
+        dna: Dna,
+        context: Arc<Context>,
+    ) -> Result<(Instance, Arc<Context>), String> {
         // Create instance and plug in our DNA
-        let context = test_context("jane");
         let mut instance = Instance::new(context.clone());
         instance.start_action_loop(context.clone());
         let context = instance.initialize_context(context);
 
-        block_on(initialize_application(dna.clone(), context.clone()))?;
+        block_on(initialize_application(dna.clone(), None, context.clone()))?;
 
         assert_eq!(instance.state().nucleus().dna(), Some(dna.clone()));
         assert!(instance.state().nucleus().has_initialized());
@@ -610,7 +767,7 @@ pub mod tests {
             ::nucleus::state::NucleusStatus::New
         );
 
-        instance.dispatch_and_wait(action);
+        assert_eq!(Ok(()), instance.dispatch_and_wait(action));
         assert_eq!(instance.state().nucleus().dna(), Some(dna));
         assert_eq!(
             instance.state().nucleus().status(),
@@ -618,6 +775,72 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn times_out_waiting_for_action() {
+        let mut instance = Instance::new(test_context("jason"));
+        let context = test_context("jane");
+        let (rx_action, rx_observer) = instance.initialize_channels();
+
+        // nothing processes the action, so the waiter has to give up
+        let result = dispatch_action_and_wait_with_timeout(
+            &instance.action_channel(),
+            &instance.observer_channel(),
+            test_action_wrapper_get(),
+            Duration::from_millis(10),
+        );
+        assert_eq!(Err(HolochainError::Timeout), result);
+
+        // the observer of the waiter that gave up gets removed with the next action
+        let action_wrapper = rx_action.try_recv().expect("action should be queued");
+        let observers = instance.process_action(action_wrapper, Vec::new(), &rx_observer, &context);
+        assert!(observers.is_empty());
+    }
+
+    #[test]
+    fn can_stop_action_loop() {
+        let mut instance = Instance::new(test_context("jason"));
+        instance.start_action_loop(test_context("jane"));
+        assert_eq!(
+            Ok(()),
+            instance.dispatch_and_wait(test_action_wrapper_get())
+        );
+
+        assert_eq!(Ok(()), instance.stop_action_loop());
+
+        // a stopped loop does not process actions anymore
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                DISPATCH_WITHOUT_CHANNELS.to_string()
+            )),
+            instance.dispatch_and_wait_with_timeout(
+                test_action_wrapper_get(),
+                Duration::from_millis(10)
+            )
+        );
+        // stopping it again does nothing
+        assert_eq!(Ok(()), instance.stop_action_loop());
+    }
+
+    #[test]
+    fn fails_waiting_without_action_loop() {
+        let mut instance = Instance::new(test_context("jason"));
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                DISPATCH_WITHOUT_CHANNELS.to_string()
+            )),
+            instance.dispatch_and_wait_with_timeout(
+                test_action_wrapper_get(),
+                Duration::from_millis(10)
+            )
+        );
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                DISPATCH_WITHOUT_CHANNELS.to_string()
+            )),
+            instance.dispatch_and_wait(test_action_wrapper_get())
+        );
+    }
+
     #[test]
     /// tests that an unimplemented genesis allows the nucleus to initialize
     /// @TODO is this right? should return unimplemented?
@@ -646,8 +869,8 @@ pub mod tests {
                 r#"
             (module
                 (memory (;0;) 17)
-                (func (export "genesis") (param $p0 i32) (result i32)
-                    i32.const 0
+                (func (export "genesis") (param $p0 i64) (result i64)
+                    i64.const 0
                 )
                 (data (i32.const 0)
                     ""
@@ -675,8 +898,8 @@ pub mod tests {
                 r#"
             (module
                 (memory (;0;) 17)
-                (func (export "genesis") (param $p0 i32) (result i32)
-                    i32.const 9
+                (func (export "genesis") (param $p0 i64) (result i64)
+                    i64.const 9
                 )
                 (data (i32.const 0)
                     "1337.0"
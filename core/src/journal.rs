@@ -0,0 +1,202 @@
+//! Optional journal of every action an instance processes, to reproduce bugs.
+//! Once a journal is set on the context, the action loop writes each action to it as
+//! one line of JSON, together with the time it got processed at and the response the
+//! agent stored for it, if any.
+//! `replay` rebuilds the state of an instance from such a journal, and `diff_states`
+//! points out where the replayed state differs from the original one.
+
+use action::ActionWrapper;
+use agent::state::ActionResponse;
+use chrono::Utc;
+use context::Context;
+use holochain_core_types::{error::HolochainError, time::Iso8601};
+use serde_json;
+use state::State;
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    sync::{mpsc::sync_channel, Arc, RwLock},
+};
+
+/// One processed action of a journal
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// when the action got processed
+    pub timestamp: Iso8601,
+    pub action_wrapper: ActionWrapper,
+    /// the response the agent stored for the action, for actions that have one
+    pub result: Option<ActionResponse>,
+}
+
+/// Appends processed actions to a journal file
+pub struct ActionJournal {
+    file: File,
+}
+
+impl ActionJournal {
+    /// Opens the journal at the given path, adding to it if it exists already
+    pub fn new(path: &str) -> Result<ActionJournal, HolochainError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(ActionJournal { file })
+    }
+
+    pub fn record(
+        &mut self,
+        action_wrapper: &ActionWrapper,
+        result: Option<ActionResponse>,
+    ) -> Result<(), HolochainError> {
+        let entry = JournalEntry {
+            timestamp: Iso8601::from(Utc::now().to_rfc3339()),
+            action_wrapper: action_wrapper.clone(),
+            result,
+        };
+        writeln!(self.file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+}
+
+/// Reads all entries of the journal at the given path, in the order they got recorded
+pub fn read_journal(path: &str) -> Result<Vec<JournalEntry>, HolochainError> {
+    let mut entries = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+/// Rebuilds the state of an instance by reducing the actions of a journal one after the other.
+/// The context has to be a fresh one, i.e. with empty storage and without a network,
+/// the reducers write to its storage just like they did for the original instance.
+/// Zome calls are not run again and actions the reducers dispatch are dropped,
+/// as their outcome is part of the journal already.
+pub fn replay(entries: &[JournalEntry], context: Arc<Context>) -> State {
+    let mut replay_context = (*context).clone();
+    replay_context.set_replaying(true);
+    let (tx_action, rx_action) = sync_channel(Context::default_channel_buffer_size());
+    let (tx_observer, _rx_observer) = sync_channel(Context::default_channel_buffer_size());
+    replay_context.action_channel = tx_action;
+    replay_context.observer_channel = tx_observer;
+    let state = Arc::new(RwLock::new(State::new(Arc::new(replay_context.clone()))));
+    replay_context.set_state(state.clone());
+    let replay_context = Arc::new(replay_context);
+
+    for entry in entries {
+        let new_state = state
+            .read()
+            .expect("owners of the state RwLock shouldn't panic")
+            .reduce(replay_context.clone(), entry.action_wrapper.clone());
+        *state
+            .write()
+            .expect("owners of the state RwLock shouldn't panic") = new_state;
+        rx_action.try_iter().for_each(drop);
+    }
+
+    let replayed_state = state
+        .read()
+        .expect("owners of the state RwLock shouldn't panic");
+    replayed_state.clone()
+}
+
+/// Describes the parts in which two states differ, e.g. an original and a replayed one.
+/// Storage is not compared, since a replay writes to a storage of its own.
+pub fn diff_states(expected: &State, actual: &State) -> Vec<String> {
+    let mut differences = Vec::new();
+    if expected.nucleus() != actual.nucleus() {
+        differences.push(format!(
+            "nucleus: expected {:?}, got {:?}",
+            expected.nucleus(),
+            actual.nucleus()
+        ));
+    }
+    if expected.agent().top_chain_header() != actual.agent().top_chain_header() {
+        differences.push(format!(
+            "top chain header: expected {:?}, got {:?}",
+            expected.agent().top_chain_header(),
+            actual.agent().top_chain_header()
+        ));
+    }
+    if expected.agent().actions() != actual.agent().actions() {
+        differences.push(format!(
+            "agent action responses: expected {:?}, got {:?}",
+            expected.agent().actions(),
+            actual.agent().actions()
+        ));
+    }
+    if expected.history != actual.history {
+        differences.push(format!(
+            "history: expected {} actions, got {}",
+            expected.history.len(),
+            actual.history.len()
+        ));
+    }
+    differences
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+    extern crate test_utils;
+
+    use self::tempfile::tempdir;
+    use super::*;
+    use holochain_core_types::{
+        cas::{content::AddressableContent, storage::ContentAddressableStorage},
+        entry::{test_entry, Entry},
+    };
+    use instance::tests::{test_context, test_instance_with_context};
+    use nucleus::actions::tests::commit;
+
+    #[test]
+    fn records_and_replays_actions() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("journal");
+        let path = path.to_str().unwrap();
+
+        let mut context = (*test_context("jane")).clone();
+        context.set_journal(ActionJournal::new(path).unwrap());
+        let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (instance, context) =
+            test_instance_with_context(dna.clone(), Arc::new(context)).unwrap();
+        commit(test_entry(), &context);
+
+        // every processed action got recorded, commits with their result
+        let entries = read_journal(path).unwrap();
+        let state = instance.state().clone();
+        for action_wrapper in state.history.iter() {
+            assert!(entries
+                .iter()
+                .any(|entry| &entry.action_wrapper == action_wrapper));
+        }
+        let commit_entry = entries
+            .iter()
+            .find(|entry| entry.action_wrapper.action() == &::action::Action::Commit(test_entry()))
+            .expect("commit should be recorded");
+        assert_eq!(
+            Some(ActionResponse::Commit(Ok(test_entry().address()))),
+            commit_entry.result
+        );
+
+        // replaying against a fresh storage leads to the same state
+        let replayed = replay(&entries, test_context("jane"));
+        assert_eq!(Vec::<String>::new(), diff_states(&state, &replayed));
+        assert_eq!(Some(dna), replayed.nucleus().dna());
+        let maybe_entry: Option<Entry> = replayed
+            .agent()
+            .chain()
+            .content_storage()
+            .fetch(&test_entry().address())
+            .unwrap();
+        assert_eq!(Some(test_entry()), maybe_entry);
+    }
+
+    #[test]
+    fn fails_reading_missing_journal() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("missing");
+
+        assert!(read_journal(path.to_str().unwrap()).is_err());
+    }
+}
@@ -4,8 +4,12 @@
 #[macro_use]
 extern crate serde_derive;
 extern crate chrono;
+extern crate failure;
 extern crate futures;
 extern crate multihash;
+extern crate parity_wasm;
+extern crate pwasm_utils;
+extern crate rand;
 extern crate rust_base58;
 extern crate serde;
 extern crate serde_json;
@@ -35,10 +39,13 @@ pub mod action;
 pub mod agent;
 pub mod context;
 pub mod dht;
+pub mod executor;
 pub mod instance;
+pub mod journal;
 #[cfg(test)]
 pub mod link_tests;
 pub mod logger;
+pub mod network;
 pub mod nucleus;
 pub mod persister;
 pub mod state;
@@ -0,0 +1,286 @@
+//! Direct messaging between the instances of different agents of the same DNA.
+//! Messages travel over the network the context of an instance is connected to,
+//! and get answered by the receive callback of the addressed zome.
+
+use context::Context;
+use failure::{err_msg, Error};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    error::HolochainError,
+    json::JsonString,
+};
+use holochain_dna::Dna;
+use holochain_net::p2p_network::GenomeHash;
+use holochain_wasm_utils::api_serialization::send::{ReceiveParams, SendArgs};
+use multihash::{decode, encode, Hash as Multihash};
+use nucleus::ribosome::callback::{receive::receive, CallbackParams, CallbackResult};
+use std::{
+    convert::TryFrom,
+    sync::{
+        mpsc::{channel, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// The payload of a direct message as it travels over the network
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson)]
+struct DirectMessage {
+    /// Name of the zome whose receive callback handles the message
+    zome_name: String,
+    payload: String,
+}
+
+/// Identifies a DNA on the network, the SHA2-256 digest of the DNA
+pub fn genome_hash(dna: &Dna) -> GenomeHash {
+    let json = String::from(JsonString::from(dna.clone()));
+    let multihash = encode(Multihash::SHA2256, json.as_bytes()).expect("SHA2-256 should encode");
+    let mut genome_hash = [0; 32];
+    genome_hash.copy_from_slice(
+        decode(&multihash)
+            .expect("encoded multihash should decode")
+            .digest,
+    );
+    genome_hash
+}
+
+/// Sends a message to the given zome of another agent's instance and waits for its response.
+/// Fails with HolochainError::Timeout if there is no response within the timeout of the args,
+/// and with HolochainError::NetworkError if the message could not be delivered
+/// or the receiving zome did not handle it.
+pub fn send_message(
+    context: &Arc<Context>,
+    zome_name: &str,
+    args: SendArgs,
+) -> Result<String, HolochainError> {
+    let network = context.network().ok_or_else(|| {
+        HolochainError::NetworkError("instance is not connected to a network".to_string())
+    })?;
+    let dna = context
+        .state()
+        .and_then(|state| state.nucleus().dna())
+        .ok_or(HolochainError::DnaMissing)?;
+    let genome_hash = genome_hash(&dna);
+    let from = String::from(context.agent.address());
+    let to = String::from(args.to_agent);
+    let message = String::from(JsonString::from(DirectMessage {
+        zome_name: zome_name.to_string(),
+        payload: args.payload,
+    }));
+
+    // deliver the message over a connection of its own, so that the network is not locked
+    // while the receiver handles it, and on its own thread so we can stop waiting for it
+    let mut connection = network
+        .lock()
+        .expect("network should not be poisoned")
+        .try_clone()
+        .map_err(|err| HolochainError::NetworkError(err.to_string()))?;
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let result = connection
+            .send_message(&genome_hash, &from, &to, &message)
+            .map_err(|err| HolochainError::NetworkError(err.to_string()));
+        // nobody is listening anymore if the sender timed out
+        let _ = sender.send(result);
+    });
+
+    match receiver.recv_timeout(Duration::from_millis(args.options.timeout_ms)) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(HolochainError::Timeout),
+        Err(RecvTimeoutError::Disconnected) => Err(HolochainError::NetworkError(
+            "message delivery failed".to_string(),
+        )),
+    }
+}
+
+/// Answers direct messages to the agent of the instance with the receive callback
+/// of the addressed zome.
+/// Does nothing if the instance is not connected to a network.
+pub fn register_message_receiver(context: &Arc<Context>, dna: &Dna) -> Result<(), HolochainError> {
+    let network = match context.network() {
+        Some(network) => network,
+        None => return Ok(()),
+    };
+    let receiver_context = context.clone();
+    let mut network = network.lock().expect("network should not be poisoned");
+    network
+        .set_on_message_callback(
+            &genome_hash(dna),
+            &String::from(context.agent.address()),
+            Box::new(move |from, message| handle_message(receiver_context.clone(), from, message)),
+        )
+        .map_err(|err| HolochainError::NetworkError(err.to_string()))
+}
+
+/// Stops answering direct messages to the agent of the instance,
+/// which also releases the context held by the receiver.
+/// Does nothing if the instance is not connected to a network.
+pub fn unregister_message_receiver(
+    context: &Arc<Context>,
+    dna: &Dna,
+) -> Result<(), HolochainError> {
+    let network = match context.network() {
+        Some(network) => network,
+        None => return Ok(()),
+    };
+    let mut network = network.lock().expect("network should not be poisoned");
+    network
+        .remove_on_message_callback(&genome_hash(dna), &String::from(context.agent.address()))
+        .map_err(|err| HolochainError::NetworkError(err.to_string()))
+}
+
+fn handle_message(context: Arc<Context>, from: &str, message: &str) -> Result<String, Error> {
+    let message = DirectMessage::try_from(JsonString::from(message.to_string()))?;
+    let params = CallbackParams::Receive(ReceiveParams {
+        from: Address::from(from),
+        payload: message.payload,
+    });
+    match receive(context, &message.zome_name, &params) {
+        CallbackResult::ReceiveResult(response) => Ok(response),
+        CallbackResult::NotImplemented => Err(err_msg(format!(
+            "zome '{}' does not implement receive",
+            message.zome_name
+        ))),
+        CallbackResult::Fail(error) => Err(err_msg(error)),
+        result => Err(err_msg(format!(
+            "unexpected result of receive: {:?}",
+            result
+        ))),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+
+    use super::*;
+    use holochain_net::{in_process::InProcessHub, p2p_network::P2pNetwork};
+    use holochain_wasm_utils::api_serialization::send::SendOptions;
+    use instance::tests::{test_context, test_instance_with_context};
+    use std::{sync::Mutex, time::Instant};
+
+    fn test_send_args(to_agent: Address, timeout_ms: u64) -> SendArgs {
+        SendArgs {
+            to_agent,
+            payload: "ping".to_string(),
+            options: SendOptions { timeout_ms },
+        }
+    }
+
+    #[test]
+    fn genome_hash_is_sha256_of_dna() {
+        let dna = test_utils::create_test_dna_with_wasm("test_zome", "test_cap", vec![]);
+        let mut other_dna = dna.clone();
+        other_dna.uuid = "other".to_string();
+
+        assert_eq!(genome_hash(&dna), genome_hash(&dna.clone()));
+        assert_ne!(genome_hash(&dna), genome_hash(&other_dna));
+    }
+
+    #[test]
+    fn fails_without_network() {
+        let dna = test_utils::create_test_dna_with_wasm("test_zome", "test_cap", vec![]);
+        let (_instance, context) = test_instance_with_context(dna, test_context("alice"))
+            .expect("Could not create test instance");
+
+        assert_eq!(
+            Err(HolochainError::NetworkError(
+                "instance is not connected to a network".to_string()
+            )),
+            send_message(
+                &context,
+                "test_zome",
+                test_send_args(Address::from("bob"), 100)
+            ),
+        );
+    }
+
+    #[test]
+    fn fails_on_unreachable_agent() {
+        let hub = InProcessHub::new();
+        let mut context = (*test_context("alice")).clone();
+        context.set_network(Arc::new(Mutex::new(hub.connect())));
+        let dna = test_utils::create_test_dna_with_wasm("test_zome", "test_cap", vec![]);
+        let (_instance, context) = test_instance_with_context(dna, Arc::new(context))
+            .expect("Could not create test instance");
+
+        assert_eq!(
+            Err(HolochainError::NetworkError(
+                "agent 'bob' is not reachable".to_string()
+            )),
+            send_message(
+                &context,
+                "test_zome",
+                test_send_args(Address::from("bob"), 100)
+            ),
+        );
+    }
+
+    #[test]
+    fn can_unregister_message_receiver() {
+        let hub = InProcessHub::new();
+        let mut context = (*test_context("alice")).clone();
+        context.set_network(Arc::new(Mutex::new(hub.connect())));
+        let dna = test_utils::create_test_dna_with_wasm("test_zome", "test_cap", vec![]);
+        let (_instance, context) = test_instance_with_context(dna.clone(), Arc::new(context))
+            .expect("Could not create test instance");
+        let alice = String::from(context.agent.address());
+        let message = String::from(JsonString::from(DirectMessage {
+            zome_name: "test_zome".to_string(),
+            payload: "ping".to_string(),
+        }));
+
+        // the receiver of alice answers, even though her zome can't handle the message
+        assert_ne!(
+            format!("agent '{}' is not reachable", alice),
+            hub.connect()
+                .send_message(&genome_hash(&dna), "bob", &alice, &message)
+                .unwrap_err()
+                .to_string()
+        );
+
+        assert_eq!(Ok(()), unregister_message_receiver(&context, &dna));
+        assert_eq!(
+            format!("agent '{}' is not reachable", alice),
+            hub.connect()
+                .send_message(&genome_hash(&dna), "bob", &alice, &message)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn releases_network_while_waiting_for_response() {
+        let hub = InProcessHub::new();
+        let mut context = (*test_context("alice")).clone();
+        context.set_network(Arc::new(Mutex::new(hub.connect())));
+        let dna = test_utils::create_test_dna_with_wasm("test_zome", "test_cap", vec![]);
+        let (_instance, context) = test_instance_with_context(dna.clone(), Arc::new(context))
+            .expect("Could not create test instance");
+        // bob takes his time to answer
+        hub.connect()
+            .set_on_message_callback(
+                &genome_hash(&dna),
+                "bob",
+                Box::new(|_, _| {
+                    thread::sleep(Duration::from_millis(1000));
+                    Ok("pong".to_string())
+                }),
+            )
+            .unwrap();
+
+        let start = Instant::now();
+        assert_eq!(
+            Err(HolochainError::Timeout),
+            send_message(
+                &context,
+                "test_zome",
+                test_send_args(Address::from("bob"), 100)
+            ),
+        );
+        assert!(start.elapsed() < Duration::from_millis(1000));
+        // bob is still busy, but the network is free for other calls
+        assert!(context.network().unwrap().try_lock().is_ok());
+    }
+}
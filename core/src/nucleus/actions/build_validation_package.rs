@@ -3,19 +3,20 @@ extern crate serde_json;
 use action::{Action, ActionWrapper};
 use agent;
 use context::Context;
+use executor::Pool;
 use futures::{future, Async, Future};
 use holochain_core_types::{
     cas::{content::AddressableContent, storage::ContentAddressableStorage},
     chain_header::ChainHeader,
     entry::{Entry, SerializedEntry},
     error::HolochainError,
-    validation::{ValidationPackage, ValidationPackageDefinition::*},
+    validation::{ChainSelector, ValidationPackage, ValidationPackageDefinition::*},
 };
 use nucleus::ribosome::callback::{
     validation_package::get_validation_package_definition, CallbackResult,
 };
 use snowflake;
-use std::{sync::Arc, thread};
+use std::sync::Arc;
 
 pub fn build_validation_package(
     entry: &Entry,
@@ -57,7 +58,9 @@ pub fn build_validation_package(
                 agent::state::create_new_chain_header(&entry, &*context.state().unwrap().agent()),
             );
 
-            thread::spawn(move || {
+            let job_context = context.clone();
+            let scheduled = context.executor.spawn(Pool::Validation, move || {
+                let context = job_context;
                 let maybe_callback_result =
                     get_validation_package_definition(entry.entry_type().clone(), context.clone());
 
@@ -103,6 +106,14 @@ pub fn build_validation_package(
                                 package.custom = Some(string);
                                 package
                             }
+                            Selection(selector) => {
+                                let headers = select_public_chain_headers(&context, &selector)?;
+                                let mut package = ValidationPackage::only_header(entry_header);
+                                package.source_chain_entries =
+                                    Some(chain_entries(&context, &headers));
+                                package.source_chain_headers = Some(headers);
+                                package
+                            }
                         })
                     });
 
@@ -114,6 +125,9 @@ pub fn build_validation_package(
                     ))))
                     .expect("action channel to be open in reducer");
             });
+            if let Err(error) = scheduled {
+                return Box::new(future::err(error));
+            }
         }
     };
 
@@ -123,7 +137,8 @@ pub fn build_validation_package(
     })
 }
 
-fn chain_header(entry: Entry, context: &Arc<Context>) -> Option<ChainHeader> {
+/// The header of the entry on the source chain of the agent, if it got committed there
+pub(crate) fn chain_header(entry: Entry, context: &Arc<Context>) -> Option<ChainHeader> {
     let chain = context.state().unwrap().agent().chain();
     let top_header = context.state().unwrap().agent().top_chain_header();
     chain
@@ -158,6 +173,51 @@ fn all_public_chain_headers(context: &Arc<Context>) -> Vec<ChainHeader> {
         .collect::<Vec<_>>()
 }
 
+/// The public headers of the source chain picked by the selector, latest first
+fn select_public_chain_headers(
+    context: &Arc<Context>,
+    selector: &ChainSelector,
+) -> Result<Vec<ChainHeader>, HolochainError> {
+    let headers = all_public_chain_headers(context);
+    match selector {
+        ChainSelector::EntryTypes(entry_types) => Ok(headers
+            .into_iter()
+            .filter(|header| entry_types.contains(&header.entry_type().to_string()))
+            .collect()),
+        ChainSelector::LastEntries(count) => Ok(headers.into_iter().take(*count).collect()),
+        ChainSelector::HeaderRange { from, to } => {
+            let range: Vec<ChainHeader> = headers
+                .into_iter()
+                .skip_while(|header| header.address() != *to)
+                .collect();
+            match range.iter().position(|header| header.address() == *from) {
+                Some(index) => Ok(range[..=index].to_vec()),
+                None => Err(HolochainError::ValidationFailed(format!(
+                    "header range from '{}' to '{}' is not part of the source chain",
+                    from, to
+                ))),
+            }
+        }
+    }
+}
+
+/// The entries of the given headers, in the same order
+fn chain_entries(context: &Arc<Context>, headers: &[ChainHeader]) -> Vec<SerializedEntry> {
+    let chain = context.state().unwrap().agent().chain();
+    headers
+        .iter()
+        .map(|chain_header| {
+            let entry: Option<Entry> = chain
+                .content_storage()
+                .fetch(chain_header.entry_address())
+                .expect("Could not fetch from CAS");
+            entry
+                .expect("Could not find entry in CAS for existing chain header")
+                .into()
+        })
+        .collect()
+}
+
 /// ValidationPackageFuture resolves to the ValidationPackage or a HolochainError.
 pub struct ValidationPackageFuture {
     context: Arc<Context>,
@@ -307,4 +367,66 @@ mod tests {
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
     }
+
+    #[test]
+    fn test_building_validation_package_selection() {
+        let (_instance, context) = instance();
+
+        commit(test_entry_package_entry(), &context);
+        commit(test_entry_package_chain_entries(), &context);
+
+        // commit entry to build validation package for
+        let chain_header = commit(test_entry_package_selection(), &context);
+
+        let maybe_validation_package = block_on(build_validation_package(
+            &test_entry_package_selection(),
+            &context.clone(),
+        ));
+        assert!(maybe_validation_package.is_ok());
+
+        // only the entries of the selected type
+        let headers = select_public_chain_headers(
+            &context,
+            &ChainSelector::EntryTypes(vec!["package_entry".to_string()]),
+        ).unwrap();
+        assert_eq!(1, headers.len());
+        let expected = ValidationPackage {
+            chain_header: Some(chain_header),
+            source_chain_entries: Some(vec![test_entry_package_entry().into()]),
+            source_chain_headers: Some(headers),
+            custom: None,
+        };
+
+        assert_eq!(maybe_validation_package.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_selecting_chain_headers() {
+        let (_instance, context) = instance();
+
+        let oldest = commit(test_entry_package_entry(), &context);
+        let middle = commit(test_entry_package_chain_entries(), &context);
+        let latest = commit(test_entry_package_chain_full(), &context);
+
+        assert_eq!(
+            Ok(vec![latest.clone(), middle.clone()]),
+            select_public_chain_headers(&context, &ChainSelector::LastEntries(2))
+        );
+        assert_eq!(
+            Ok(vec![middle.clone(), oldest.clone()]),
+            select_public_chain_headers(
+                &context,
+                &ChainSelector::HeaderRange {
+                    from: oldest.address(),
+                    to: middle.address(),
+                }
+            )
+        );
+        // the range has to run from the older to the newer header
+        let reversed_range = ChainSelector::HeaderRange {
+            from: latest.address(),
+            to: oldest.address(),
+        };
+        assert!(select_public_chain_headers(&context, &reversed_range).is_err());
+    }
 }
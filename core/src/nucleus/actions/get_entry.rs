@@ -2,21 +2,93 @@ extern crate serde_json;
 use context::Context;
 use futures::{future, Future};
 use holochain_core_types::{
-    cas::{content::Address, storage::ContentAddressableStorage},
-    entry::Entry,
+    cas::{
+        content::{Address, AddressableContent},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
+    entry::{Entry, ToEntry},
     error::HolochainError,
 };
+use holochain_wasm_utils::api_serialization::get_entry::{
+    EntryMeta, GetEntryOptions, GetEntryResult,
+};
+use nucleus::validation_cache::{note_read, DhtRead};
 use std::sync::Arc;
 
 fn get_entry_from_dht_cas(
     context: &Arc<Context>,
     address: Address,
 ) -> Result<Option<Entry>, HolochainError> {
-    let dht = context.state().unwrap().dht().content_storage();
-    dht.fetch(&address)
+    note_read(DhtRead::Entry(address.clone()));
+    let dht = context.state().unwrap().dht();
+    // Updated entries are resolved to their latest version, removed ones are not returned
+    match dht.get_latest_address(&address)? {
+        None => Ok(None),
+        Some(latest_address) => dht.content_storage().fetch(&latest_address),
+    }
+}
+
+fn local_chain_header(context: &Arc<Context>, address: &Address) -> Option<ChainHeader> {
+    let chain = context.state().unwrap().agent().chain();
+    let top_header = context.state().unwrap().agent().top_chain_header();
+    chain
+        .iter(&top_header)
+        .find(|ref header| header.entry_address() == address)
+}
+
+fn get_entry_result_from_dht_cas(
+    context: &Arc<Context>,
+    address: Address,
+    options: &GetEntryOptions,
+) -> Result<GetEntryResult, HolochainError> {
+    note_read(DhtRead::Entry(address.clone()));
+    let dht = context.state().unwrap().dht();
+    let address = if options.follow_updates {
+        dht.get_latest_version(&address)?
+    } else {
+        address
+    };
+    let maybe_entry: Option<Entry> = dht.content_storage().fetch(&address)?;
+    let entry = match maybe_entry {
+        None => return Ok(GetEntryResult::not_found()),
+        Some(entry) => entry,
+    };
+    let crud_status = dht.get_crud_status(&address)?;
+    if !options.status_mask.contains(crud_status) {
+        return Ok(GetEntryResult::not_found());
+    }
+
+    let mut result = GetEntryResult::not_found();
+    result.address = Some(address.clone());
+    result.entry = Some(entry.serialize());
+    if options.include_header || options.include_sources {
+        let maybe_header = local_chain_header(context, &address);
+        if options.include_sources {
+            // TODO: ask the network for the sources of entries that were not committed locally
+            result.sources = Some(
+                maybe_header
+                    .iter()
+                    .map(|_| context.agent.to_entry().address())
+                    .collect(),
+            );
+        }
+        if options.include_header {
+            result.header = maybe_header;
+        }
+    }
+    if options.include_meta {
+        result.meta = Some(EntryMeta {
+            crud_status,
+            crud_link: dht.get_crud_link(&address)?,
+        });
+    }
+    Ok(result)
 }
 
 /// GetEntry Action Creator
+/// Follows the update chain of the entry at the given address
+/// and resolves to its latest version, or to None if it has been removed.
 ///
 /// Returns a future that resolves to an Ok(ActionWrapper) or an Err(error_message:String).
 pub fn get_entry(
@@ -29,13 +101,32 @@ pub fn get_entry(
     }
 }
 
+/// GetEntryResult Action Creator
+/// Like get_entry but applies the given options: which CRUD statuses are returned,
+/// whether updates get followed and what extra data is added to the result.
+///
+/// Returns a future that resolves to an Ok(GetEntryResult) or an Err(HolochainError).
+pub fn get_entry_result(
+    context: &Arc<Context>,
+    address: Address,
+    options: &GetEntryOptions,
+) -> Box<dyn Future<Item = GetEntryResult, Error = HolochainError>> {
+    match get_entry_result_from_dht_cas(context, address, options) {
+        Err(err) => Box::new(future::err(err)),
+        Ok(result) => Box::new(future::ok(result)),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use futures::executor::block_on;
     use holochain_core_types::{
         cas::{content::AddressableContent, storage::ContentAddressableStorage},
-        entry::test_entry,
+        crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
+        eav::{EntityAttributeValue, EntityAttributeValueStorage},
+        entry::{test_entry, test_entry_b},
     };
+    use holochain_wasm_utils::api_serialization::get_entry::GetEntryOptions;
     use instance::tests::test_context_with_state;
 
     #[test]
@@ -72,4 +163,91 @@ pub mod tests {
         assert_eq!(Ok(Some(entry.clone())), block_on(future));
     }
 
+    #[test]
+    fn get_entry_follows_update_chain() {
+        let entry = test_entry();
+        let new_entry = test_entry_b();
+        let context = test_context_with_state();
+        let dht = context.state().unwrap().dht();
+        dht.content_storage().add(&entry).unwrap();
+        dht.content_storage().add(&new_entry).unwrap();
+        dht.meta_storage()
+            .add_eav(&EntityAttributeValue::new(
+                &entry.address(),
+                &STATUS_NAME.to_string(),
+                &CrudStatus::MODIFIED.address(),
+            ))
+            .unwrap();
+        dht.meta_storage()
+            .add_eav(&EntityAttributeValue::new(
+                &entry.address(),
+                &LINK_NAME.to_string(),
+                &new_entry.address(),
+            ))
+            .unwrap();
+        let result = super::get_entry_from_dht_cas(&context, entry.address());
+        assert_eq!(Ok(Some(new_entry.clone())), result);
+
+        dht.meta_storage()
+            .add_eav(&EntityAttributeValue::new(
+                &new_entry.address(),
+                &STATUS_NAME.to_string(),
+                &CrudStatus::DELETED.address(),
+            ))
+            .unwrap();
+        let result = super::get_entry_from_dht_cas(&context, entry.address());
+        assert_eq!(Ok(None), result);
+    }
+
+    #[test]
+    fn get_entry_result_applies_options() {
+        let entry = test_entry();
+        let new_entry = test_entry_b();
+        let context = test_context_with_state();
+        let dht = context.state().unwrap().dht();
+        dht.content_storage().add(&entry).unwrap();
+        dht.content_storage().add(&new_entry).unwrap();
+        dht.meta_storage()
+            .add_eav(&EntityAttributeValue::new(
+                &entry.address(),
+                &STATUS_NAME.to_string(),
+                &CrudStatus::MODIFIED.address(),
+            ))
+            .unwrap();
+        dht.meta_storage()
+            .add_eav(&EntityAttributeValue::new(
+                &entry.address(),
+                &LINK_NAME.to_string(),
+                &new_entry.address(),
+            ))
+            .unwrap();
+
+        // default options follow the update
+        let result = super::get_entry_result_from_dht_cas(
+            &context,
+            entry.address(),
+            &GetEntryOptions::default(),
+        ).unwrap();
+        assert_eq!(Some(new_entry.address()), result.address);
+        assert_eq!(Some(new_entry.serialize()), result.entry);
+        assert_eq!(None, result.meta);
+
+        // without following, the modified entry is filtered by the default status mask
+        let mut options = GetEntryOptions::default();
+        options.follow_updates = false;
+        let result =
+            super::get_entry_result_from_dht_cas(&context, entry.address(), &options).unwrap();
+        assert!(!result.found());
+
+        // unless the mask asks for modified entries
+        options.status_mask = CrudStatus::LIVE | CrudStatus::MODIFIED;
+        options.include_meta = true;
+        let result =
+            super::get_entry_result_from_dht_cas(&context, entry.address(), &options).unwrap();
+        assert_eq!(Some(entry.serialize()), result.entry);
+        let meta = result.meta.expect("meta should be included");
+        assert_eq!(CrudStatus::MODIFIED, meta.crud_status);
+        assert_eq!(Some(new_entry.address()), meta.crud_link);
+    }
+
 }
@@ -0,0 +1,225 @@
+use context::Context;
+use futures::{future, Future};
+use holochain_core_types::{
+    cas::{content::Address, storage::ContentAddressableStorage},
+    entry::Entry,
+    error::HolochainError,
+};
+use holochain_wasm_utils::api_serialization::get_links::{
+    GetLinksOptions, GetLinksResult, LinkResult,
+};
+use nucleus::validation_cache::{note_read, DhtRead};
+use std::sync::Arc;
+
+fn get_links_result_from_dht(
+    context: &Arc<Context>,
+    address: Address,
+    tag: String,
+    options: &GetLinksOptions,
+) -> Result<GetLinksResult, HolochainError> {
+    // an empty page would come without a cursor to continue from
+    if options.limit == Some(0) {
+        return Err(HolochainError::ErrorGeneric(String::from(
+            "limit of links must be greater than 0",
+        )));
+    }
+    note_read(DhtRead::Links(address.clone(), tag.clone()));
+    let dht = context.state().unwrap().dht();
+    let mut links = Vec::new();
+    let mut next_cursor = None;
+
+    // Targets are ordered by address, so the cursor is the last target of the previous page
+    let targets = dht
+        .get_link_targets(address, tag)?
+        .into_iter()
+        .filter(|(target, _)| match options.cursor {
+            Some(ref cursor) => target > cursor,
+            None => true,
+        })
+        .filter(|(_, status)| options.status_mask.contains(*status));
+
+    for (target, status) in targets {
+        let needs_entry = options.include_entries || options.target_type.is_some();
+        let maybe_entry: Option<Entry> = if needs_entry {
+            dht.content_storage().fetch(&target)?
+        } else {
+            None
+        };
+        if let Some(ref target_type) = options.target_type {
+            let type_matches = maybe_entry
+                .as_ref()
+                .map(|entry| &entry.entry_type().to_string() == target_type)
+                .unwrap_or(false);
+            if !type_matches {
+                continue;
+            }
+        }
+        if options
+            .limit
+            .map(|limit| links.len() >= limit)
+            .unwrap_or(false)
+        {
+            // there is at least one more link, so point the cursor at the end of this page
+            next_cursor = links.last().map(|link: &LinkResult| link.target.clone());
+            break;
+        }
+        links.push(LinkResult {
+            target,
+            status,
+            entry: if options.include_entries {
+                maybe_entry.map(|entry| entry.serialize())
+            } else {
+                None
+            },
+        });
+    }
+
+    Ok(GetLinksResult { links, next_cursor })
+}
+
+/// GetLinksResult Action Creator
+/// Returns the links from the given address with the given tag, ordered by target address
+/// and filtered and paginated according to the given options.
+///
+/// Returns a future that resolves to an Ok(GetLinksResult) or an Err(HolochainError).
+pub fn get_links_result(
+    context: &Arc<Context>,
+    address: Address,
+    tag: String,
+    options: &GetLinksOptions,
+) -> Box<dyn Future<Item = GetLinksResult, Error = HolochainError>> {
+    match get_links_result_from_dht(context, address, tag, options) {
+        Err(err) => Box::new(future::err(err)),
+        Ok(result) => Box::new(future::ok(result)),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        crud_status::CrudStatus,
+        entry::{test_entry, test_entry_a, test_entry_b, test_entry_c},
+        links_entry::Link,
+    };
+    use instance::tests::test_context_with_state;
+
+    /// adds links from test_entry() to the given targets, returns the sorted target addresses
+    fn add_test_links(context: &Arc<Context>, targets: &[Entry]) -> Vec<Address> {
+        let base = test_entry();
+        let mut dht = (*context.state().unwrap().dht()).clone();
+        dht.content_storage().add(&base).unwrap();
+        let mut addresses = Vec::new();
+        for target in targets {
+            dht.content_storage().add(target).unwrap();
+            dht.add_link(
+                &Link::new(&base.address(), &target.address(), "test-tag"),
+                &Address::from("link-instance"),
+            ).unwrap();
+            addresses.push(target.address());
+        }
+        addresses.sort();
+        addresses
+    }
+
+    #[test]
+    fn get_links_result_paginates() {
+        let context = test_context_with_state();
+        let targets = add_test_links(&context, &[test_entry_a(), test_entry_b(), test_entry_c()]);
+
+        let mut options = GetLinksOptions::default();
+        options.limit = Some(2);
+        let result = block_on(get_links_result(
+            &context,
+            test_entry().address(),
+            String::from("test-tag"),
+            &options,
+        )).unwrap();
+        assert_eq!(targets[0..2].to_vec(), result.addresses());
+        assert_eq!(Some(targets[1].clone()), result.next_cursor);
+
+        options.cursor = result.next_cursor;
+        let result = block_on(get_links_result(
+            &context,
+            test_entry().address(),
+            String::from("test-tag"),
+            &options,
+        )).unwrap();
+        assert_eq!(targets[2..].to_vec(), result.addresses());
+        assert_eq!(None, result.next_cursor);
+    }
+
+    #[test]
+    fn get_links_result_rejects_limit_of_zero() {
+        let context = test_context_with_state();
+        add_test_links(&context, &[test_entry_a()]);
+
+        let mut options = GetLinksOptions::default();
+        options.limit = Some(0);
+        let result = get_links_result_from_dht(
+            &context,
+            test_entry().address(),
+            String::from("test-tag"),
+            &options,
+        );
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(String::from(
+                "limit of links must be greater than 0"
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn get_links_result_filters_and_includes_entries() {
+        let context = test_context_with_state();
+        let targets = add_test_links(&context, &[test_entry_a(), test_entry_b()]);
+        let mut dht = (*context.state().unwrap().dht()).clone();
+        dht.remove_link(&Link::new(&test_entry().address(), &targets[0], "test-tag"))
+            .unwrap();
+
+        // removed links are hidden by default
+        let options = GetLinksOptions::default();
+        let result = get_links_result_from_dht(
+            &context,
+            test_entry().address(),
+            String::from("test-tag"),
+            &options,
+        ).unwrap();
+        assert_eq!(vec![targets[1].clone()], result.addresses());
+        assert_eq!(None, result.links[0].entry);
+
+        // but can be requested with their status
+        let mut options = GetLinksOptions::default();
+        options.status_mask = CrudStatus::LIVE | CrudStatus::DELETED;
+        options.include_entries = true;
+        let result = get_links_result_from_dht(
+            &context,
+            test_entry().address(),
+            String::from("test-tag"),
+            &options,
+        ).unwrap();
+        assert_eq!(targets, result.addresses());
+        assert_eq!(CrudStatus::DELETED, result.links[0].status);
+        let entry: Option<Entry> = context
+            .state()
+            .unwrap()
+            .dht()
+            .content_storage()
+            .fetch(&targets[1])
+            .unwrap();
+        assert_eq!(entry.map(|entry| entry.serialize()), result.links[1].entry);
+
+        // target type filter
+        options.target_type = Some(String::from("testEntryTypeB"));
+        let result = get_links_result_from_dht(
+            &context,
+            test_entry().address(),
+            String::from("test-tag"),
+            &options,
+        ).unwrap();
+        assert_eq!(vec![test_entry_b().address()], result.addresses());
+    }
+}
@@ -1,16 +1,27 @@
 extern crate futures;
 use action::{Action, ActionWrapper};
-use agent::actions::commit::commit_entry;
+use agent::{actions::commit::commit_entry, state::create_new_chain_header};
 use context::Context;
+use executor::Pool;
 use futures::{executor::block_on, future, Async, Future};
-use holochain_core_types::entry::ToEntry;
+use holochain_core_types::{
+    cas::content::Address,
+    entry::{Entry, ToEntry},
+    entry_type::EntryType,
+    error::HolochainError,
+    hash::HashString,
+    json::JsonString,
+    validation::{EntryAction, EntryLifecycle, ValidationData, ValidationPackage},
+};
 use holochain_dna::Dna;
 use instance::dispatch_action_and_wait;
+use network::register_message_receiver;
 use nucleus::{
+    actions::validate::validate_entry,
     ribosome::callback::{genesis::genesis, CallbackParams, CallbackResult},
     state::NucleusStatus,
 };
-use std::{sync::Arc, thread, time::*};
+use std::{sync::Arc, time::*};
 
 /// Timeout in seconds for initialization process.
 /// Future will resolve to an error after this duration.
@@ -20,12 +31,17 @@ const INITIALIZATION_TIMEOUT: u64 = 30;
 /// This is the high-level initialization function that wraps the whole process of initializing an
 /// instance. It creates both InitApplication and ReturnInitializationResult actions asynchronously.
 ///
+/// The optional membrane proof is the credential the agent joins with, e.g. an invite code.
+/// It gets committed right after the AgentId and is handed to the validation of the AgentId
+/// and to the genesis callbacks, so zomes can decide who may join.
+///
 /// Returns a future that resolves to an Ok(NucleusStatus) or an Err(String) which carries either
 /// the Dna error or errors from the genesis callback.
 ///
 /// Use futures::executor::block_on to wait for an initialized instance.
 pub fn initialize_application(
     dna: Dna,
+    membrane_proof: Option<JsonString>,
     context: Arc<Context>,
 ) -> Box<dyn Future<Item = NucleusStatus, Error = String>> {
     if context.state().unwrap().nucleus().status != NucleusStatus::New {
@@ -35,26 +51,28 @@ pub fn initialize_application(
     }
 
     let context_clone = context.clone();
+    let membrane_proof = membrane_proof.map(String::from);
 
-    thread::spawn(move || {
+    let scheduled = context.executor.spawn(Pool::ZomeCalls, move || {
         let action_wrapper = ActionWrapper::new(Action::InitApplication(dna.clone()));
-        dispatch_action_and_wait(
+        if let Err(error) = dispatch_action_and_wait(
             &context_clone.action_channel,
             &context_clone.observer_channel,
             action_wrapper.clone(),
-        );
+        ) {
+            // the action loop is gone or stuck, the initialization can't go on without it
+            let _ = context_clone.action_channel.send(ActionWrapper::new(
+                Action::ReturnInitializationResult(Some(error.to_string())),
+            ));
+            return;
+        }
 
-        // Commit DNA to chain
+        // Validate and commit DNA to chain
         let dna_entry = dna.to_entry();
-        let dna_commit = block_on(commit_entry(
-            dna_entry,
-            &context_clone.action_channel.clone(),
-            &context_clone,
-        ));
+        let dna_commit = commit_genesis_entry(dna_entry, None, &context_clone);
 
-        // Let initialization fail if DNA could not be committed.
-        // Currently this cannot happen since ToEntry for Dna always creates
-        // an entry from a Dna object. So I can't create a test for the code below.
+        // Let initialization fail if DNA could not be validated or committed.
+        // The DNA of a new chain always sits at its first position, so this should not happen.
         // Hence skipping it for codecov for now but leaving it in for resilience.
         #[cfg_attr(tarpaulin, skip)]
         {
@@ -69,19 +87,22 @@ pub fn initialize_application(
             };
         }
 
-        // Commit AgentId to chain
+        // Validate and commit AgentId to chain, followed by the membrane proof if there is one
         let agent_id_entry = context_clone.agent.to_entry();
-        let agent_id_commit = block_on(commit_entry(
-            agent_id_entry,
-            &context_clone.action_channel.clone(),
-            &context_clone,
-        ));
-
-        // Let initialization fail if AgentId could not be committed.
-        // Currently this cannot happen since ToEntry for Agent always creates
-        // an entry from an Agent object. So I can't create a test for the code below.
-        // Hence skipping it for codecov for now but leaving it in for resilience.
-        #[cfg_attr(tarpaulin, skip)]
+        let agent_id_commit =
+            commit_genesis_entry(agent_id_entry, membrane_proof.clone(), &context_clone).and_then(
+                |address| match membrane_proof.clone() {
+                    Some(proof) => commit_genesis_entry(
+                        Entry::new(EntryType::MembraneProof, JsonString::from(proof)),
+                        None,
+                        &context_clone,
+                    ),
+                    None => Ok(address),
+                },
+            );
+
+        // Let initialization fail if AgentId could not be validated or committed,
+        // e.g. because a zome does not admit the agent or its membrane proof.
         {
             if agent_id_commit.is_err() {
                 context_clone
@@ -98,7 +119,13 @@ pub fn initialize_application(
         let results: Vec<_> = dna
             .zomes
             .keys()
-            .map(|zome_name| genesis(context_clone.clone(), zome_name, &CallbackParams::Genesis))
+            .map(|zome_name| {
+                genesis(
+                    context_clone.clone(),
+                    zome_name,
+                    &CallbackParams::Genesis(membrane_proof.clone()),
+                )
+            })
             .collect();
 
         let fail_result = results.iter().find(|ref r| match r {
@@ -106,11 +133,18 @@ pub fn initialize_application(
             _ => false,
         });
 
-        let maybe_error = fail_result.and_then(|result| match result {
+        let mut maybe_error = fail_result.and_then(|result| match result {
             CallbackResult::Fail(error_string) => Some(error_string.clone()),
             _ => None,
         });
 
+        // Start answering direct messages from other agents
+        if maybe_error.is_none() {
+            maybe_error = register_message_receiver(&context_clone, &dna)
+                .map_err(|e| e.to_string())
+                .err();
+        }
+
         context_clone
             .action_channel
             .send(ActionWrapper::new(Action::ReturnInitializationResult(
@@ -118,6 +152,9 @@ pub fn initialize_application(
             )))
             .expect("Action channel not usable in initialize_application()");
     });
+    if let Err(error) = scheduled {
+        return Box::new(future::err(error.to_string()));
+    }
 
     Box::new(InitializationFuture {
         context: context.clone(),
@@ -125,6 +162,36 @@ pub fn initialize_application(
     })
 }
 
+/// Validates a system entry of genesis against the chain and commits it.
+/// There is no zome to build a validation package for system entries, they only come
+/// with their chain header.
+fn commit_genesis_entry(
+    entry: Entry,
+    membrane_proof: Option<String>,
+    context: &Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let header = create_new_chain_header(&entry, &*context.state().unwrap().agent());
+    let validation_data = ValidationData {
+        package: ValidationPackage::only_header(header),
+        sources: vec![HashString::from("<insert your agent key here>")],
+        lifecycle: EntryLifecycle::Chain,
+        action: EntryAction::Commit,
+        bundle: None,
+        old_entry: None,
+        old_entry_header: None,
+        membrane_proof,
+        link_action: None,
+    };
+    block_on(
+        validate_entry(
+            entry.entry_type().clone(),
+            entry.clone(),
+            validation_data,
+            context,
+        ).and_then(|_| commit_entry(entry, &context.action_channel, context)),
+    )
+}
+
 /// InitializationFuture resolves to an Ok(NucleusStatus) or an Err(String).
 /// Tracks the nucleus status.
 pub struct InitializationFuture {
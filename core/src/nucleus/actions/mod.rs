@@ -1,5 +1,6 @@
 pub mod build_validation_package;
 pub mod get_entry;
+pub mod get_links;
 pub mod initialize;
 pub mod validate;
 
@@ -45,6 +46,11 @@ pub mod tests {
             .unwrap()
             .entry_types
             .insert(String::from("package_chain_full"), EntryTypeDef::new());
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .insert(String::from("package_selection"), EntryTypeDef::new());
 
         let (instance, context) =
             test_instance_and_context(dna).expect("Could not create test instance");
@@ -82,6 +88,14 @@ pub mod tests {
         )
     }
 
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_entry_package_selection() -> Entry {
+        Entry::new(
+            EntryType::App(String::from("package_selection")),
+            "test value",
+        )
+    }
+
     #[cfg_attr(tarpaulin, skip)]
     pub fn commit(entry: Entry, context: &Arc<Context>) -> ChainHeader {
         let chain = context.state().unwrap().agent().chain();
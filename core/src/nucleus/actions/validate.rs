@@ -2,14 +2,21 @@ extern crate futures;
 extern crate serde_json;
 use action::{Action, ActionWrapper};
 use context::Context;
+use executor::Pool;
 use futures::{future, Async, Future};
 use holochain_core_types::{
     cas::content::AddressableContent, entry::Entry, entry_type::EntryType, error::HolochainError,
     hash::HashString, validation::ValidationData,
 };
-use nucleus::ribosome::callback::{self, CallbackResult};
+use nucleus::{
+    ribosome::callback::{self, CallbackResult},
+    validation_cache::{
+        cached_result, dependencies_hash, dht_dependencies, dna_hash, record_result,
+        recording_reads, ValidationRecord,
+    },
+};
 use snowflake;
-use std::{sync::Arc, thread};
+use std::sync::Arc;
 
 /// ValidateEntry Action Creator
 /// This is the high-level validate function that wraps the whole validation process and is what should
@@ -33,25 +40,49 @@ pub fn validate_entry(
         .unwrap()
         .get_zome_name_for_entry_type(&entry_type.to_string())
     {
-        None => {
+        // System entries are not defined by a zome but validated by core
+        None if entry_type.is_app() => {
             return Box::new(future::err(HolochainError::ValidationFailed(format!(
                 "Unknown entry type: '{}'",
                 entry_type.to_string(),
             ))));;
         }
-        Some(_) => {
+        _ => {
+            // Entries validated before with the same dependencies don't need to be validated again
+            let dna_hash = dna_hash(&context.state().unwrap().nucleus().dna().unwrap());
+            let dependencies = match dependencies_hash(&entry_type, &validation_data) {
+                Ok(dependencies) => dependencies,
+                Err(error) => return Box::new(future::err(error)),
+            };
+            match cached_result(context, &dna_hash, &address, &dependencies) {
+                Ok(Some(Ok(()))) => return Box::new(future::ok(address)),
+                Ok(Some(Err(error))) => {
+                    return Box::new(future::err(HolochainError::ValidationFailed(error)))
+                }
+                // a cache that can't be read only costs a new validation
+                _ => (),
+            }
+
             let id = id.clone();
             let address = address.clone();
             let entry = entry.clone();
-            let context = context.clone();
-            thread::spawn(move || {
-                let maybe_validation_result = callback::validate_entry::validate_entry(
-                    entry.clone(),
-                    entry_type.clone(),
-                    validation_data.clone(),
-                    context.clone(),
-                );
+            let job_context = context.clone();
+            let scheduled = context.executor.spawn(Pool::Validation, move || {
+                let context = job_context;
+                let (maybe_validation_result, reads) = recording_reads(|| {
+                    callback::validate_entry::validate_entry(
+                        entry.clone(),
+                        entry_type.clone(),
+                        validation_data.clone(),
+                        context.clone(),
+                    )
+                });
 
+                // Only keep what the validation callback decided
+                let decided = match maybe_validation_result {
+                    Ok(CallbackResult::Pass) | Ok(CallbackResult::Fail(_)) => true,
+                    _ => false,
+                };
                 let result = match maybe_validation_result {
                     Ok(validation_result) => match validation_result {
                         CallbackResult::Fail(error_string) => Err(error_string),
@@ -65,6 +96,27 @@ pub fn validate_entry(
                     Err(error) => Err(error.to_string()),
                 };
 
+                if decided {
+                    let recorded = dht_dependencies(&context, reads).and_then(|dht_dependencies| {
+                        record_result(
+                            &context,
+                            &ValidationRecord {
+                                entry_address: address.clone(),
+                                dna_hash,
+                                dependencies,
+                                dht_dependencies,
+                                result: result.clone(),
+                            },
+                        )
+                    });
+                    if let Err(error) = recorded {
+                        let _ = context.log(&format!(
+                            "could not record validation of {}: {}",
+                            address, error
+                        ));
+                    }
+                }
+
                 context
                     .action_channel
                     .send(ActionWrapper::new(Action::ReturnValidationResult((
@@ -73,6 +125,9 @@ pub fn validate_entry(
                     ))))
                     .expect("action channel to be open in reducer");
             });
+            if let Err(error) = scheduled {
+                return Box::new(future::err(error));
+            }
         }
     };
 
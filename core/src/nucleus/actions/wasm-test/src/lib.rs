@@ -71,6 +71,23 @@ define_zome! {
                 hdk::ValidationPackageDefinition::ChainFull
             },
 
+            validation: |entry: TestEntryType, _ctx: hdk::ValidationData| {
+                (entry.stuff != "FAIL")
+                    .ok_or_else(|| "FAIL content is not allowed".to_string())
+            }
+        ),
+
+        entry!(
+            name: "package_selection",
+            description: "asdfda",
+            sharing: Sharing::Public,
+
+            validation_package: || {
+                hdk::ValidationPackageDefinition::Selection(
+                    hdk::ChainSelector::EntryTypes(vec!["package_entry".to_string()])
+                )
+            },
+
             validation: |entry: TestEntryType, _ctx: hdk::ValidationData| {
                 (entry.stuff != "FAIL")
                     .ok_or_else(|| "FAIL content is not allowed".to_string())
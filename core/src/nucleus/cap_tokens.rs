@@ -0,0 +1,310 @@
+//! Capability grants: an agent commits CapTokenGrant entries to its source chain
+//! to give callers that would not get through the membrane of a capability access
+//! to some of its functions. The address of the grant is the token the caller has to
+//! present with its calls, until the agent revokes it with a CapTokenRevocation entry.
+//! Every grant carries a random nonce, so tokens can't be guessed and granting the same
+//! access again after a revocation yields a new token.
+
+use agent::actions::commit::commit_entry;
+use context::Context;
+use futures::executor::block_on;
+use holochain_core_types::{
+    cap_grant::{CapToken, CapTokenGrant, CapTokenRevocation},
+    cas::{content::Address, storage::ContentAddressableStorage},
+    entry::{Entry, ToEntry},
+    entry_type::EntryType,
+    error::HolochainError,
+};
+use nucleus::{Provenance, ZomeFnCall};
+use rand::{thread_rng, Rng};
+use rust_base58::ToBase58;
+use std::sync::Arc;
+
+/// Grants access to the given functions of a capability of a zome of the running DNA,
+/// to the given agents only or to anyone holding the token if there are no assignees.
+/// Returns the token of the grant.
+pub fn grant_capability(
+    context: &Arc<Context>,
+    zome: &str,
+    capability: &str,
+    functions: Vec<String>,
+    assignees: Option<Vec<Address>>,
+) -> Result<CapToken, HolochainError> {
+    {
+        let state = context.state().ok_or(HolochainError::DnaMissing)?;
+        let dna = state.nucleus().dna().ok_or(HolochainError::DnaMissing)?;
+        let cap = dna
+            .get_capability_with_zome_name(zome, capability)
+            .map_err(HolochainError::Dna)?;
+        if let Some(function) = functions
+            .iter()
+            .find(|function| !cap.functions.iter().any(|decl| &decl.name == *function))
+        {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "function '{}' is not part of capability '{}'",
+                function, capability
+            )));
+        }
+    }
+
+    let mut nonce = [0u8; 32];
+    thread_rng().fill(&mut nonce);
+    let grant = CapTokenGrant::new(zome, capability, functions, assignees, &nonce.to_base58());
+    block_on(commit_entry(
+        grant.to_entry(),
+        &context.action_channel,
+        context,
+    ))
+}
+
+/// Revokes a grant of the agent, calls presenting its token get rejected from then on.
+pub fn revoke_capability(context: &Arc<Context>, token: &CapToken) -> Result<(), HolochainError> {
+    if find_grant(context, token)?.is_none() {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "capability token '{}' is unknown or already revoked",
+            token
+        )));
+    }
+
+    let revocation = CapTokenRevocation::new(token.clone());
+    block_on(commit_entry(
+        revocation.to_entry(),
+        &context.action_channel,
+        context,
+    )).map(|_| ())
+}
+
+/// Checks the token of a zome function call grants access to the called function,
+/// and that the caller is one of its assignees if it has any.
+/// Fails with HolochainError::DoesNotHaveCapabilityToken otherwise.
+pub fn verify_token(context: &Arc<Context>, zome_call: &ZomeFnCall) -> Result<(), HolochainError> {
+    let token = zome_call
+        .token
+        .as_ref()
+        .ok_or(HolochainError::DoesNotHaveCapabilityToken)?;
+    let grant = find_grant(context, token)?.ok_or(HolochainError::DoesNotHaveCapabilityToken)?;
+
+    if !grant.grants(
+        &zome_call.zome_name,
+        &zome_call.cap_name,
+        &zome_call.fn_name,
+    ) {
+        return Err(HolochainError::DoesNotHaveCapabilityToken);
+    }
+    match (grant.assignees(), &zome_call.provenance) {
+        (None, _) => Ok(()),
+        (Some(_), Provenance::RemoteAgent(agent)) if grant.is_assigned_to(agent) => Ok(()),
+        _ => Err(HolochainError::DoesNotHaveCapabilityToken),
+    }
+}
+
+/// The grant with the given token on the source chain of the agent, None if there is none
+/// or if it got revoked.
+fn find_grant(
+    context: &Arc<Context>,
+    token: &CapToken,
+) -> Result<Option<CapTokenGrant>, HolochainError> {
+    let revoked = chain_entries(context, &EntryType::CapTokenRevocation)?
+        .iter()
+        .map(CapTokenRevocation::from_entry)
+        .any(|revocation| revocation.revoked_token() == token);
+    if revoked {
+        return Ok(None);
+    }
+
+    Ok(chain_entries(context, &EntryType::CapTokenGrant)?
+        .into_iter()
+        .find(|entry| &entry.address() == token)
+        .map(|entry| CapTokenGrant::from_entry(&entry)))
+}
+
+/// All entries of the given type on the source chain of the agent, latest first
+fn chain_entries(
+    context: &Arc<Context>,
+    entry_type: &EntryType,
+) -> Result<Vec<Entry>, HolochainError> {
+    let state = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("state is not initialized".to_string()))?;
+    let chain = state.agent().chain();
+    let mut entries = Vec::new();
+    for header in chain.iter_type(&state.agent().top_chain_header(), entry_type) {
+        if let Some(entry) = chain
+            .content_storage()
+            .fetch::<Entry>(header.entry_address())?
+        {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+
+    use super::*;
+    use holochain_dna::zome::capabilities::{FnDeclaration, Membrane};
+    use instance::tests::test_instance_and_context;
+
+    /// context of an instance with an api-key capability "test_cap" with functions "test"
+    /// and "other" in zome "test_zome"
+    pub fn test_cap_tokens_context() -> Arc<Context> {
+        let mut capability = test_utils::create_test_cap(Membrane::ApiKey);
+        for name in vec!["test", "other"] {
+            let mut fn_decl = FnDeclaration::new();
+            fn_decl.name = name.to_string();
+            capability.functions.push(fn_decl);
+        }
+        let dna = test_utils::create_test_dna_with_cap("test_zome", "test_cap", &capability, &[]);
+        let (_instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        context
+    }
+
+    fn test_call(fn_name: &str, token: Option<CapToken>, provenance: Provenance) -> ZomeFnCall {
+        let mut zome_call = ZomeFnCall::new("test_zome", "test_cap", fn_name, "{}");
+        zome_call.token = token;
+        zome_call.provenance = provenance;
+        zome_call
+    }
+
+    #[test]
+    fn can_grant_and_revoke() {
+        let context = test_cap_tokens_context();
+        let token = grant_capability(
+            &context,
+            "test_zome",
+            "test_cap",
+            vec!["test".to_string()],
+            None,
+        ).expect("grant should be committed");
+
+        assert_eq!(
+            Ok(()),
+            verify_token(
+                &context,
+                &test_call("test", Some(token.clone()), Provenance::External)
+            )
+        );
+        // only granted functions
+        assert_eq!(
+            Err(HolochainError::DoesNotHaveCapabilityToken),
+            verify_token(
+                &context,
+                &test_call("other", Some(token.clone()), Provenance::External)
+            )
+        );
+
+        assert_eq!(Ok(()), revoke_capability(&context, &token));
+        assert_eq!(
+            Err(HolochainError::DoesNotHaveCapabilityToken),
+            verify_token(
+                &context,
+                &test_call("test", Some(token.clone()), Provenance::External)
+            )
+        );
+        assert!(revoke_capability(&context, &token).is_err());
+    }
+
+    #[test]
+    fn can_grant_again_after_revoking() {
+        let context = test_cap_tokens_context();
+        let grant = || {
+            grant_capability(
+                &context,
+                "test_zome",
+                "test_cap",
+                vec!["test".to_string()],
+                None,
+            ).expect("grant should be committed")
+        };
+        let token = grant();
+        assert_eq!(Ok(()), revoke_capability(&context, &token));
+
+        // the same access granted again comes with a new token
+        let new_token = grant();
+        assert_ne!(token, new_token);
+        assert_eq!(
+            Ok(()),
+            verify_token(
+                &context,
+                &test_call("test", Some(new_token), Provenance::External)
+            )
+        );
+        assert_eq!(
+            Err(HolochainError::DoesNotHaveCapabilityToken),
+            verify_token(
+                &context,
+                &test_call("test", Some(token), Provenance::External)
+            )
+        );
+    }
+
+    #[test]
+    fn only_assignees_can_use_token() {
+        let context = test_cap_tokens_context();
+        let token = grant_capability(
+            &context,
+            "test_zome",
+            "test_cap",
+            vec!["test".to_string()],
+            Some(vec![Address::from("bob")]),
+        ).expect("grant should be committed");
+
+        assert_eq!(
+            Ok(()),
+            verify_token(
+                &context,
+                &test_call(
+                    "test",
+                    Some(token.clone()),
+                    Provenance::RemoteAgent(Address::from("bob"))
+                )
+            )
+        );
+        for provenance in vec![
+            Provenance::RemoteAgent(Address::from("eve")),
+            Provenance::External,
+        ] {
+            assert_eq!(
+                Err(HolochainError::DoesNotHaveCapabilityToken),
+                verify_token(
+                    &context,
+                    &test_call("test", Some(token.clone()), provenance)
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_missing_and_unknown_tokens() {
+        let context = test_cap_tokens_context();
+
+        for token in vec![None, Some(Address::from("not a token"))] {
+            assert_eq!(
+                Err(HolochainError::DoesNotHaveCapabilityToken),
+                verify_token(&context, &test_call("test", token, Provenance::External))
+            );
+        }
+    }
+
+    #[test]
+    fn fails_to_grant_unknown_functions() {
+        let context = test_cap_tokens_context();
+
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "function 'missing' is not part of capability 'test_cap'".to_string()
+            )),
+            grant_capability(
+                &context,
+                "test_zome",
+                "test_cap",
+                vec!["missing".to_string()],
+                None
+            )
+        );
+        assert!(grant_capability(&context, "test_zome", "missing_cap", vec![], None).is_err());
+    }
+}
@@ -1,38 +1,80 @@
 /// Nucleus is the module that handles DNA, including the Ribosome.
 ///
 pub mod actions;
+pub mod cap_tokens;
 pub mod ribosome;
 pub mod state;
+pub mod validation_cache;
 
 use action::{Action, ActionWrapper, NucleusReduceFn};
 use context::Context;
+use executor::Pool;
 use holochain_core_types::{
+    cap_grant::CapToken,
+    cas::content::Address,
     error::{DnaError, HcResult, HolochainError},
     json::JsonString,
 };
-use holochain_dna::{wasm::DnaWasm, zome::capabilities::Capability, Dna};
-use instance::{dispatch_action_with_observer, Observer};
+use holochain_dna::{
+    wasm::DnaWasm,
+    zome::capabilities::{Capability, FnDeclaration, Membrane},
+    Dna,
+};
+use instance::{dispatch_action_and_wait_for, Observer, RECV_DEFAULT_TIMEOUT_MS};
 use nucleus::{
+    cap_tokens::verify_token,
     ribosome::api::call::reduce_call,
     state::{NucleusState, NucleusStatus},
 };
 use snowflake;
 use std::{
-    sync::{
-        mpsc::{sync_channel, SyncSender},
-        Arc,
-    },
-    thread,
+    sync::{mpsc::SyncSender, Arc},
+    time::Duration,
 };
 
+/// Who is calling a Zome function.
+/// The membrane of the called capability decides which callers get through.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Provenance {
+    /// A client of the instance, like a UI or the container
+    External,
+    /// The agent running the instance, for calls the instance makes itself
+    Agent,
+    /// A zome of the same DNA, by name
+    Zome(String),
+    /// Another agent of the same DNA, by address
+    RemoteAgent(Address),
+}
+
+impl Provenance {
+    /// Public capabilities can be called by anyone, agent capabilities by anything local
+    /// to the instance, and zome capabilities only by zomes.
+    /// Nobody can pass an api-key membrane without a capability token.
+    pub fn can_pass(&self, membrane: &Membrane) -> bool {
+        match (membrane, self) {
+            (Membrane::Public, _) => true,
+            (Membrane::Agent, Provenance::RemoteAgent(_)) => false,
+            (Membrane::Agent, _) => true,
+            (Membrane::Zome, Provenance::Zome(_)) => true,
+            (Membrane::Zome, _) => false,
+            (Membrane::ApiKey, _) => false,
+        }
+    }
+}
+
 /// Struct holding data for requesting the execution of a Zome function (ExecutionZomeFunction Action)
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ZomeFnCall {
     id: snowflake::ProcessUniqueId,
     pub zome_name: String,
     pub cap_name: String,
     pub fn_name: String,
+    #[serde(with = "::action::serialize_json_string")]
     pub parameters: JsonString,
+    /// Who is making the call, the agent of the instance unless set otherwise
+    pub provenance: Provenance,
+    /// Token of a capability grant, for callers that can't pass the membrane by themselves
+    pub token: Option<CapToken>,
 }
 
 impl ZomeFnCall {
@@ -50,6 +92,8 @@ impl ZomeFnCall {
             cap_name: capability.to_string(),
             fn_name: function.to_string(),
             parameters: parameters.into(),
+            provenance: Provenance::Agent,
+            token: None,
         }
     }
 
@@ -78,70 +122,93 @@ impl EntrySubmission {
     }
 }
 
-/// Dispatch ExecuteZoneFunction to and block until call has finished.
+/// Dispatch ExecuteZoneFunction to and block until call has finished,
+/// but not longer than RECV_DEFAULT_TIMEOUT_MS.
+/// Fails with HolochainError::Timeout if there is no result in time.
 pub fn call_zome_and_wait_for_result(
     call: ZomeFnCall,
     action_channel: &SyncSender<ActionWrapper>,
     observer_channel: &SyncSender<Observer>,
 ) -> Result<JsonString, HolochainError> {
-    let call_action_wrapper = ActionWrapper::new(Action::ExecuteZomeFunction(call.clone()));
-
-    // Dispatch action with observer closure that waits for a result in the state
-    let (sender, receiver) = sync_channel(1);
-    dispatch_action_with_observer(
+    wait_for_zome_call_result(
+        call,
         action_channel,
         observer_channel,
-        call_action_wrapper,
-        move |state: &super::state::State| {
-            if let Some(result) = state.nucleus().zome_call_result(&call) {
-                sender
-                    .send(result.clone())
-                    .expect("local channel to be open");
-                true
-            } else {
-                false
-            }
-        },
-    );
-    // Block until we got that result through the channel:
-    receiver.recv().expect("local channel to work")
+        RECV_DEFAULT_TIMEOUT_MS,
+    )
+}
+
+/// Dispatch ExecuteZoneFunction and block until call has finished,
+/// but not longer than the given timeout.
+/// Fails with HolochainError::Timeout if there is no result in time.
+/// The call itself keeps running, only its result gets dropped.
+pub fn call_zome_and_wait_for_result_with_timeout(
+    call: ZomeFnCall,
+    action_channel: &SyncSender<ActionWrapper>,
+    observer_channel: &SyncSender<Observer>,
+    timeout: Duration,
+) -> Result<JsonString, HolochainError> {
+    wait_for_zome_call_result(call, action_channel, observer_channel, timeout)
 }
 
-/// Dispatch ExecuteZoneFunction to Instance and block until call has finished.
+/// Dispatch ExecuteZoneFunction to Instance and block until call has finished,
+/// but not longer than RECV_DEFAULT_TIMEOUT_MS.
 /// for test only??
 pub fn call_and_wait_for_result(
     call: ZomeFnCall,
     instance: &mut super::instance::Instance,
 ) -> Result<JsonString, HolochainError> {
-    let call_action = ActionWrapper::new(Action::ExecuteZomeFunction(call.clone()));
+    call_zome_and_wait_for_result(
+        call,
+        &instance.action_channel(),
+        &instance.observer_channel(),
+    )
+}
 
-    // Dispatch action with observer closure that waits for a result in the state
-    let (sender, receiver) = sync_channel(1);
-    instance.dispatch_with_observer(call_action, move |state: &super::state::State| {
-        if let Some(result) = state.nucleus().zome_call_result(&call) {
-            sender
-                .send(result.clone())
-                .expect("local channel to be open");
-            true
-        } else {
-            false
-        }
-    });
+/// Dispatch ExecuteZoneFunction to Instance and block until call has finished,
+/// but not longer than the given timeout.
+pub fn call_and_wait_for_result_with_timeout(
+    call: ZomeFnCall,
+    instance: &mut super::instance::Instance,
+    timeout: Duration,
+) -> Result<JsonString, HolochainError> {
+    call_zome_and_wait_for_result_with_timeout(
+        call,
+        &instance.action_channel(),
+        &instance.observer_channel(),
+        timeout,
+    )
+}
 
-    // Block until we got that result through the channel:
-    receiver.recv().expect("local channel to work")
+fn wait_for_zome_call_result(
+    call: ZomeFnCall,
+    action_channel: &SyncSender<ActionWrapper>,
+    observer_channel: &SyncSender<Observer>,
+    timeout: Duration,
+) -> Result<JsonString, HolochainError> {
+    let call_action_wrapper = ActionWrapper::new(Action::ExecuteZomeFunction(call.clone()));
+
+    // Dispatch action with observer closure that waits for a result in the state
+    dispatch_action_and_wait_for(
+        action_channel,
+        observer_channel,
+        call_action_wrapper,
+        Some(timeout),
+        move |state: &super::state::State| state.nucleus().zome_call_result(&call),
+    )?
 }
 
 pub type ZomeFnResult = HcResult<JsonString>;
 
-#[derive(Clone, Debug, PartialEq, Hash)]
+#[derive(Clone, Debug, PartialEq, Hash, Serialize, Deserialize)]
 pub struct ExecuteZomeFnResponse {
     call: ZomeFnCall,
+    #[serde(with = "::action::serialize_zome_fn_result")]
     result: ZomeFnResult,
 }
 
 impl ExecuteZomeFnResponse {
-    fn new(call: ZomeFnCall, result: Result<JsonString, HolochainError>) -> Self {
+    pub(crate) fn new(call: ZomeFnCall, result: Result<JsonString, HolochainError>) -> Self {
         ExecuteZomeFnResponse { call, result }
     }
 
@@ -210,33 +277,80 @@ fn reduce_init_application(
     }
 }
 
+/// Runs the zome function on the current thread.
+/// If a function declaration is given, the result gets checked against its outputs.
+pub(crate) fn run_zome_fn_call(
+    context: Arc<Context>,
+    zome_call: &ZomeFnCall,
+    wasm: &DnaWasm,
+    dna_name: &str,
+    fn_declaration: Option<FnDeclaration>,
+) -> ZomeFnResult {
+    // Have Ribosome spin up DNA and call the zome function
+    let call_result = ribosome::run_dna(
+        dna_name,
+        context,
+        wasm.code.clone(),
+        zome_call,
+        Some(zome_call.clone().parameters.into_bytes()),
+    );
+    // Check the result against the declared output
+    match fn_declaration {
+        Some(fn_declaration) => call_result.and_then(|result| {
+            fn_declaration
+                .check_output(&String::from(result.clone()))
+                .map(|_| result)
+                .map_err(HolochainError::Dna)
+        }),
+        None => call_result,
+    }
+}
+
+/// Runs the zome function on a worker of the zome call pool.
+/// If a function declaration is given, the result gets checked against its outputs.
 pub(crate) fn launch_zome_fn_call(
     context: Arc<Context>,
     zome_call: ZomeFnCall,
     wasm: &DnaWasm,
     dna_name: String,
+    fn_declaration: Option<FnDeclaration>,
 ) {
-    let code = wasm.code.clone();
+    // the outcome of the call is recorded in the journal that is being replayed
+    if context.replaying() {
+        return;
+    }
+    let wasm = wasm.clone();
+    let job_context = context.clone();
+    let job_call = zome_call.clone();
 
-    thread::spawn(move || {
-        // Have Ribosome spin up DNA and call the zome function
-        let call_result = ribosome::run_dna(
+    let job = move || {
+        let call_result = run_zome_fn_call(
+            job_context.clone(),
+            &job_call,
+            &wasm,
             &dna_name,
-            context.clone(),
-            code,
-            &zome_call,
-            Some(zome_call.clone().parameters.into_bytes()),
+            fn_declaration,
         );
         // Construct response
-        let response = ExecuteZomeFnResponse::new(zome_call.clone(), call_result);
+        let response = ExecuteZomeFnResponse::new(job_call.clone(), call_result);
         // Send ReturnZomeFunctionResult Action
+        job_context
+            .action_channel
+            .send(ActionWrapper::new(Action::ReturnZomeFunctionResult(
+                response,
+            )))
+            .expect("action channel to be open in reducer");
+    };
+
+    if let Err(error) = context.executor.spawn(Pool::ZomeCalls, job) {
+        let response = ExecuteZomeFnResponse::new(zome_call, Err(error));
         context
             .action_channel
             .send(ActionWrapper::new(Action::ReturnZomeFunctionResult(
                 response,
             )))
             .expect("action channel to be open in reducer");
-    });
+    }
 }
 
 /// Reduce ExecuteZomeFunction Action
@@ -315,14 +429,31 @@ fn reduce_execute_zome_function(
         .functions
         .iter()
         .find(|&fn_declaration| fn_declaration.name == fn_call.fn_name);
-    if maybe_fn.is_none() {
+    let fn_declaration = match maybe_fn {
+        None => {
+            dispatch_error_result(
+                &context.action_channel,
+                &fn_call,
+                HolochainError::Dna(DnaError::ZomeFunctionNotFound(format!(
+                    "Zome function '{}' not found",
+                    fn_call.fn_name.clone()
+                ))),
+            );
+            return;
+        }
+        Some(fn_declaration) => fn_declaration,
+    };
+    // Check the caller is allowed through the membrane of the capability
+    if let Err(error) = check_membrane(&context, capability, &fn_call) {
+        dispatch_error_result(&context.action_channel, &fn_call, error);
+        return;
+    }
+    // Check the parameters against the declared inputs
+    if let Err(error) = fn_declaration.check_inputs(&fn_call.parameters) {
         dispatch_error_result(
             &context.action_channel,
             &fn_call,
-            HolochainError::Dna(DnaError::ZomeFunctionNotFound(format!(
-                "Zome function '{}' not found",
-                fn_call.fn_name.clone()
-            ))),
+            HolochainError::Dna(error),
         );
         return;
     }
@@ -335,6 +466,11 @@ fn reduce_execute_zome_function(
         fn_call,
         &zome.code,
         state.dna.clone().unwrap().name,
+        if capability.check_outputs {
+            Some(fn_declaration.clone())
+        } else {
+            None
+        },
     );
 }
 
@@ -426,18 +562,36 @@ fn get_capability_with_zome_call(
     }
 }
 
+/// Fails with HolochainError::DoesNotHaveCapabilityToken
+/// if the caller of a ZomeFnCall can't pass the membrane of the called capability
+/// and does not present a token that grants access to the called function.
+pub(crate) fn check_membrane(
+    context: &Arc<Context>,
+    capability: &Capability,
+    zome_call: &ZomeFnCall,
+) -> Result<(), HolochainError> {
+    if zome_call.provenance.can_pass(&capability.cap_type.membrane) {
+        return Ok(());
+    }
+    match capability.cap_type.membrane {
+        // Zome capabilities are internal to the DNA, there is no token for them
+        Membrane::Zome => Err(HolochainError::DoesNotHaveCapabilityToken),
+        _ => verify_token(context, zome_call),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     extern crate test_utils;
     use super::*;
     use action::{tests::test_action_wrapper_rzfr, ActionWrapper};
-    use holochain_dna::Dna;
+    use holochain_dna::{zome::capabilities::FnParameter, Dna};
     use instance::{
         tests::{test_context, test_context_with_channels, test_instance},
         Instance,
     };
     use nucleus::state::tests::test_nucleus_state;
-    use std::sync::Arc;
+    use std::sync::{mpsc::sync_channel, Arc};
 
     use holochain_core_types::json::{JsonString, RawString};
     use std::error::Error;
@@ -616,6 +770,140 @@ pub mod tests {
         assert_eq!(JsonString::from(RawString::from(1337)), result.unwrap());
     }
 
+    #[test]
+    /// tests that waiting for a zome call gives up after the timeout
+    fn call_zome_function_with_timeout() {
+        // main only returns when the zome aborts it after a second
+        let dna = test_utils::create_test_dna_with_blocking_main("test_zome", "test_cap", 1000);
+        let mut instance = test_instance(dna).expect("Could not initialize test instance");
+
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "");
+        let result = super::call_and_wait_for_result_with_timeout(
+            zome_call,
+            &mut instance,
+            Duration::from_millis(100),
+        );
+        assert_eq!(Err(HolochainError::Timeout), result);
+
+        // the instance keeps working after a waiter gave up
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "");
+        let result = super::call_and_wait_for_result_with_timeout(
+            zome_call,
+            &mut instance,
+            Duration::from_secs(10),
+        );
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call timed out (limit: 1000ms)".to_string()
+            )),
+            result
+        );
+    }
+
+    #[test]
+    /// tests that calls from callers outside of the membrane of the capability are rejected
+    fn call_zome_function_outside_membrane() {
+        // agent membrane by default
+        let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        let mut instance = test_instance(dna).expect("Could not initialize test instance");
+
+        let mut zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "");
+        zome_call.provenance = Provenance::RemoteAgent(Address::from("bob"));
+
+        let result = super::call_and_wait_for_result(zome_call, &mut instance);
+
+        assert_eq!(Err(HolochainError::DoesNotHaveCapabilityToken), result);
+    }
+
+    #[test]
+    /// tests that calls with parameters not matching the declared inputs are rejected
+    fn call_zome_function_with_invalid_parameters() {
+        let mut dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .capabilities
+            .get_mut("test_cap")
+            .unwrap()
+            .functions[0]
+            .inputs
+            .push(FnParameter::new("post", "string"));
+        let mut instance = test_instance(dna).expect("Could not initialize test instance");
+
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "{}");
+        let result = super::call_and_wait_for_result(zome_call, &mut instance);
+
+        assert_eq!(
+            Err(HolochainError::Dna(DnaError::ZomeFunctionInputInvalid(
+                String::from("Input 'post' of zome function 'main' is missing")
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn call_zome_function_checks_outputs_only_if_enabled() {
+        let mut dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .capabilities
+            .get_mut("test_cap")
+            .unwrap()
+            .functions[0]
+            .outputs
+            .push(FnParameter::new("result", "string"));
+        let mut instance = test_instance(dna.clone()).expect("Could not initialize test instance");
+
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "{}");
+        let result = super::call_and_wait_for_result(zome_call, &mut instance);
+        assert!(result.is_ok());
+
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .capabilities
+            .get_mut("test_cap")
+            .unwrap()
+            .check_outputs = true;
+        let mut instance = test_instance(dna).expect("Could not initialize test instance");
+
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "{}");
+        let result = super::call_and_wait_for_result(zome_call, &mut instance);
+        assert_eq!(
+            Err(HolochainError::Dna(DnaError::ZomeFunctionOutputInvalid(
+                String::from("Output 'result' of zome function 'main' is not of type 'string'")
+            ))),
+            result
+        );
+    }
+
+    #[test]
+    fn provenance_can_pass() {
+        let remote_agent = Provenance::RemoteAgent(Address::from("bob"));
+        let zome = Provenance::Zome("test_zome".to_string());
+
+        for provenance in vec![
+            &Provenance::External,
+            &Provenance::Agent,
+            &zome,
+            &remote_agent,
+        ] {
+            assert!(provenance.can_pass(&Membrane::Public));
+            assert!(!provenance.can_pass(&Membrane::ApiKey));
+        }
+
+        assert!(Provenance::External.can_pass(&Membrane::Agent));
+        assert!(Provenance::Agent.can_pass(&Membrane::Agent));
+        assert!(zome.can_pass(&Membrane::Agent));
+        assert!(!remote_agent.can_pass(&Membrane::Agent));
+
+        assert!(!Provenance::External.can_pass(&Membrane::Zome));
+        assert!(!Provenance::Agent.can_pass(&Membrane::Zome));
+        assert!(zome.can_pass(&Membrane::Zome));
+        assert!(!remote_agent.can_pass(&Membrane::Zome));
+    }
+
     #[test]
     /// smoke test reducing over a nucleus
     fn can_reduce_execfn_action() {
@@ -0,0 +1,160 @@
+use holochain_core_types::error::{HolochainError, RibosomeErrorCode, ZomeAbort};
+use holochain_wasm_utils::memory_allocation::WasmAllocation;
+use nucleus::ribosome::{api::ZomeApiResult, HolochainTrap, Runtime};
+use wasmi::RuntimeArgs;
+
+/// ZomeApiFunction::Abort function code
+/// args: [0] pointer to the message, [1] pointer to the filename, [2] line, [3] column, as i32
+/// Both strings are AssemblyScript strings: an u32 length followed by that many UTF-16 code units.
+/// A null pointer stands for an empty string.
+/// Logs the diagnostics and traps, which fails the zome call with HolochainError::ZomeAborted.
+pub fn invoke_abort(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    let message_ptr: u32 = args.nth_checked(0)?;
+    let filename_ptr: u32 = args.nth_checked(1)?;
+    let abort = ZomeAbort {
+        message: read_assemblyscript_string(runtime, message_ptr).map_err(HolochainTrap::from)?,
+        filename: read_assemblyscript_string(runtime, filename_ptr).map_err(HolochainTrap::from)?,
+        line: args.nth_checked(2)?,
+        column: args.nth_checked(3)?,
+    };
+
+    runtime
+        .context
+        .log(&format!(
+            "zome_log:ABORT: '{}' in zome function '{}' at {}:{}:{}",
+            abort.message, runtime.zome_call.fn_name, abort.filename, abort.line, abort.column
+        ))
+        .expect("Logger should work");
+
+    Err(HolochainTrap(HolochainError::ZomeAborted(abort)).into())
+}
+
+/// Read an AssemblyScript string out of wasm memory
+fn read_assemblyscript_string(runtime: &Runtime, ptr: u32) -> Result<String, RibosomeErrorCode> {
+    if ptr == 0 {
+        return Ok(String::new());
+    }
+    let header = runtime.memory_manager.read(WasmAllocation::new(ptr, 4)?)?;
+    let length = u32::from(header[0])
+        | u32::from(header[1]) << 8
+        | u32::from(header[2]) << 16
+        | u32::from(header[3]) << 24;
+    if length == 0 {
+        return Ok(String::new());
+    }
+    let utf16_length = length
+        .checked_mul(2)
+        .ok_or(RibosomeErrorCode::NotAnAllocation)?;
+    let bytes = runtime
+        .memory_manager
+        .read(WasmAllocation::new(ptr + 4, utf16_length)?)?;
+    let code_units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| u16::from(pair[0]) | u16::from(pair[1]) << 8)
+        .collect();
+    Ok(String::from_utf16_lossy(&code_units))
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate wabt;
+
+    use self::wabt::Wat2Wasm;
+    use super::*;
+    use holochain_core_types::json::JsonString;
+    use instance::tests::test_context_and_logger;
+    use nucleus::{ribosome, ZomeFnCall};
+
+    /// wasm with a "test" function that aborts with the given message and filename pointers
+    /// "oops!" is stored at offset 8 and "main.ts" at offset 32
+    fn test_abort_wasm(message_ptr: u32, filename_ptr: u32) -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(format!(
+                r#"
+(module
+    (import "env" "abort"
+        (func $abort
+            (param i32 i32 i32 i32)
+        )
+    )
+
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (data (i32.const 8) "\05\00\00\00o\00o\00p\00s\00!\00")
+    (data (i32.const 32) "\07\00\00\00m\00a\00i\00n\00.\00t\00s\00")
+
+    (func
+        (export "test")
+        (param $allocation i64)
+        (result i64)
+
+        (call $abort (i32.const {}) (i32.const {}) (i32.const 12) (i32.const 3))
+        (i64.const 0)
+    )
+)
+                "#,
+                message_ptr, filename_ptr
+            ))
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec()
+    }
+
+    fn run_abort(
+        message_ptr: u32,
+        filename_ptr: u32,
+    ) -> (Result<JsonString, HolochainError>, Vec<String>) {
+        let (context, logger) = test_context_and_logger("test");
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "test", "");
+        let result = ribosome::run_dna(
+            "TestApp",
+            context,
+            test_abort_wasm(message_ptr, filename_ptr),
+            &zome_call,
+            None,
+        );
+        let log = logger.lock().unwrap().log.clone();
+        (result, log)
+    }
+
+    #[test]
+    fn aborts_call_with_diagnostics() {
+        let (result, log) = run_abort(8, 32);
+        assert_eq!(
+            Err(HolochainError::ZomeAborted(ZomeAbort {
+                message: "oops!".to_string(),
+                filename: "main.ts".to_string(),
+                line: 12,
+                column: 3,
+            })),
+            result,
+        );
+        assert!(log.contains(
+            &"zome_log:ABORT: 'oops!' in zome function 'test' at main.ts:12:3".to_string()
+        ));
+    }
+
+    #[test]
+    fn reads_null_pointer_as_empty_string() {
+        assert_eq!(
+            Err(HolochainError::ZomeAborted(ZomeAbort {
+                message: "oops!".to_string(),
+                filename: String::new(),
+                line: 12,
+                column: 3,
+            })),
+            run_abort(8, 0).0,
+        );
+    }
+
+    #[test]
+    fn errors_on_out_of_bounds_message() {
+        assert_eq!(
+            Err(HolochainError::Ribosome(RibosomeErrorCode::NotAnAllocation)),
+            run_abort(70_000, 32).0,
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use agent::actions::commit::*;
+use context::Context;
+use futures::{executor::block_on, FutureExt};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::{Entry, SerializedEntry},
+    error::HolochainError,
+    hash::HashString,
+    validation::{EntryAction, EntryLifecycle, ValidationBundle, ValidationData},
+};
+use holochain_wasm_utils::api_serialization::bundle::{BundleOnClose, StartBundleArgs};
+use nucleus::{
+    actions::{build_validation_package::*, validate::*},
+    ribosome::{api::ZomeApiResult, Runtime},
+};
+use serde_json::Value;
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// Entries committed while a bundle is open.
+/// They are only validated and added to the source chain when the bundle gets closed
+/// with BundleOnClose::Commit, a bundle that is still open at the end of the zome call
+/// is thrown away.
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    entries: Vec<Entry>,
+    deadline: Instant,
+    user_param: Value,
+}
+
+impl Bundle {
+    pub fn new(args: StartBundleArgs) -> Self {
+        Bundle {
+            entries: Vec::new(),
+            deadline: Instant::now() + Duration::from_millis(args.timeout_ms),
+            user_param: args.user_param,
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        Instant::now() > self.deadline
+    }
+}
+
+/// ZomeApiFunction::StartBundle function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: StartBundleArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_start_bundle(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let input = match StartBundleArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        Err(_) => {
+            println!(
+                "invoke_start_bundle failed to deserialize StartBundleArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = start_bundle(&mut runtime.bundle, input);
+    runtime.store_result(result)
+}
+
+/// ZomeApiFunction::CloseBundle function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: BundleOnClose
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_close_bundle(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let input = match BundleOnClose::try_from(args_str.clone()) {
+        Ok(input) => input,
+        Err(_) => {
+            println!(
+                "invoke_close_bundle failed to deserialize BundleOnClose: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = close_bundle(&runtime.context, &mut runtime.bundle, input);
+    runtime.store_result(result)
+}
+
+/// Opens a new bundle, there can only be one open bundle per zome call.
+pub fn start_bundle(
+    bundle: &mut Option<Bundle>,
+    args: StartBundleArgs,
+) -> Result<(), HolochainError> {
+    if bundle.is_some() {
+        return Err(HolochainError::ErrorGeneric(
+            "a bundle is already open".to_string(),
+        ));
+    }
+    *bundle = Some(Bundle::new(args));
+    Ok(())
+}
+
+/// Adds an entry to the open bundle instead of committing it.
+/// Returns the address the entry will have once the bundle is committed.
+/// Discards the bundle and fails with HolochainError::Timeout if it timed out.
+pub fn stage_entry(bundle: &mut Option<Bundle>, entry: Entry) -> Result<Address, HolochainError> {
+    if bundle.as_ref().map(Bundle::is_expired).unwrap_or(false) {
+        *bundle = None;
+        return Err(HolochainError::Timeout);
+    }
+    match bundle {
+        Some(bundle) => {
+            let address = entry.address();
+            bundle.entries.push(entry);
+            Ok(address)
+        }
+        None => Err(HolochainError::ErrorGeneric(
+            "no bundle is open".to_string(),
+        )),
+    }
+}
+
+/// Fails if a bundle is open.
+/// Only commits can be staged, other chain operations are refused inside a bundle
+/// so they can't slip past its validation.
+pub fn outside_bundle(bundle: &Option<Bundle>) -> Result<(), HolochainError> {
+    match bundle {
+        Some(_) => Err(HolochainError::ErrorGeneric(
+            "only commits are allowed inside a bundle".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Closes the open bundle.
+/// On BundleOnClose::Commit every staged entry is validated knowing all entries of the bundle,
+/// and only if all of them are valid they get committed, in the order they were staged.
+/// The entries are committed in a single action, so either all of them end up on the chain
+/// or none of them does.
+/// A bundle that timed out is discarded and fails with HolochainError::Timeout.
+pub fn close_bundle(
+    context: &Arc<Context>,
+    bundle: &mut Option<Bundle>,
+    action: BundleOnClose,
+) -> Result<(), HolochainError> {
+    let bundle = bundle
+        .take()
+        .ok_or_else(|| HolochainError::ErrorGeneric("no bundle is open".to_string()))?;
+    match action {
+        BundleOnClose::Discard => Ok(()),
+        BundleOnClose::Commit => if bundle.is_expired() {
+            Err(HolochainError::Timeout)
+        } else {
+            commit_bundle(context, bundle)
+        },
+    }
+}
+
+fn commit_bundle(context: &Arc<Context>, bundle: Bundle) -> Result<(), HolochainError> {
+    let validation_bundle = ValidationBundle {
+        entries: bundle
+            .entries
+            .iter()
+            .cloned()
+            .map(SerializedEntry::from)
+            .collect(),
+        user_param: bundle.user_param.clone(),
+    };
+
+    // 1. Validate all entries before committing any of them
+    for entry in bundle.entries.iter() {
+        block_on(
+            build_validation_package(entry, context)
+                .and_then(|validation_package| {
+                    Ok(ValidationData {
+                        package: validation_package,
+                        sources: vec![HashString::from("<insert your agent key here>")],
+                        lifecycle: EntryLifecycle::Chain,
+                        action: EntryAction::Commit,
+                        bundle: Some(validation_bundle.clone()),
+                        old_entry: None,
+                        old_entry_header: None,
+                        membrane_proof: None,
+                        link_action: None,
+                    })
+                })
+                .and_then(|validation_data| {
+                    validate_entry(
+                        entry.entry_type().clone(),
+                        entry.clone(),
+                        validation_data,
+                        context,
+                    )
+                }),
+        )?;
+    }
+
+    // 2. Commit the valid entries to chain and DHT, all in one action
+    block_on(commit_entries(
+        bundle.entries,
+        &context.action_channel,
+        context,
+    ))?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+
+    use super::*;
+    use holochain_core_types::entry::{test_entry, test_entry_b};
+    use instance::tests::test_instance_and_context;
+    use nucleus::ribosome::{
+        api::{
+            tests::{test_capability, test_zome_api_function_wasm, test_zome_name},
+            ZomeApiFunction,
+        },
+        Defn,
+    };
+    use serde_json;
+
+    fn test_bundle_context() -> Arc<Context> {
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            test_zome_api_function_wasm(ZomeApiFunction::CommitAppEntry.as_str()),
+        );
+        let (_instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        context
+    }
+
+    fn test_start_bundle_args(timeout_ms: u64) -> StartBundleArgs {
+        StartBundleArgs {
+            timeout_ms,
+            user_param: serde_json::from_str(r#"{"reason":"test"}"#).unwrap(),
+        }
+    }
+
+    /// addresses of all entries on the source chain, latest first
+    fn chain_entry_addresses(context: &Arc<Context>) -> Vec<Address> {
+        let state = context.state().expect("state should be initialized");
+        let agent = state.agent();
+        agent
+            .chain()
+            .iter(&agent.top_chain_header())
+            .map(|header| header.entry_address().clone())
+            .collect()
+    }
+
+    #[test]
+    fn commits_staged_entries_on_close() {
+        let context = test_bundle_context();
+        let mut bundle = None;
+
+        assert_eq!(
+            Ok(()),
+            start_bundle(&mut bundle, test_start_bundle_args(60_000))
+        );
+        assert_eq!(
+            Ok(test_entry().address()),
+            stage_entry(&mut bundle, test_entry())
+        );
+        assert_eq!(
+            Ok(test_entry_b().address()),
+            stage_entry(&mut bundle, test_entry_b())
+        );
+        assert!(!chain_entry_addresses(&context).contains(&test_entry().address()));
+
+        assert_eq!(
+            Ok(()),
+            close_bundle(&context, &mut bundle, BundleOnClose::Commit)
+        );
+        assert!(bundle.is_none());
+        assert_eq!(
+            vec![test_entry_b().address(), test_entry().address()],
+            chain_entry_addresses(&context)[..2].to_vec()
+        );
+    }
+
+    #[test]
+    fn throws_staged_entries_away_on_discard() {
+        let context = test_bundle_context();
+        let mut bundle = None;
+
+        start_bundle(&mut bundle, test_start_bundle_args(60_000)).unwrap();
+        stage_entry(&mut bundle, test_entry()).unwrap();
+
+        assert_eq!(
+            Ok(()),
+            close_bundle(&context, &mut bundle, BundleOnClose::Discard)
+        );
+        assert!(bundle.is_none());
+        assert!(!chain_entry_addresses(&context).contains(&test_entry().address()));
+    }
+
+    #[test]
+    fn discards_bundle_after_timeout() {
+        let context = test_bundle_context();
+        let mut bundle = None;
+
+        start_bundle(&mut bundle, test_start_bundle_args(0)).unwrap();
+        ::std::thread::sleep(Duration::from_millis(10));
+
+        assert_eq!(
+            Err(HolochainError::Timeout),
+            stage_entry(&mut bundle, test_entry())
+        );
+        assert!(bundle.is_none());
+
+        start_bundle(&mut bundle, test_start_bundle_args(0)).unwrap();
+        ::std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(
+            Err(HolochainError::Timeout),
+            close_bundle(&context, &mut bundle, BundleOnClose::Commit)
+        );
+        assert!(bundle.is_none());
+    }
+
+    #[test]
+    fn fails_to_start_nested_bundle() {
+        let mut bundle = None;
+        start_bundle(&mut bundle, test_start_bundle_args(60_000)).unwrap();
+
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "a bundle is already open".to_string()
+            )),
+            start_bundle(&mut bundle, test_start_bundle_args(60_000))
+        );
+    }
+
+    #[test]
+    fn fails_to_close_without_bundle() {
+        let context = test_bundle_context();
+
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "no bundle is open".to_string()
+            )),
+            close_bundle(&context, &mut None, BundleOnClose::Commit)
+        );
+    }
+
+    #[test]
+    fn refuses_other_chain_operations_inside_bundle() {
+        let mut bundle = None;
+        assert_eq!(Ok(()), outside_bundle(&bundle));
+
+        start_bundle(&mut bundle, test_start_bundle_args(60_000)).unwrap();
+        assert_eq!(
+            Err(HolochainError::ErrorGeneric(
+                "only commits are allowed inside a bundle".to_string()
+            )),
+            outside_bundle(&bundle)
+        );
+    }
+}
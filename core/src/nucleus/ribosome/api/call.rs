@@ -1,38 +1,38 @@
 use action::{Action, ActionWrapper};
 use context::Context;
 use holochain_core_types::error::HolochainError;
-use holochain_dna::zome::capabilities::Membrane;
 use holochain_wasm_utils::api_serialization::ZomeFnCallArgs;
 use instance::RECV_DEFAULT_TIMEOUT_MS;
 use nucleus::{
-    get_capability_with_zome_call, launch_zome_fn_call,
+    check_membrane, get_capability_with_zome_call, launch_zome_fn_call,
     ribosome::{api::ZomeApiResult, Runtime},
+    run_zome_fn_call,
     state::NucleusState,
-    ZomeFnCall,
-};
-use std::{
-    convert::TryFrom,
-    sync::{mpsc::channel, Arc},
+    ExecuteZomeFnResponse, Provenance, ZomeFnCall, ZomeFnResult,
 };
+use std::{convert::TryFrom, sync::Arc};
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 // ZomeFnCallArgs to ZomeFnCall
 impl ZomeFnCall {
-    fn from_args(args: ZomeFnCallArgs) -> Self {
-        ZomeFnCall::new(&args.zome_name, &args.cap_name, &args.fn_name, args.fn_args)
+    fn from_args(args: ZomeFnCallArgs, caller_zome: &str) -> Self {
+        let mut zome_call =
+            ZomeFnCall::new(&args.zome_name, &args.cap_name, &args.fn_name, args.fn_args);
+        zome_call.provenance = Provenance::Zome(caller_zome.to_string());
+        zome_call
     }
 }
 
 /// HcApiFuncIndex::CALL function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// expected complex argument: {zome_name: String, cap_name: String, fn_name: String, args: String}
 /// args from API call are converted into a ZomeFnCall
 /// Launch an Action::Call with newly formed ZomeFnCall
 /// Waits for a ZomeFnResult
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_call(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
-    let args_str = runtime.load_json_string_from_args(&args);
+    let args_str = runtime.load_json_string_from_args(&args)?;
 
     let input = match ZomeFnCallArgs::try_from(args_str.clone()) {
         Ok(input) => input,
@@ -43,8 +43,8 @@ pub fn invoke_call(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
         }
     };
 
-    // ZomeFnCallArgs to ZomeFnCall
-    let zome_call = ZomeFnCall::from_args(input);
+    // ZomeFnCallArgs to ZomeFnCall, called by the zome of the running call
+    let zome_call = ZomeFnCall::from_args(input, &runtime.zome_call.zome_name);
 
     // Don't allow recursive calls
     if zome_call.same_fn_as(&runtime.zome_call) {
@@ -53,44 +53,58 @@ pub fn invoke_call(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
 
     // Create Call Action
     let action_wrapper = ActionWrapper::new(Action::Call(zome_call.clone()));
-    // Send Action and block
-    let (sender, receiver) = channel();
-    ::instance::dispatch_action_with_observer(
+    let checked_call = zome_call.clone();
+    // Send Action and block until the call got checked, or the check timed out
+    let result = ::instance::dispatch_action_and_wait_for(
         &runtime.context.action_channel,
         &runtime.context.observer_channel,
-        action_wrapper.clone(),
-        move |state: &::state::State| {
-            // Observer waits for a ribosome_call_result
-            let maybe_result = state.nucleus().zome_call_result(&zome_call);
-            match maybe_result {
-                Some(result) => {
-                    // @TODO never panic in wasm
-                    // @see https://github.com/holochain/holochain-rust/issues/159
-                    sender
-                        .send(result)
-                        // the channel stays connected until the first message has been sent
-                        // if this fails that means that it was called after having returned done=true
-                        .expect("observer called after done");
+        action_wrapper,
+        Some(RECV_DEFAULT_TIMEOUT_MS),
+        // Observer waits for the call to be recorded, with an error if it got rejected
+        move |state: &::state::State| state.nucleus().zome_calls.get(&checked_call).cloned(),
+    ).and_then(|rejection| {
+        rejection.unwrap_or_else(|| run_nested_call(&runtime.context, zome_call))
+    });
+    runtime.store_result(result)
+}
 
-                    true
-                }
-                None => false,
-            }
-        },
-    );
-    // TODO #97 - Return error if timeout or something failed
-    // return Err(_);
+/// Runs a call that reduce_call has accepted on the thread of the calling zome.
+/// The caller holds a worker while it waits for the result anyway,
+/// giving the call a worker of its own could starve the pool.
+fn run_nested_call(context: &Arc<Context>, zome_call: ZomeFnCall) -> ZomeFnResult {
+    let dna = context
+        .state()
+        .and_then(|state| state.nucleus().dna())
+        .ok_or(HolochainError::DnaMissing)?;
+    let cap = dna
+        .get_capability_with_zome_name(&zome_call.zome_name, &zome_call.cap_name)
+        .map_err(HolochainError::Dna)?;
+    let fn_declaration = cap
+        .functions
+        .iter()
+        .find(|fn_declaration| fn_declaration.name == zome_call.fn_name)
+        .cloned()
+        .filter(|_| cap.check_outputs);
+    let code = dna
+        .get_wasm_from_zome_name(zome_call.zome_name.clone())
+        .expect("zome not found, Should have failed before when getting capability.");
 
-    let result = receiver
-        .recv_timeout(RECV_DEFAULT_TIMEOUT_MS)
-        .expect("observer dropped before done");
-    runtime.store_result(result)
+    let result = run_zome_fn_call(context.clone(), &zome_call, code, &dna.name, fn_declaration);
+    // Record the result, the call does not count as running anymore
+    let response = ExecuteZomeFnResponse::new(zome_call, result.clone());
+    context
+        .action_channel
+        .send(ActionWrapper::new(Action::ReturnZomeFunctionResult(
+            response,
+        )))
+        .expect("action channel to be open in reducer");
+    result
 }
 
 /// Reduce Call Action
 ///   1. Checks for correctness of ZomeFnCall inside the Action
 ///   2. Checks for permission to access Capability
-///   3. Execute the exposed Zome function in a separate thread
+///   3. Execute the exposed Zome function on the zome call pool, unless a zome makes the call
 /// Send the result in a ReturnZomeFunctionResult Action on success or failure like ExecuteZomeFunction
 pub(crate) fn reduce_call(
     context: Arc<Context>,
@@ -122,37 +136,44 @@ pub(crate) fn reduce_call(
     let cap = maybe_cap.unwrap().clone();
 
     // 2. Checks for permission to access Capability
-    // TODO #301 - Do real Capability token check
-    let can_call = match cap.cap_type.membrane {
-        Membrane::Public => true,
-        Membrane::Zome => {
-            // TODO #301 - check if caller zome_name is same as called zome_name
-            false
-        }
-        Membrane::Agent => {
-            // TODO #301 - check if caller has Agent Capability
-            false
-        }
-        Membrane::ApiKey => {
-            // TODO #301 - check if caller has ApiKey Capability
-            false
-        }
-    };
-    if !can_call {
+    if let Err(error) = check_membrane(&context, &cap, &fn_call) {
         // Notify failure
-        state.zome_calls.insert(
-            fn_call.clone(),
-            Some(Err(HolochainError::DoesNotHaveCapabilityToken)),
-        );
+        state.zome_calls.insert(fn_call.clone(), Some(Err(error)));
         return;
     }
 
-    // 3. Get the exposed Zome function WASM and execute it in a separate thread
+    // 3. Checks the parameters against the declared inputs
+    let fn_declaration = cap
+        .functions
+        .iter()
+        .find(|fn_declaration| fn_declaration.name == fn_call.fn_name)
+        .cloned();
+    if let Some(ref fn_declaration) = fn_declaration {
+        if let Err(error) = fn_declaration.check_inputs(&fn_call.parameters) {
+            // Notify failure
+            state
+                .zome_calls
+                .insert(fn_call.clone(), Some(Err(HolochainError::Dna(error))));
+            return;
+        }
+    }
+
+    // 4. Get the exposed Zome function WASM and execute it
     let maybe_code = dna.get_wasm_from_zome_name(fn_call.zome_name.clone());
     let code =
         maybe_code.expect("zome not found, Should have failed before when getting capability.");
     state.zome_calls.insert(fn_call.clone(), None);
-    launch_zome_fn_call(context, fn_call, &code, state.dna.clone().unwrap().name);
+    // Calls from inside a zome are run by their caller, see invoke_call
+    if let Provenance::Zome(_) = fn_call.provenance {
+        return;
+    }
+    launch_zome_fn_call(
+        context,
+        fn_call,
+        &code,
+        state.dna.clone().unwrap().name,
+        fn_declaration.filter(|_| cap.check_outputs),
+    );
 }
 
 #[cfg(test)]
@@ -164,10 +185,14 @@ pub mod tests {
     use self::tempfile::tempdir;
     use super::*;
     use context::Context;
+    use executor::Pool;
     use holochain_agent::Agent;
     use holochain_cas_implementations::{cas::file::FilesystemStorage, eav::file::EavFileStorage};
-    use holochain_core_types::{error::DnaError, json::JsonString};
-    use holochain_dna::{zome::capabilities::Capability, Dna};
+    use holochain_core_types::{cas::content::Address, error::DnaError, json::JsonString};
+    use holochain_dna::{
+        zome::capabilities::{Capability, Membrane},
+        Dna,
+    };
     use instance::{
         tests::{test_instance, TestLogger},
         Observer,
@@ -184,7 +209,10 @@ pub mod tests {
     };
     use persister::SimplePersister;
     use serde_json;
-    use std::sync::{mpsc::RecvTimeoutError, Arc, Mutex};
+    use std::sync::{
+        mpsc::{channel, RecvTimeoutError},
+        Arc, Mutex,
+    };
     use test_utils::create_test_dna_with_cap;
 
     /// dummy commit args from standard test entry
@@ -231,11 +259,13 @@ pub mod tests {
     #[cfg_attr(tarpaulin, skip)]
     fn test_reduce_call(
         dna: Dna,
+        provenance: Provenance,
         expected: Result<Result<JsonString, HolochainError>, RecvTimeoutError>,
     ) {
         let context = create_context();
 
-        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "test", "{}");
+        let mut zome_call = ZomeFnCall::new("test_zome", "test_cap", "test", "{}");
+        zome_call.provenance = provenance;
         let zome_call_action = ActionWrapper::new(Action::Call(zome_call.clone()));
 
         // Set up instance and process the action
@@ -277,9 +307,10 @@ pub mod tests {
 
     #[test]
     fn test_call_no_token() {
+        // agent membrane by default
         let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
         let expected = Ok(Err(HolochainError::DoesNotHaveCapabilityToken));
-        test_reduce_call(dna, expected);
+        test_reduce_call(dna, Provenance::RemoteAgent(Address::from("bob")), expected);
     }
 
     #[test]
@@ -288,7 +319,7 @@ pub mod tests {
         let expected = Ok(Err(HolochainError::Dna(DnaError::ZomeNotFound(
             r#"Zome 'test_zome' not found"#.to_string(),
         ))));
-        test_reduce_call(dna, expected);
+        test_reduce_call(dna, Provenance::Zome("other_zome".to_string()), expected);
     }
 
     #[test]
@@ -300,6 +331,53 @@ pub mod tests {
 
         // Expecting timeout since there is no function in wasm to call
         let expected = Err(RecvTimeoutError::Disconnected);
-        test_reduce_call(dna, expected);
+        test_reduce_call(dna, Provenance::RemoteAgent(Address::from("bob")), expected);
+    }
+
+    #[test]
+    fn test_call_zome_membrane() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::Call.as_str());
+        let mut capability = Capability::new();
+        capability.cap_type.membrane = Membrane::Zome;
+        let dna = create_test_dna_with_cap(&test_zome_name(), "test_cap", &capability, &wasm);
+
+        test_reduce_call(
+            dna.clone(),
+            Provenance::External,
+            Ok(Err(HolochainError::DoesNotHaveCapabilityToken)),
+        );
+        // Expecting timeout since there is no function in wasm to call
+        test_reduce_call(
+            dna,
+            Provenance::Zome("other_zome".to_string()),
+            Err(RecvTimeoutError::Disconnected),
+        );
+    }
+
+    #[test]
+    fn test_call_from_zome_is_run_by_caller() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::Call.as_str());
+        let mut capability = Capability::new();
+        capability.cap_type.membrane = Membrane::Public;
+        let dna = create_test_dna_with_cap(&test_zome_name(), "test_cap", &capability, &wasm);
+        let context = create_context();
+        let instance = test_instance(dna).expect("Could not initialize test instance");
+
+        let mut zome_call = ZomeFnCall::new("test_zome", "test_cap", "test", "{}");
+        zome_call.provenance = Provenance::Zome("other_zome".to_string());
+        let (_, rx_observer) = channel::<Observer>();
+        instance.process_action(
+            ActionWrapper::new(Action::Call(zome_call.clone())),
+            Vec::new(),
+            &rx_observer,
+            &context,
+        );
+
+        // the call got accepted, but nothing got scheduled for it
+        assert_eq!(
+            Some(&None),
+            instance.state().nucleus().zome_calls.get(&zome_call)
+        );
+        assert_eq!(0, context.executor.metrics(Pool::ZomeCalls).workers);
     }
 }
@@ -0,0 +1,73 @@
+use holochain_core_types::cas::content::Address;
+use holochain_wasm_utils::api_serialization::cap_grant::GrantCapabilityArgs;
+use nucleus::{
+    cap_tokens::{grant_capability, revoke_capability},
+    ribosome::{
+        api::{bundle::outside_bundle, ZomeApiResult},
+        Runtime,
+    },
+};
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::GrantCapability function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: GrantCapabilityArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_grant_capability(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let input = match GrantCapabilityArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        Err(_) => {
+            println!(
+                "invoke_grant_capability failed to deserialize GrantCapabilityArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // Only commits can be staged in a bundle
+    if let Err(hc_err) = outside_bundle(&runtime.bundle) {
+        return runtime.store_result::<Address>(Err(hc_err));
+    }
+
+    // zomes grant access to their own capabilities
+    let zome_name = runtime.zome_call.zome_name.clone();
+    let result = grant_capability(
+        &runtime.context,
+        &zome_name,
+        &input.capability,
+        input.functions,
+        input.assignees,
+    );
+    runtime.store_result(result)
+}
+
+/// ZomeApiFunction::RevokeCapability function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected argument: the capability token to revoke
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_revoke_capability(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let token = match Address::try_from(args_str.clone()) {
+        Ok(token) => token,
+        Err(_) => {
+            println!(
+                "invoke_revoke_capability failed to deserialize Address: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // Only commits can be staged in a bundle
+    if let Err(hc_err) = outside_bundle(&runtime.bundle) {
+        return runtime.store_result::<()>(Err(hc_err));
+    }
+
+    let result = revoke_capability(&runtime.context, &token);
+    runtime.store_result(result)
+}
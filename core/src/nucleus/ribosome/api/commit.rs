@@ -9,18 +9,21 @@ use holochain_core_types::{
 };
 use nucleus::{
     actions::{build_validation_package::*, validate::*},
-    ribosome::{api::ZomeApiResult, Runtime},
+    ribosome::{
+        api::{bundle::stage_entry, ZomeApiResult},
+        Runtime,
+    },
 };
 use std::convert::TryFrom;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::CommitAppEntry function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Expected complex argument: CommitArgs
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_commit_app_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
-    let args_str = runtime.load_json_string_from_args(&args);
+    let args_str = runtime.load_json_string_from_args(&args)?;
     let serialized_entry = match SerializedEntry::try_from(args_str.clone()) {
         Ok(entry_input) => entry_input,
         // Exit on error
@@ -36,6 +39,12 @@ pub fn invoke_commit_app_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> Zom
     // Create Chain Entry
     let entry = Entry::from(serialized_entry);
 
+    // Inside a bundle the entry only gets validated and committed when the bundle is closed
+    if runtime.bundle.is_some() {
+        let result = stage_entry(&mut runtime.bundle, entry);
+        return runtime.store_result(result);
+    }
+
     // Wait for future to be resolved
     let task_result: Result<Address, HolochainError> = block_on(
         // 1. Build the context needed for validation of the entry
@@ -46,6 +55,11 @@ pub fn invoke_commit_app_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> Zom
                     sources: vec![HashString::from("<insert your agent key here>")],
                     lifecycle: EntryLifecycle::Chain,
                     action: EntryAction::Commit,
+                    bundle: None,
+                    old_entry: None,
+                    old_entry_header: None,
+                    membrane_proof: None,
+                    link_action: None,
                 })
             })
             // 2. Validate the entry
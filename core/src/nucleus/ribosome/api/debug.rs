@@ -2,11 +2,11 @@ use nucleus::ribosome::{api::ZomeApiResult, Runtime};
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::Debug function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Expecting a string as complex input argument
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_debug(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
-    let payload = runtime.load_json_string_from_args(args);
+    let payload = runtime.load_json_string_from_args(args)?;
     println!("{}", payload);
     // TODO #502 - log in logger as DEBUG log-level
     runtime
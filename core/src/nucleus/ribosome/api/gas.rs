@@ -0,0 +1,93 @@
+use nucleus::ribosome::{api::ZomeApiResult, HolochainTrap, Runtime};
+use wasmi::RuntimeArgs;
+
+/// ZomeApiFunction::Gas function code
+/// Called by the gas counter injected into every module before each block of instructions.
+/// args: [0] fuel cost of the block about to run, as i32
+/// Traps once the zome call ran out of fuel or time, which aborts it.
+pub fn invoke_gas(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    let fuel: u32 = args.nth_checked(0)?;
+    runtime
+        .meter
+        .charge(u64::from(fuel))
+        .map_err(HolochainTrap)?;
+    Ok(None)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use self::wabt::Wat2Wasm;
+    use holochain_core_types::error::HolochainError;
+    use holochain_dna::zome::Config;
+    use instance::tests::test_instance_and_context;
+    use nucleus::{ribosome, ZomeFnCall};
+    use std::sync::Arc;
+
+    /// wasm with a "test" function that loops forever
+    fn test_infinite_loop_wasm() -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(
+                r#"
+(module
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (func
+        (export "test")
+        (param $allocation i64)
+        (result i64)
+
+        (loop (br 0))
+        (i64.const 0)
+    )
+)
+                "#,
+            )
+            .unwrap()
+            .as_ref()
+            .to_vec()
+    }
+
+    fn run_infinite_loop(config: Config) -> Result<(), HolochainError> {
+        let wasm = test_infinite_loop_wasm();
+        let mut dna = test_utils::create_test_dna_with_wasm("test_zome", "test_cap", wasm.clone());
+        dna.zomes.get_mut("test_zome").unwrap().config = config;
+        let dna_name = dna.name.to_string();
+        let (_instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "test", "");
+        ribosome::run_dna(&dna_name, Arc::clone(&context), wasm, &zome_call, None).map(|_| ())
+    }
+
+    #[test]
+    fn aborts_call_that_runs_out_of_fuel() {
+        let mut config = Config::new();
+        config.fuel_limit = Some(10_000);
+
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call ran out of fuel (limit: 10000)".to_string()
+            )),
+            run_infinite_loop(config),
+        );
+    }
+
+    #[test]
+    fn aborts_call_that_times_out() {
+        let mut config = Config::new();
+        config.timeout_ms = Some(100);
+
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call timed out (limit: 100ms)".to_string()
+            )),
+            run_infinite_loop(config),
+        );
+    }
+}
@@ -1,40 +1,34 @@
 use futures::executor::block_on;
-use holochain_core_types::cas::content::Address;
+use holochain_wasm_utils::api_serialization::get_entry::GetEntryArgs;
 use nucleus::{
-    actions::get_entry::get_entry,
+    actions::get_entry::get_entry_result,
     ribosome::{api::ZomeApiResult, Runtime},
 };
 use std::convert::TryFrom;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::GetAppEntry function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Expected complex argument: GetEntryArgs
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_get_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
-    let args_str = runtime.load_json_string_from_args(&args);
-    let try_address = Address::try_from(args_str.clone());
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let try_args = GetEntryArgs::try_from(args_str.clone());
     // Exit on error
-    if try_address.is_err() {
+    if try_args.is_err() {
         println!(
-            "invoke_get_entry failed to deserialize Address: {:?}",
+            "invoke_get_entry failed to deserialize GetEntryArgs: {:?}",
             args_str
         );
         return ribosome_error_code!(ArgumentDeserializationFailed);
     }
-    let address = try_address.unwrap();
+    let input = try_args.unwrap();
 
-    let future = get_entry(&runtime.context, address);
+    let future = get_entry_result(&runtime.context, input.address, &input.options);
     let result = block_on(future);
 
-    // runtime.store_result(match result {
-    //     Ok(maybe_entry) => Ok(maybe_entry.and_then(|entry| Some(entry.serialize()))),
-    //     Err(hc_err) => Err(hc_err),
-    // })
-    let api_result =
-        result.map(|maybe_entry| maybe_entry.and_then(|entry| Some(entry.serialize())));
-    runtime.store_result(api_result)
+    runtime.store_result(result)
 }
 
 #[cfg(test)]
@@ -49,6 +43,9 @@ mod tests {
         error::ZomeApiInternalResult,
         json::JsonString,
     };
+    use holochain_wasm_utils::api_serialization::get_entry::{
+        GetEntryArgs, GetEntryOptions, GetEntryResult,
+    };
     use instance::tests::{test_context_and_logger, test_instance};
     use nucleus::{
         ribosome::{
@@ -64,12 +61,18 @@ mod tests {
 
     /// dummy get args from standard test entry
     pub fn test_get_args_bytes() -> Vec<u8> {
-        JsonString::from(test_entry().address()).into_bytes()
+        JsonString::from(GetEntryArgs {
+            address: test_entry().address(),
+            options: GetEntryOptions::default(),
+        }).into_bytes()
     }
 
-    /// dummy get args from standard test entry
+    /// dummy get args for an address that was never committed
     pub fn test_get_args_unknown() -> Vec<u8> {
-        JsonString::from(Address::from("xxxxxxxxx")).into_bytes()
+        JsonString::from(GetEntryArgs {
+            address: Address::from("xxxxxxxxx"),
+            options: GetEntryOptions::default(),
+        }).into_bytes()
     }
 
     /// wat string that exports both get and a commit dispatches so we can test a round trip
@@ -82,15 +85,15 @@ mod tests {
 (module
     (import "env" "hc_get_entry"
         (func $get
-            (param i32)
-            (result i32)
+            (param i64)
+            (result i64)
         )
     )
 
     (import "env" "hc_commit_entry"
         (func $commit
-            (param i32)
-            (result i32)
+            (param i64)
+            (result i64)
         )
     )
 
@@ -99,8 +102,8 @@ mod tests {
 
     (func
         (export "get_dispatch")
-            (param $allocation i32)
-            (result i32)
+            (param $allocation i64)
+            (result i64)
 
         (call
             $get
@@ -110,8 +113,8 @@ mod tests {
 
     (func
         (export "commit_dispatch")
-            (param $allocation i32)
-            (result i32)
+            (param $allocation i64)
+            (result i64)
 
         (call
             $commit
@@ -121,16 +124,16 @@ mod tests {
 
     (func
         (export "__hdk_validate_app_entry")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
-        (i32.const 0)
+        (i64.const 0)
     )
 
     (func
         (export "__hdk_get_validation_package_for_entry_type")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
         ;; This writes "Entry" into memory
         (i32.store (i32.const 0) (i32.const 34))
@@ -141,15 +144,15 @@ mod tests {
         (i32.store (i32.const 5) (i32.const 121))
         (i32.store (i32.const 6) (i32.const 34))
 
-        (i32.const 7)
+        (i64.const 7)
     )
 
     (func
         (export "__list_capabilities")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
-        (i32.const 0)
+        (i64.const 0)
     )
 )
                 "#,
@@ -223,7 +226,13 @@ mod tests {
         assert_eq!(
             JsonString::from(
                 String::from(JsonString::from(ZomeApiInternalResult::success(
-                    test_entry().serialize()
+                    GetEntryResult {
+                        address: Some(test_entry().address()),
+                        entry: Some(test_entry().serialize()),
+                        header: None,
+                        sources: None,
+                        meta: None,
+                    }
                 ))) + "\u{0}",
             ),
             call_result,
@@ -270,7 +279,9 @@ mod tests {
 
         assert_eq!(
             JsonString::from(
-                String::from(JsonString::from(ZomeApiInternalResult::success(None))) + "\u{0}"
+                String::from(JsonString::from(ZomeApiInternalResult::success(
+                    GetEntryResult::not_found()
+                ))) + "\u{0}"
             ),
             call_result,
         );
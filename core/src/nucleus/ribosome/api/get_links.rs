@@ -1,16 +1,19 @@
-use holochain_core_types::cas::content::Address;
+use futures::executor::block_on;
 use holochain_wasm_utils::api_serialization::get_links::GetLinksArgs;
-use nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use nucleus::{
+    actions::get_links::get_links_result,
+    ribosome::{api::ZomeApiResult, Runtime},
+};
 use std::convert::TryFrom;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::GetLinks function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Expected complex argument: GetLinksArgs
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_get_links(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
-    let args_str = runtime.load_json_string_from_args(&args);
+    let args_str = runtime.load_json_string_from_args(&args)?;
     let input = match GetLinksArgs::try_from(args_str.clone()) {
         Ok(input) => input,
         Err(_) => {
@@ -21,21 +24,15 @@ pub fn invoke_get_links(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiRes
             return ribosome_error_code!(ArgumentDeserializationFailed);
         }
     };
-    // Get links from DHT
-    let maybe_links = runtime
-        .context
-        .state()
-        .unwrap()
-        .dht()
-        .get_links(input.entry_address, input.tag);
-
-    runtime.store_result(match maybe_links {
-        Ok(links) => Ok(links
-            .iter()
-            .map(|eav| eav.value())
-            .collect::<Vec<Address>>()),
-        Err(hc_err) => Err(hc_err),
-    })
+    // Get a page of links from DHT
+    let result = block_on(get_links_result(
+        &runtime.context,
+        input.entry_address,
+        input.tag,
+        &input.options,
+    ));
+
+    runtime.store_result(result)
 }
 
 #[cfg(test)]
@@ -47,22 +44,34 @@ pub mod tests {
     use dht::actions::add_link::add_link;
     use futures::executor::block_on;
     use holochain_core_types::{
-        cas::content::Address, entry::Entry, entry_type::test_entry_type, json::JsonString,
-        links_entry::Link,
+        cas::content::Address, entry::Entry, entry_type::test_entry_type,
+        error::ZomeApiInternalResult, json::JsonString, links_entry::Link,
+    };
+    use holochain_wasm_utils::api_serialization::get_links::{
+        GetLinksArgs, GetLinksOptions, GetLinksResult,
     };
-    use holochain_wasm_utils::api_serialization::get_links::GetLinksArgs;
     use instance::tests::{test_context_and_logger, test_instance};
     use nucleus::ribosome::{
         api::{tests::*, ZomeApiFunction},
         Defn,
     };
     use serde_json;
+    use std::convert::TryFrom;
 
-    /// dummy link_entries args from standard test entry
+    /// dummy get_links args for the given base and tag
     pub fn test_get_links_args_bytes(base: &Address, tag: &str) -> Vec<u8> {
+        test_get_links_args_bytes_with_options(base, tag, GetLinksOptions::default())
+    }
+
+    pub fn test_get_links_args_bytes_with_options(
+        base: &Address,
+        tag: &str,
+        options: GetLinksOptions,
+    ) -> Vec<u8> {
         let args = GetLinksArgs {
             entry_address: base.clone(),
             tag: String::from(tag),
+            options,
         };
         serde_json::to_string(&args)
             .expect("args should serialize")
@@ -101,8 +110,9 @@ pub mod tests {
         let link1 = Link::new(&entry_hashes[0], &entry_hashes[1], "test-tag");
         let link2 = Link::new(&entry_hashes[0], &entry_hashes[2], "test-tag");
 
-        assert!(block_on(add_link(&link1, &initialized_context)).is_ok());
-        assert!(block_on(add_link(&link2, &initialized_context)).is_ok());
+        let instance = Address::from("link-instance");
+        assert!(block_on(add_link(&link1, &instance, &initialized_context)).is_ok());
+        assert!(block_on(add_link(&link2, &instance, &initialized_context)).is_ok());
 
         let call_result = test_zome_api_function_call(
             &dna_name,
@@ -112,27 +122,37 @@ pub mod tests {
             test_get_links_args_bytes(&entry_hashes[0], "test-tag"),
         );
 
-        let expected_1 = JsonString::from(
-            format!(
-                r#"{{"ok":true,"value":"[\"{}\",\"{}\"]","error":"null"}}"#,
-                entry_hashes[1], entry_hashes[2]
-            ) + "\u{0}",
-        );
+        let mut expected = vec![entry_hashes[1].clone(), entry_hashes[2].clone()];
+        expected.sort();
+        let result = get_links_result_from_call(call_result);
+        assert_eq!(expected, result.addresses());
+        assert_eq!(None, result.next_cursor);
 
-        let expected_2 = JsonString::from(
-            format!(
-                r#"{{"ok":true,"value":"[\"{}\",\"{}\"]","error":"null"}}"#,
-                entry_hashes[2], entry_hashes[1]
-            ) + "\u{0}",
+        // paginated
+        let mut options = GetLinksOptions::default();
+        options.limit = Some(1);
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            initialized_context.clone(),
+            &instance,
+            &wasm,
+            test_get_links_args_bytes_with_options(&entry_hashes[0], "test-tag", options.clone()),
         );
+        let result = get_links_result_from_call(call_result);
+        assert_eq!(vec![expected[0].clone()], result.addresses());
+        assert_eq!(Some(expected[0].clone()), result.next_cursor);
 
-        assert!(
-            call_result == expected_1 || call_result == expected_2,
-            "\n call_result = '{:?}'\n   ordering1 = '{:?}'\n   ordering2 = '{:?}'",
-            call_result,
-            expected_1,
-            expected_2,
+        options.cursor = result.next_cursor;
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            initialized_context.clone(),
+            &instance,
+            &wasm,
+            test_get_links_args_bytes_with_options(&entry_hashes[0], "test-tag", options),
         );
+        let result = get_links_result_from_call(call_result);
+        assert_eq!(vec![expected[1].clone()], result.addresses());
+        assert_eq!(None, result.next_cursor);
 
         let call_result = test_zome_api_function_call(
             &dna_name,
@@ -141,11 +161,18 @@ pub mod tests {
             &wasm,
             test_get_links_args_bytes(&entry_hashes[0], "other-tag"),
         );
+        let result = get_links_result_from_call(call_result);
+        assert!(result.links.is_empty());
+    }
 
-        assert_eq!(
-            call_result,
-            JsonString::from(String::from(r#"{"ok":true,"value":"[]","error":"null"}"#) + "\u{0}"),
-        );
+    fn get_links_result_from_call(call_result: JsonString) -> GetLinksResult {
+        let call_result = String::from(call_result);
+        let result = ZomeApiInternalResult::try_from(JsonString::from(
+            call_result.trim_right_matches('\u{0}'),
+        )).expect("valid ZomeApiInternalResult JsonString");
+        assert!(result.ok, "result = {:?}", result);
+        GetLinksResult::try_from(JsonString::from(result.value))
+            .expect("valid GetLinksResult JsonString")
     }
 
 }
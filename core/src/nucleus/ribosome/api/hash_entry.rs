@@ -11,16 +11,16 @@ use wasmi::{RuntimeArgs, RuntimeValue};
 
 pub fn get_entry_type(dna: &Dna, entry_type_name: &str) -> Result<EntryType, Option<RuntimeValue>> {
     let entry_type = EntryType::from_str(&entry_type_name).map_err(|_| {
-        Some(RuntimeValue::I32(
-            holochain_core_types::error::RibosomeErrorCode::UnknownEntryType as i32,
+        Some(RuntimeValue::I64(
+            holochain_core_types::error::RibosomeErrorCode::UnknownEntryType as i64,
         ))
     })?;
     // Check if AppEntry is a valid AppEntryType
     if entry_type.is_app() {
         let result = dna.get_entry_type_def(entry_type_name);
         if result.is_none() {
-            return Err(Some(RuntimeValue::I32(
-                holochain_core_types::error::RibosomeErrorCode::UnknownEntryType as i32,
+            return Err(Some(RuntimeValue::I64(
+                holochain_core_types::error::RibosomeErrorCode::UnknownEntryType as i64,
             )));
         }
     }
@@ -29,12 +29,12 @@ pub fn get_entry_type(dna: &Dna, entry_type_name: &str) -> Result<EntryType, Opt
 }
 
 /// ZomeApiFunction::hash_entry function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Expected complex argument: entry_type_name and entry_value as JsonString
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_hash_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
-    let args_str = runtime.load_json_string_from_args(&args);
+    let args_str = runtime.load_json_string_from_args(&args)?;
     let serialized_entry = match SerializedEntry::try_from(args_str) {
         Ok(input) => input,
         Err(_) => return ribosome_error_code!(ArgumentDeserializationFailed),
@@ -1,13 +1,16 @@
-use holochain_core_types::{entry_type::EntryType, hash::HashString, json::JsonString};
+use holochain_core_types::{
+    cas::storage::ContentAddressableStorage, entry::Entry, entry_type::EntryType, hash::HashString,
+    json::JsonString,
+};
 use holochain_wasm_utils::api_serialization::ZomeApiGlobals;
 use multihash::Hash as Multihash;
 use nucleus::ribosome::{api::ZomeApiResult, Runtime};
 use wasmi::RuntimeArgs;
 
 /// ZomeApiFunction::InitGlobals secret function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Not expecting any complex input
-/// Returns an HcApiReturnCode as I32
+/// Returns an HcApiReturnCode as I64
 pub fn invoke_init_globals(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeApiResult {
     // Create the ZomeApiGlobals struct with some default values
     let mut globals = ZomeApiGlobals {
@@ -18,6 +21,7 @@ pub fn invoke_init_globals(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeAp
         agent_address: HashString::encode_from_str("FIXME-agent_address", Multihash::SHA2256),
         agent_initial_hash: HashString::from(""),
         agent_latest_hash: HashString::from(""),
+        membrane_proof: None,
     };
 
     // Update fields
@@ -43,6 +47,19 @@ pub fn invoke_init_globals(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeAp
                 globals.agent_initial_hash = found_entries.pop().unwrap();
                 globals.agent_address = globals.agent_latest_hash.clone();
             }
+            // Update membrane proof
+            let chain = state.agent().chain();
+            if let Some(chain_header) = chain
+                .iter_type(&maybe_top, &EntryType::MembraneProof)
+                .last()
+            {
+                let maybe_entry: Option<Entry> = chain
+                    .content_storage()
+                    .fetch(chain_header.entry_address())
+                    .unwrap_or(None);
+                globals.membrane_proof =
+                    maybe_entry.map(|entry| String::from(entry.value().to_owned()));
+            }
         }
     };
 
@@ -1,16 +1,32 @@
+use agent::{actions::commit::*, state::create_new_chain_header};
 use dht::actions::add_link::*;
-use futures::executor::block_on;
+use futures::{executor::block_on, FutureExt};
+use holochain_core_types::{
+    cas::{content::AddressableContent, storage::ContentAddressableStorage},
+    entry::ToEntry,
+    entry_type::EntryType,
+    error::HolochainError,
+    hash::HashString,
+    links_entry::{LinkActionKind, LinkEntry},
+    validation::{EntryAction, EntryLifecycle, LinkAction, ValidationData, ValidationPackage},
+};
 use holochain_wasm_utils::api_serialization::link_entries::LinkEntriesArgs;
-use nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use nucleus::{
+    actions::validate::*,
+    ribosome::{
+        api::{bundle::outside_bundle, ZomeApiResult},
+        Runtime,
+    },
+};
 use std::convert::TryFrom;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 /// ZomeApiFunction::LinkEntries function code
-/// args: [0] encoded MemoryAllocation as u32
+/// args: [0] encoded MemoryAllocation as u64
 /// Expected complex argument: LinkEntriesArgs
 pub fn invoke_link_entries(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
     // deserialize args
-    let args_str = runtime.load_json_string_from_args(&args);
+    let args_str = runtime.load_json_string_from_args(&args)?;
     let input = match LinkEntriesArgs::try_from(args_str.clone()) {
         Ok(entry_input) => entry_input,
         // Exit on error
@@ -22,9 +38,84 @@ pub fn invoke_link_entries(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
             return ribosome_error_code!(ArgumentDeserializationFailed);
         }
     };
-    // Wait for add_link() future to be resolved
-    let result = block_on(add_link(&input.to_link(), &runtime.context));
-    runtime.store_result(result)
+
+    // Only commits can be staged in a bundle
+    if let Err(hc_err) = outside_bundle(&runtime.bundle) {
+        return runtime.store_result::<()>(Err(hc_err));
+    }
+    let link = input.to_link();
+
+    // Don't validate or commit links from a base that is not there
+    let base_exists = runtime
+        .context
+        .state()
+        .expect("state should be initialized")
+        .dht()
+        .content_storage()
+        .contains(link.base());
+    match base_exists {
+        Ok(true) => (),
+        Ok(false) => {
+            return runtime.store_result::<()>(Err(HolochainError::ErrorGeneric(String::from(
+                "Base for link not found",
+            ))))
+        }
+        Err(hc_err) => return runtime.store_result::<()>(Err(hc_err)),
+    }
+
+    let link_entry = LinkEntry::from_link(LinkActionKind::ADD, &link).to_entry();
+    let chain_header = create_new_chain_header(
+        &link_entry,
+        &*runtime
+            .context
+            .state()
+            .expect("state should be initialized")
+            .agent(),
+    );
+    let validation_data = |lifecycle| ValidationData {
+        package: ValidationPackage::only_header(chain_header.clone()),
+        sources: vec![HashString::from("<insert your agent key here>")],
+        lifecycle,
+        action: EntryAction::Commit,
+        bundle: None,
+        old_entry: None,
+        old_entry_header: None,
+        membrane_proof: None,
+        link_action: Some(LinkAction::Commit),
+    };
+
+    // Wait for future to be resolved
+    let task_result: Result<(), HolochainError> = block_on(
+        // 1. Validate the link before committing it
+        validate_entry(
+            EntryType::Link,
+            link_entry.clone(),
+            validation_data(EntryLifecycle::Chain),
+            &runtime.context,
+        )
+        // 2. Commit the link entry to the source chain
+        .and_then(|_| {
+            commit_entry(
+                link_entry.clone(),
+                &runtime.context.action_channel,
+                &runtime.context,
+            )
+        })
+        // 3. Validate the link again before holding it in the DHT.
+        // Without a network we are our own DHT node, so this happens right here.
+        .and_then(|_| {
+            validate_entry(
+                EntryType::Link,
+                link_entry.clone(),
+                validation_data(EntryLifecycle::Dht),
+                &runtime.context,
+            )
+        })
+        // 4. Add the link to the DHT, as the instance committed with the header of step 2
+        .and_then(|_| add_link(&link, &chain_header.address(), &runtime.context)),
+    );
+
+    runtime.store_result(task_result)
 }
 
 #[cfg(test)]
@@ -36,10 +127,12 @@ pub mod tests {
     use futures::executor::block_on;
     use holochain_core_types::{
         cas::content::AddressableContent,
-        entry::test_entry,
-        error::{CoreError, ZomeApiInternalResult},
+        entry::{test_entry, ToEntry},
+        error::{CoreError, HolochainError, ZomeApiInternalResult},
         json::JsonString,
+        links_entry::{LinkActionKind, LinkEntry},
     };
+    use holochain_dna::{zome::entry_types::LinksTo, Dna};
     use holochain_wasm_utils::api_serialization::link_entries::*;
     use instance::tests::{test_context_and_logger, test_instance};
     use nucleus::ribosome::{
@@ -63,6 +156,21 @@ pub mod tests {
             .into_bytes()
     }
 
+    /// declares the link of test_link_args_bytes() in the DNA
+    pub fn declare_test_link(dna: &mut Dna) {
+        let mut links_to = LinksTo::new();
+        links_to.target_type = String::from("testEntryType");
+        links_to.tag = String::from("test-tag");
+        dna.zomes
+            .get_mut(&test_zome_name())
+            .expect("test zome should exist")
+            .entry_types
+            .get_mut("testEntryType")
+            .expect("test entry type should exist")
+            .links_to
+            .push(links_to);
+    }
+
     /// dummy commit args from standard test entry
     pub fn test_commit_args_bytes() -> Vec<u8> {
         JsonString::from(test_entry().serialize()).into_bytes()
@@ -79,7 +187,7 @@ pub mod tests {
         assert_eq!(
             call_result,
             JsonString::from(
-                "{\"ok\":false,\"value\":\"null\",\"error\":\"{\\\"kind\\\":{\\\"ErrorGeneric\\\":\\\"Base for link not found\\\"},\\\"file\\\":\\\"core/src/nucleus/ribosome/runtime.rs\\\",\\\"line\\\":\\\"83\\\"}\"}"
+                "{\"ok\":false,\"value\":\"null\",\"error\":\"{\\\"kind\\\":{\\\"ErrorGeneric\\\":\\\"Base for link not found\\\"},\\\"file\\\":\\\"core/src/nucleus/ribosome/runtime.rs\\\",\\\"line\\\":\\\"94\\\"}\"}"
             ),
         );
 
@@ -93,11 +201,12 @@ pub mod tests {
     #[test]
     fn returns_ok_if_base_is_present() {
         let wasm = test_zome_api_function_wasm(ZomeApiFunction::LinkEntries.as_str());
-        let dna = test_utils::create_test_dna_with_wasm(
+        let mut dna = test_utils::create_test_dna_with_wasm(
             &test_zome_name(),
             &test_capability(),
             wasm.clone(),
         );
+        declare_test_link(&mut dna);
 
         let dna_name = &dna.name.to_string().clone();
         let instance = test_instance(dna).expect("Could not create test instance");
@@ -113,7 +222,7 @@ pub mod tests {
 
         let call_result = test_zome_api_function_call(
             &dna_name,
-            initialized_context,
+            initialized_context.clone(),
             &instance,
             &wasm,
             test_link_args_bytes(),
@@ -125,6 +234,59 @@ pub mod tests {
                 String::from(JsonString::from(ZomeApiInternalResult::success(None))) + "\u{0}"
             ),
         );
+
+        // the link entry got committed to the source chain
+        let link_entry = LinkEntry::new(
+            LinkActionKind::ADD,
+            &test_entry().address(),
+            &test_entry().address(),
+            "test-tag",
+        ).to_entry();
+        let agent = initialized_context.state().unwrap().agent();
+        assert_eq!(
+            agent.top_chain_header().unwrap().entry_address(),
+            &link_entry.address()
+        );
+    }
+
+    #[test]
+    fn errors_if_link_is_not_declared() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::LinkEntries.as_str());
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+
+        let dna_name = &dna.name.to_string().clone();
+        let instance = test_instance(dna).expect("Could not create test instance");
+
+        let (context, _) = test_context_and_logger("joan");
+        let initialized_context = instance.initialize_context(context);
+
+        block_on(commit_entry(
+            test_entry(),
+            &initialized_context.action_channel.clone(),
+            &initialized_context,
+        )).expect("Could not commit entry for testing");
+
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            initialized_context,
+            &instance,
+            &wasm,
+            test_link_args_bytes(),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result)
+            .expect("valid ZomeApiInternalResult JsonString");
+        let core_err = CoreError::try_from(result).expect("valid CoreError JsonString");
+        assert_eq!(
+            HolochainError::ValidationFailed(String::from(
+                "Link with tag 'test-tag' from 'testEntryType' to 'testEntryType' is not declared in the DNA"
+            )),
+            core_err.kind,
+        );
     }
 
 }
@@ -1,22 +1,46 @@
 //! Module for ZomeApiFunctions
 //! ZomeApiFunctions are the functions provided by the ribosome that are callable by Zomes.
 
+pub mod abort;
+pub mod bundle;
 pub mod call;
+pub mod cap_grant;
 pub mod commit;
 pub mod debug;
+pub mod gas;
 pub mod get_entry;
 pub mod get_links;
 pub mod hash_entry;
 pub mod init_globals;
 pub mod link_entries;
+pub mod property;
 pub mod query;
+pub mod remove_entry;
+pub mod remove_link;
+pub mod send;
+pub mod update_entry;
 
 use holochain_dna::zome::capabilities::ReservedCapabilityNames;
 use nucleus::ribosome::{
     api::{
-        call::invoke_call, commit::invoke_commit_app_entry, debug::invoke_debug,
-        get_entry::invoke_get_entry, get_links::invoke_get_links, hash_entry::invoke_hash_entry,
-        init_globals::invoke_init_globals, link_entries::invoke_link_entries, query::invoke_query,
+        abort::invoke_abort,
+        bundle::{invoke_close_bundle, invoke_start_bundle},
+        call::invoke_call,
+        cap_grant::{invoke_grant_capability, invoke_revoke_capability},
+        commit::invoke_commit_app_entry,
+        debug::invoke_debug,
+        gas::invoke_gas,
+        get_entry::invoke_get_entry,
+        get_links::invoke_get_links,
+        hash_entry::invoke_hash_entry,
+        init_globals::invoke_init_globals,
+        link_entries::invoke_link_entries,
+        property::invoke_property,
+        query::invoke_query,
+        remove_entry::invoke_remove_entry,
+        remove_link::invoke_remove_link,
+        send::invoke_send,
+        update_entry::invoke_update_entry,
     },
     runtime::Runtime,
     Defn,
@@ -41,7 +65,7 @@ pub enum ZomeApiFunction {
     MissingNo = 0,
 
     /// Abort is a way to receive useful debug info from
-    /// assemblyscript memory allocators
+    /// assemblyscript memory allocators, it fails the zome call
     /// message: mem address in the wasm memory for an error message
     /// filename: mem address in the wasm memory for a filename
     /// line: line number
@@ -74,6 +98,47 @@ pub enum ZomeApiFunction {
     GetLinks,
     Query,
     HashEntry,
+
+    /// Commit a new version of an entry and mark the old one as modified
+    /// update_entry(new_entry: SerializedEntry, address: Address) -> Address
+    UpdateEntry,
+
+    /// Commit a deletion entry and mark the removed entry as deleted
+    /// remove_entry(address: Address)
+    RemoveEntry,
+
+    /// Commit a link removal and retract the link in the DHT
+    /// remove_link(base: Address, target: Address, tag: String)
+    RemoveLink,
+
+    /// Gas is imported by the gas counter injected into every module when it is compiled,
+    /// it charges the fuel cost of the next block of instructions to the running zome call
+    /// fuel: cost of the block
+    Gas,
+
+    /// Get a property from the properties of the DNA, by key or JSON pointer
+    /// property(name: String) -> JsonString
+    Property,
+
+    /// Send a direct message to another agent and return the response of its receive callback
+    /// send(to_agent: Address, payload: String, options: SendOptions) -> String
+    Send,
+
+    /// Stage the following commits of the zome call until the bundle gets closed
+    /// start_bundle(timeout_ms: u64, user_param: Value)
+    StartBundle,
+
+    /// Commit or discard the entries staged since start_bundle
+    /// close_bundle(action: BundleOnClose)
+    CloseBundle,
+
+    /// Commit a grant of access to functions of a capability of the zome
+    /// grant_capability(capability: String, functions: Vec<String>, assignees: Option<Vec<Address>>) -> Address
+    GrantCapability,
+
+    /// Commit the revocation of a capability grant
+    /// revoke_capability(token: Address)
+    RevokeCapability,
 }
 
 impl Defn for ZomeApiFunction {
@@ -90,6 +155,16 @@ impl Defn for ZomeApiFunction {
             ZomeApiFunction::GetLinks => "hc_get_links",
             ZomeApiFunction::Query => "hc_query",
             ZomeApiFunction::HashEntry => "hc_hash_entry",
+            ZomeApiFunction::UpdateEntry => "hc_update_entry",
+            ZomeApiFunction::RemoveEntry => "hc_remove_entry",
+            ZomeApiFunction::RemoveLink => "hc_remove_link",
+            ZomeApiFunction::Gas => "gas",
+            ZomeApiFunction::Property => "hc_property",
+            ZomeApiFunction::Send => "hc_send",
+            ZomeApiFunction::StartBundle => "hc_start_bundle",
+            ZomeApiFunction::CloseBundle => "hc_close_bundle",
+            ZomeApiFunction::GrantCapability => "hc_grant_capability",
+            ZomeApiFunction::RevokeCapability => "hc_revoke_capability",
         }
     }
 
@@ -129,6 +204,16 @@ impl FromStr for ZomeApiFunction {
             "hc_get_links" => Ok(ZomeApiFunction::GetLinks),
             "hc_query" => Ok(ZomeApiFunction::Query),
             "hc_hash_entry" => Ok(ZomeApiFunction::HashEntry),
+            "hc_update_entry" => Ok(ZomeApiFunction::UpdateEntry),
+            "hc_remove_entry" => Ok(ZomeApiFunction::RemoveEntry),
+            "hc_remove_link" => Ok(ZomeApiFunction::RemoveLink),
+            "gas" => Ok(ZomeApiFunction::Gas),
+            "hc_property" => Ok(ZomeApiFunction::Property),
+            "hc_send" => Ok(ZomeApiFunction::Send),
+            "hc_start_bundle" => Ok(ZomeApiFunction::StartBundle),
+            "hc_close_bundle" => Ok(ZomeApiFunction::CloseBundle),
+            "hc_grant_capability" => Ok(ZomeApiFunction::GrantCapability),
+            "hc_revoke_capability" => Ok(ZomeApiFunction::RevokeCapability),
             _ => Err("Cannot convert string to ZomeApiFunction"),
         }
     }
@@ -143,12 +228,9 @@ impl ZomeApiFunction {
     // cannot test this because PartialEq is not implemented for fns
     #[cfg_attr(tarpaulin, skip)]
     pub fn as_fn(&self) -> (fn(&mut Runtime, &RuntimeArgs) -> ZomeApiResult) {
-        // TODO Implement a proper "abort" function for handling assemblyscript aborts
-        // @see: https://github.com/holochain/holochain-rust/issues/324
-
         match *self {
             ZomeApiFunction::MissingNo => noop,
-            ZomeApiFunction::Abort => noop,
+            ZomeApiFunction::Abort => invoke_abort,
             ZomeApiFunction::Debug => invoke_debug,
             ZomeApiFunction::CommitAppEntry => invoke_commit_app_entry,
             ZomeApiFunction::GetAppEntry => invoke_get_entry,
@@ -158,6 +240,16 @@ impl ZomeApiFunction {
             ZomeApiFunction::GetLinks => invoke_get_links,
             ZomeApiFunction::Query => invoke_query,
             ZomeApiFunction::HashEntry => invoke_hash_entry,
+            ZomeApiFunction::UpdateEntry => invoke_update_entry,
+            ZomeApiFunction::RemoveEntry => invoke_remove_entry,
+            ZomeApiFunction::RemoveLink => invoke_remove_link,
+            ZomeApiFunction::Gas => invoke_gas,
+            ZomeApiFunction::Property => invoke_property,
+            ZomeApiFunction::Send => invoke_send,
+            ZomeApiFunction::StartBundle => invoke_start_bundle,
+            ZomeApiFunction::CloseBundle => invoke_close_bundle,
+            ZomeApiFunction::GrantCapability => invoke_grant_capability,
+            ZomeApiFunction::RevokeCapability => invoke_revoke_capability,
         }
     }
 }
@@ -201,8 +293,8 @@ pub mod tests {
                 // define the signature as 1 input, 1 output
                 // (import "env" "<canonical name>"
                 //      (func $zome_api_function
-                //          (param i32)
-                //          (result i32)
+                //          (param i64)
+                //          (result i64)
                 //      )
                 // )
                 //
@@ -219,10 +311,10 @@ pub mod tests {
                 // (func (export "test") ...)
                 //
                 // define the memory allocation for the memory manager that the serialized input
-                // struct can be found across as an i32 to the exported function, also the function
-                // return type is i32
-                // (param $allocation i32)
-                // (result i32)
+                // struct can be found across as an i64 to the exported function, also the function
+                // return type is i64
+                // (param $allocation i64)
+                // (result i64)
                 //
                 // call the imported function and pass the exported function arguments straight
                 // through, let the return also fall straight through
@@ -236,8 +328,8 @@ pub mod tests {
 (module
     (import "env" "{}"
         (func $zome_api_function
-            (param i32)
-            (result i32)
+            (param i64)
+            (result i64)
         )
     )
 
@@ -246,8 +338,8 @@ pub mod tests {
 
     (func
         (export "test")
-            (param $allocation i32)
-            (result i32)
+            (param $allocation i64)
+            (result i64)
 
         (call
             $zome_api_function
@@ -257,17 +349,17 @@ pub mod tests {
 
     (func
         (export "__hdk_validate_app_entry")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
-        (i32.const 0)
+        (i64.const 0)
     )
 
 
     (func
         (export "__hdk_get_validation_package_for_entry_type")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
         ;; This writes "Entry" into memory
         (i32.store (i32.const 0) (i32.const 34))
@@ -278,15 +370,15 @@ pub mod tests {
         (i32.store (i32.const 5) (i32.const 121))
         (i32.store (i32.const 6) (i32.const 34))
 
-        (i32.const 7)
+        (i64.const 7)
     )
 
     (func
         (export "__list_capabilities")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
-        (i32.const 0)
+        (i64.const 0)
     )
 )
                 "#,
@@ -381,6 +473,16 @@ pub mod tests {
             ("hc_get_links", ZomeApiFunction::GetLinks),
             ("hc_query", ZomeApiFunction::Query),
             ("hc_hash_entry", ZomeApiFunction::HashEntry),
+            ("hc_update_entry", ZomeApiFunction::UpdateEntry),
+            ("hc_remove_entry", ZomeApiFunction::RemoveEntry),
+            ("hc_remove_link", ZomeApiFunction::RemoveLink),
+            ("gas", ZomeApiFunction::Gas),
+            ("hc_property", ZomeApiFunction::Property),
+            ("hc_send", ZomeApiFunction::Send),
+            ("hc_start_bundle", ZomeApiFunction::StartBundle),
+            ("hc_close_bundle", ZomeApiFunction::CloseBundle),
+            ("hc_grant_capability", ZomeApiFunction::GrantCapability),
+            ("hc_revoke_capability", ZomeApiFunction::RevokeCapability),
         ] {
             assert_eq!(ZomeApiFunction::from_str(input).unwrap(), output);
         }
@@ -407,6 +509,16 @@ pub mod tests {
             (ZomeApiFunction::GetLinks, "hc_get_links"),
             (ZomeApiFunction::Query, "hc_query"),
             (ZomeApiFunction::HashEntry, "hc_hash_entry"),
+            (ZomeApiFunction::UpdateEntry, "hc_update_entry"),
+            (ZomeApiFunction::RemoveEntry, "hc_remove_entry"),
+            (ZomeApiFunction::RemoveLink, "hc_remove_link"),
+            (ZomeApiFunction::Gas, "gas"),
+            (ZomeApiFunction::Property, "hc_property"),
+            (ZomeApiFunction::Send, "hc_send"),
+            (ZomeApiFunction::StartBundle, "hc_start_bundle"),
+            (ZomeApiFunction::CloseBundle, "hc_close_bundle"),
+            (ZomeApiFunction::GrantCapability, "hc_grant_capability"),
+            (ZomeApiFunction::RevokeCapability, "hc_revoke_capability"),
         ] {
             assert_eq!(output, input.as_str());
         }
@@ -424,6 +536,16 @@ pub mod tests {
             ("hc_get_links", 8),
             ("hc_query", 9),
             ("hc_hash_entry", 10),
+            ("hc_update_entry", 11),
+            ("hc_remove_entry", 12),
+            ("hc_remove_link", 13),
+            ("gas", 14),
+            ("hc_property", 15),
+            ("hc_send", 16),
+            ("hc_start_bundle", 17),
+            ("hc_close_bundle", 18),
+            ("hc_grant_capability", 19),
+            ("hc_revoke_capability", 20),
         ] {
             assert_eq!(output, ZomeApiFunction::str_to_index(input));
         }
@@ -441,6 +563,16 @@ pub mod tests {
             (8, ZomeApiFunction::GetLinks),
             (9, ZomeApiFunction::Query),
             (10, ZomeApiFunction::HashEntry),
+            (11, ZomeApiFunction::UpdateEntry),
+            (12, ZomeApiFunction::RemoveEntry),
+            (13, ZomeApiFunction::RemoveLink),
+            (14, ZomeApiFunction::Gas),
+            (15, ZomeApiFunction::Property),
+            (16, ZomeApiFunction::Send),
+            (17, ZomeApiFunction::StartBundle),
+            (18, ZomeApiFunction::CloseBundle),
+            (19, ZomeApiFunction::GrantCapability),
+            (20, ZomeApiFunction::RevokeCapability),
         ] {
             assert_eq!(output, ZomeApiFunction::from_index(input));
         }
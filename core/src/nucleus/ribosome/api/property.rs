@@ -0,0 +1,135 @@
+use context::Context;
+use holochain_core_types::{error::HolochainError, json::JsonString};
+use holochain_wasm_utils::api_serialization::property::PropertyArgs;
+use nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use serde_json::Value;
+use std::{convert::TryFrom, sync::Arc};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::Property function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: PropertyArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_property(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let input = match PropertyArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        Err(_) => {
+            println!(
+                "invoke_property failed to deserialize PropertyArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = get_property(&runtime.context, &input.name);
+    runtime.store_result(result)
+}
+
+/// Look a property up in the properties of the running DNA.
+/// Returns the property as a JSON value.
+pub fn get_property(context: &Arc<Context>, name: &str) -> Result<JsonString, HolochainError> {
+    let dna = context
+        .state()
+        .and_then(|state| state.nucleus().dna())
+        .ok_or(HolochainError::DnaMissing)?;
+    resolve_property(&dna.properties, name)
+        .map(|value| JsonString::from(value.to_string()))
+        .ok_or_else(|| HolochainError::ErrorGeneric(format!("DNA property '{}' not found", name)))
+}
+
+/// A name starting with a '/' is a JSON pointer into the properties,
+/// any other name is a top level key.
+fn resolve_property<'a>(properties: &'a Value, name: &str) -> Option<&'a Value> {
+    if name.starts_with('/') {
+        properties.pointer(name)
+    } else {
+        properties.get(name)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+
+    use super::*;
+    use holochain_core_types::error::{CoreError, ZomeApiInternalResult};
+    use instance::tests::test_instance_and_context;
+    use nucleus::ribosome::{
+        api::{
+            tests::{
+                test_capability, test_zome_api_function_call, test_zome_api_function_wasm,
+                test_zome_name,
+            },
+            ZomeApiFunction,
+        },
+        Defn,
+    };
+    use serde_json;
+
+    fn test_properties() -> Value {
+        serde_json::from_str(r#"{"language": "en", "limits": {"max_posts": 10}}"#)
+            .expect("test properties should be valid JSON")
+    }
+
+    /// calls hc_property in an instance of a DNA with the test properties
+    fn call_property(name: &str) -> ZomeApiInternalResult {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::Property.as_str());
+        let mut dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        dna.properties = test_properties();
+        let dna_name = dna.name.to_string();
+        let (instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+
+        let args_bytes = JsonString::from(PropertyArgs {
+            name: name.to_string(),
+        }).into_bytes();
+        let call_result =
+            test_zome_api_function_call(&dna_name, context, &instance, &wasm, args_bytes);
+        ZomeApiInternalResult::try_from(call_result)
+            .expect("valid ZomeApiInternalResult JsonString")
+    }
+
+    #[test]
+    fn resolves_keys_and_json_pointers() {
+        let properties = test_properties();
+        assert_eq!(
+            Some(&Value::from("en")),
+            resolve_property(&properties, "language")
+        );
+        assert_eq!(
+            Some(&Value::from(10)),
+            resolve_property(&properties, "/limits/max_posts")
+        );
+        assert_eq!(None, resolve_property(&properties, "max_posts"));
+        assert_eq!(None, resolve_property(&properties, "/limits/foo"));
+    }
+
+    #[test]
+    fn can_get_property() {
+        let result = call_property("language");
+        assert!(result.ok);
+        assert_eq!("\"en\"", result.value);
+
+        let result = call_property("/limits/max_posts");
+        assert!(result.ok);
+        assert_eq!("10", result.value);
+    }
+
+    #[test]
+    fn errors_on_missing_property() {
+        let result = call_property("foo");
+        assert!(!result.ok);
+        let core_err = CoreError::try_from(result).expect("valid CoreError JsonString");
+        assert_eq!(
+            HolochainError::ErrorGeneric("DNA property 'foo' not found".to_string()),
+            core_err.kind,
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use agent::{actions::commit::*, state::create_new_chain_header};
+use dht::actions::remove_entry::*;
+use futures::{executor::block_on, FutureExt};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    delete_entry::DeletionEntry,
+    entry::ToEntry,
+    error::HolochainError,
+    hash::HashString,
+    validation::{EntryAction, EntryLifecycle, ValidationData, ValidationPackage},
+};
+use nucleus::{
+    actions::{build_validation_package::*, get_entry::get_entry, validate::*},
+    ribosome::{
+        api::{bundle::outside_bundle, ZomeApiResult},
+        Runtime,
+    },
+};
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::RemoveEntry function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected argument: Address of the entry to remove
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_remove_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let try_address = Address::try_from(args_str.clone());
+    // Exit on error
+    if try_address.is_err() {
+        println!(
+            "invoke_remove_entry failed to deserialize Address: {:?}",
+            args_str
+        );
+        return ribosome_error_code!(ArgumentDeserializationFailed);
+    }
+    let address = try_address.unwrap();
+
+    // Only commits can be staged in a bundle
+    if let Err(hc_err) = outside_bundle(&runtime.bundle) {
+        return runtime.store_result::<()>(Err(hc_err));
+    }
+
+    // Get the latest version of the entry to remove
+    let maybe_entry = block_on(get_entry(&runtime.context, address)).and_then(|maybe_entry| {
+        maybe_entry
+            .ok_or_else(|| HolochainError::ErrorGeneric(String::from("Entry to remove not found")))
+    });
+    let entry = match maybe_entry {
+        Ok(entry) => entry,
+        Err(hc_err) => return runtime.store_result::<()>(Err(hc_err)),
+    };
+    let deletion_entry = DeletionEntry::new(entry.address()).to_entry();
+    let entry_header = chain_header(entry.clone(), &runtime.context);
+    let deletion_header = create_new_chain_header(
+        &deletion_entry,
+        &*runtime
+            .context
+            .state()
+            .expect("state should be initialized")
+            .agent(),
+    );
+
+    // Wait for future to be resolved
+    let task_result: Result<(), HolochainError> = block_on(
+        // 1. Build the context needed for validation of the removal
+        build_validation_package(&entry, &runtime.context)
+            .and_then(|validation_package| {
+                Ok(ValidationData {
+                    package: validation_package,
+                    sources: vec![HashString::from("<insert your agent key here>")],
+                    lifecycle: EntryLifecycle::Chain,
+                    action: EntryAction::Delete,
+                    bundle: None,
+                    old_entry: Some(entry.clone().into()),
+                    old_entry_header: entry_header,
+                    membrane_proof: None,
+                    link_action: None,
+                })
+            })
+            // 2. Validate the removal with the rules of the removed entry's type
+            .and_then(|validation_data| {
+                validate_entry(
+                    entry.entry_type().clone(),
+                    entry.clone(),
+                    validation_data,
+                    &runtime.context,
+                )
+            })
+            // 3. Validate the deletion entry itself with the rules of core
+            .and_then(|_| {
+                validate_entry(
+                    deletion_entry.entry_type().clone(),
+                    deletion_entry.clone(),
+                    ValidationData {
+                        package: ValidationPackage::only_header(deletion_header),
+                        sources: vec![HashString::from("<insert your agent key here>")],
+                        lifecycle: EntryLifecycle::Chain,
+                        action: EntryAction::Commit,
+                        bundle: None,
+                        old_entry: None,
+                        old_entry_header: None,
+                        membrane_proof: None,
+                        link_action: None,
+                    },
+                    &runtime.context,
+                )
+            })
+            // 4. Commit the deletion entry to chain and DHT
+            .and_then(|_| {
+                commit_entry(
+                    deletion_entry.clone(),
+                    &runtime.context.action_channel,
+                    &runtime.context,
+                )
+            })
+            // 5. Mark the removed entry as DELETED
+            .and_then(|deletion_address| {
+                remove_entry(entry.address(), deletion_address, &runtime.context)
+            }),
+    );
+
+    runtime.store_result(task_result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        crud_status::CrudStatus,
+        entry::test_entry,
+        error::{CoreError, ZomeApiInternalResult},
+        json::JsonString,
+    };
+    use instance::tests::{test_context_and_logger, test_instance};
+    use nucleus::{
+        ribosome::{
+            self,
+            api::{
+                commit::tests::test_commit_args_bytes,
+                tests::{
+                    test_capability, test_parameters, test_zome_api_function_wasm, test_zome_name,
+                },
+                ZomeApiFunction,
+            },
+            Defn,
+        },
+        ZomeFnCall,
+    };
+    use std::{convert::TryFrom, sync::Arc};
+
+    /// dummy remove args from standard test entry
+    pub fn test_remove_args_bytes() -> Vec<u8> {
+        JsonString::from(test_entry().address()).into_bytes()
+    }
+
+    #[test]
+    /// test that we can remove a committed entry and that it can not be removed twice
+    fn test_remove_round_trip() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::RemoveEntry.as_str());
+        let commit_wasm = test_zome_api_function_wasm(ZomeApiFunction::CommitAppEntry.as_str());
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        let instance = test_instance(dna.clone()).expect("Could not initialize test instance");
+        let (context, _) = test_context_and_logger("joan");
+        let context = instance.initialize_context(context);
+
+        let call = ZomeFnCall::new(
+            &test_zome_name(),
+            &test_capability(),
+            "test",
+            test_parameters(),
+        );
+
+        ribosome::run_dna(
+            &dna.name.to_string(),
+            Arc::clone(&context),
+            commit_wasm,
+            &call,
+            Some(test_commit_args_bytes()),
+        ).expect("test should be callable");
+
+        let call_result = ribosome::run_dna(
+            &dna.name.to_string(),
+            Arc::clone(&context),
+            wasm.clone(),
+            &call,
+            Some(test_remove_args_bytes()),
+        ).expect("test should be callable");
+
+        assert_eq!(
+            call_result,
+            JsonString::from(
+                String::from(JsonString::from(ZomeApiInternalResult::success(None))) + "\u{0}"
+            ),
+        );
+        assert_eq!(
+            Ok(CrudStatus::DELETED),
+            context
+                .state()
+                .unwrap()
+                .dht()
+                .get_crud_status(&test_entry().address()),
+        );
+
+        let call_result = ribosome::run_dna(
+            &dna.name.to_string(),
+            Arc::clone(&context),
+            wasm,
+            &call,
+            Some(test_remove_args_bytes()),
+        ).expect("test should be callable");
+
+        let result = ZomeApiInternalResult::try_from(call_result)
+            .expect("valid ZomeApiInternalResult JsonString");
+        assert!(!result.ok);
+
+        let core_err = CoreError::try_from(result).expect("valid CoreError JsonString");
+        assert_eq!("Entry to remove not found", core_err.kind.to_string(),);
+    }
+
+}
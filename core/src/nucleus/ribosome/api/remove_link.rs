@@ -0,0 +1,250 @@
+use agent::{actions::commit::*, state::create_new_chain_header};
+use dht::actions::remove_link::*;
+use futures::{executor::block_on, FutureExt};
+use holochain_core_types::{
+    entry::ToEntry,
+    entry_type::EntryType,
+    error::HolochainError,
+    hash::HashString,
+    links_entry::{LinkActionKind, LinkEntry},
+    validation::{EntryAction, EntryLifecycle, LinkAction, ValidationData, ValidationPackage},
+};
+use holochain_wasm_utils::api_serialization::link_entries::LinkEntriesArgs;
+use nucleus::{
+    actions::validate::*,
+    ribosome::{
+        api::{bundle::outside_bundle, ZomeApiResult},
+        Runtime,
+    },
+};
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::RemoveLink function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: LinkEntriesArgs
+pub fn invoke_remove_link(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let input = match LinkEntriesArgs::try_from(args_str.clone()) {
+        Ok(entry_input) => entry_input,
+        // Exit on error
+        Err(_) => {
+            println!(
+                "invoke_remove_link failed to deserialize LinkEntriesArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // Only commits can be staged in a bundle
+    if let Err(hc_err) = outside_bundle(&runtime.bundle) {
+        return runtime.store_result::<()>(Err(hc_err));
+    }
+    let link = input.to_link();
+
+    // Don't commit a removal for a link that is not there
+    let link_exists = runtime
+        .context
+        .state()
+        .expect("state should be initialized")
+        .dht()
+        .get_links(link.base().clone(), link.tag().clone())
+        .map(|links| links.iter().any(|eav| eav.value() == *link.target()));
+    match link_exists {
+        Ok(true) => (),
+        Ok(false) => {
+            return runtime.store_result::<()>(Err(HolochainError::ErrorGeneric(String::from(
+                "Link to remove not found",
+            ))))
+        }
+        Err(hc_err) => return runtime.store_result::<()>(Err(hc_err)),
+    }
+
+    let link_entry = LinkEntry::from_link(LinkActionKind::DELETE, &link).to_entry();
+    let chain_header = create_new_chain_header(
+        &link_entry,
+        &*runtime
+            .context
+            .state()
+            .expect("state should be initialized")
+            .agent(),
+    );
+    let validation_data = |lifecycle| ValidationData {
+        package: ValidationPackage::only_header(chain_header.clone()),
+        sources: vec![HashString::from("<insert your agent key here>")],
+        lifecycle,
+        action: EntryAction::Commit,
+        bundle: None,
+        old_entry: None,
+        old_entry_header: None,
+        membrane_proof: None,
+        link_action: Some(LinkAction::Delete),
+    };
+
+    // Wait for future to be resolved
+    let task_result: Result<(), HolochainError> = block_on(
+        // 1. Validate the removal before committing it
+        validate_entry(
+            EntryType::Link,
+            link_entry.clone(),
+            validation_data(EntryLifecycle::Chain),
+            &runtime.context,
+        )
+        // 2. Commit the removal to the source chain so it stays auditable
+        .and_then(|_| {
+            commit_entry(
+                link_entry.clone(),
+                &runtime.context.action_channel,
+                &runtime.context,
+            )
+        })
+        // 3. Validate the removal again before retracting the link in the DHT.
+        // Without a network we are our own DHT node, so this happens right here.
+        .and_then(|_| {
+            validate_entry(
+                EntryType::Link,
+                link_entry.clone(),
+                validation_data(EntryLifecycle::Dht),
+                &runtime.context,
+            )
+        })
+        // 4. Retract the link in the DHT
+        .and_then(|_| remove_link(&link, &runtime.context)),
+    );
+
+    runtime.store_result(task_result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use agent::actions::commit::commit_entry;
+    use context::Context;
+    use dht::actions::add_link::add_link;
+    use futures::executor::block_on;
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        entry::test_entry,
+        error::{CoreError, ZomeApiInternalResult},
+        json::JsonString,
+        links_entry::Link,
+    };
+    use instance::tests::{test_context_and_logger, test_instance};
+    use nucleus::ribosome::{
+        api::{
+            link_entries::tests::{declare_test_link, test_link_args_bytes},
+            tests::*,
+            ZomeApiFunction,
+        },
+        Defn,
+    };
+    use std::{convert::TryFrom, sync::Arc};
+
+    #[test]
+    fn errors_if_link_is_not_present_test() {
+        let (call_result, _) =
+            test_zome_api_function(ZomeApiFunction::RemoveLink.as_str(), test_link_args_bytes());
+
+        let result = ZomeApiInternalResult::try_from(call_result)
+            .expect("valid ZomeApiInternalResult JsonString");
+        assert!(!result.ok);
+
+        let core_err = CoreError::try_from(result).expect("valid CoreError JsonString");
+        assert_eq!("Link to remove not found", core_err.kind.to_string(),);
+    }
+
+    /// Adds the link of test_link_args_bytes() and calls remove_link for it,
+    /// with the link declared in the DNA or not
+    fn remove_test_link(declared: bool) -> (JsonString, Arc<Context>) {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::RemoveLink.as_str());
+        let mut dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        if declared {
+            declare_test_link(&mut dna);
+        }
+
+        let dna_name = &dna.name.to_string().clone();
+        let instance = test_instance(dna).expect("Could not create test instance");
+
+        let (context, _) = test_context_and_logger("joan");
+        let initialized_context = instance.initialize_context(context);
+
+        let entry = test_entry();
+        block_on(commit_entry(
+            entry.clone(),
+            &initialized_context.action_channel.clone(),
+            &initialized_context,
+        )).expect("Could not commit entry for testing");
+        let link = Link::new(&entry.address(), &entry.address(), "test-tag");
+        block_on(add_link(
+            &link,
+            &Address::from("link-instance"),
+            &initialized_context,
+        )).expect("Could not add link for testing");
+
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            initialized_context.clone(),
+            &instance,
+            &wasm,
+            test_link_args_bytes(),
+        );
+        (call_result, initialized_context)
+    }
+
+    #[test]
+    fn can_remove_link_test() {
+        let (call_result, context) = remove_test_link(true);
+
+        assert_eq!(
+            call_result,
+            JsonString::from(
+                String::from(JsonString::from(ZomeApiInternalResult::success(None))) + "\u{0}"
+            ),
+        );
+
+        let links = context
+            .state()
+            .unwrap()
+            .dht()
+            .get_links(test_entry().address(), "test-tag".into())
+            .unwrap();
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn errors_if_removal_is_invalid_test() {
+        let (call_result, context) = remove_test_link(false);
+
+        let result = ZomeApiInternalResult::try_from(call_result)
+            .expect("valid ZomeApiInternalResult JsonString");
+        assert!(!result.ok);
+        let core_err = CoreError::try_from(result).expect("valid CoreError JsonString");
+        assert_eq!(
+            "Link with tag 'test-tag' from 'testEntryType' to 'testEntryType' is not declared in the DNA",
+            core_err.kind.to_string(),
+        );
+
+        // the removal was not committed and the link is still there
+        let state = context.state().unwrap();
+        assert_eq!(
+            Some(test_entry().address()),
+            state
+                .agent()
+                .top_chain_header()
+                .map(|header| header.entry_address().clone())
+        );
+        let links = state
+            .dht()
+            .get_links(test_entry().address(), "test-tag".into())
+            .unwrap();
+        assert_eq!(1, links.len());
+    }
+}
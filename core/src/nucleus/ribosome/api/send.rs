@@ -0,0 +1,177 @@
+use holochain_core_types::json::RawString;
+use holochain_wasm_utils::api_serialization::send::SendArgs;
+use network::send_message;
+use nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::Send function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: SendArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_send(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let input = match SendArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        Err(_) => {
+            println!("invoke_send failed to deserialize SendArgs: {:?}", args_str);
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // the message is handled by the zome of the same name in the instance of the receiver
+    let zome_name = runtime.zome_call.zome_name.clone();
+    let result = send_message(&runtime.context, &zome_name, input).map(RawString::from);
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use self::wabt::Wat2Wasm;
+    use super::*;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        error::{CoreError, HolochainError, ZomeApiInternalResult},
+        json::JsonString,
+    };
+    use holochain_dna::zome::Config;
+    use holochain_net::in_process::InProcessHub;
+    use holochain_wasm_utils::api_serialization::send::SendOptions;
+    use instance::tests::{test_context, test_instance_with_context};
+    use nucleus::ribosome::api::tests::{
+        test_capability, test_zome_api_function_call, test_zome_name,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// a receive callback that responds with "pong",
+    /// stored out of the way of the input written to the start of the memory
+    const PONG_RECEIVE: &str = r#"
+    (data (i32.const 1024) "\"pong\"")
+
+    (func
+        (export "receive")
+        (param $allocation i64)
+        (result i64)
+
+        (i64.const 4398046511110)
+    )
+    "#;
+
+    /// a receive callback that never responds
+    const STUCK_RECEIVE: &str = r#"
+    (func
+        (export "receive")
+        (param $allocation i64)
+        (result i64)
+
+        (loop (br 0))
+        (i64.const 0)
+    )
+    "#;
+
+    /// wasm with a "test" function that passes its input to hc_send
+    /// and the given receive callback
+    fn test_send_wasm(receive: &str) -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(format!(
+                r#"
+(module
+    (import "env" "hc_send"
+        (func $hc_send
+            (param i64)
+            (result i64)
+        )
+    )
+
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (func
+        (export "test")
+        (param $allocation i64)
+        (result i64)
+
+        (call $hc_send (get_local $allocation))
+    )
+    {}
+)
+                "#,
+                receive
+            ))
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec()
+    }
+
+    /// has alice send "ping" to bob, both running the given receive callback
+    fn send_ping(receive: &str, config: Config, timeout_ms: u64) -> ZomeApiInternalResult {
+        let wasm = test_send_wasm(receive);
+        let mut dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        dna.zomes.get_mut(&test_zome_name()).unwrap().config = config;
+        let dna_name = dna.name.to_string();
+
+        let hub = InProcessHub::new();
+        let mut alice_context = (*test_context("alice")).clone();
+        alice_context.set_network(Arc::new(Mutex::new(hub.connect())));
+        let mut bob_context = (*test_context("bob")).clone();
+        bob_context.set_network(Arc::new(Mutex::new(hub.connect())));
+        let (alice, alice_context) =
+            test_instance_with_context(dna.clone(), Arc::new(alice_context))
+                .expect("Could not create test instance");
+        let (_bob, bob_context) = test_instance_with_context(dna, Arc::new(bob_context))
+            .expect("Could not create test instance");
+
+        let args_bytes = JsonString::from(SendArgs {
+            to_agent: bob_context.agent.address(),
+            payload: "ping".to_string(),
+            options: SendOptions { timeout_ms },
+        }).into_bytes();
+        let call_result =
+            test_zome_api_function_call(&dna_name, alice_context, &alice, &wasm, args_bytes);
+        ZomeApiInternalResult::try_from(call_result)
+            .expect("valid ZomeApiInternalResult JsonString")
+    }
+
+    fn assert_send_error(expected: HolochainError, result: ZomeApiInternalResult) {
+        assert!(!result.ok);
+        let core_err = CoreError::try_from(result).expect("valid CoreError JsonString");
+        assert_eq!(expected, core_err.kind);
+    }
+
+    #[test]
+    fn returns_response_of_receiver() {
+        let result = send_ping(PONG_RECEIVE, Config::new(), 5_000);
+        assert!(result.ok);
+        assert_eq!("\"pong\"", result.value);
+    }
+
+    #[test]
+    fn errors_on_missing_receive() {
+        assert_send_error(
+            HolochainError::NetworkError("zome 'test_zome' does not implement receive".to_string()),
+            send_ping("", Config::new(), 5_000),
+        );
+    }
+
+    #[test]
+    fn times_out_on_stuck_receiver() {
+        // stop the receiver eventually
+        let mut config = Config::new();
+        config.timeout_ms = Some(1_000);
+
+        assert_send_error(
+            HolochainError::Timeout,
+            send_ping(STUCK_RECEIVE, config, 100),
+        );
+    }
+}
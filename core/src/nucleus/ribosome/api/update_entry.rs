@@ -0,0 +1,215 @@
+use agent::actions::commit::*;
+use dht::actions::update_entry::*;
+use futures::{executor::block_on, FutureExt};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::Entry,
+    error::HolochainError,
+    hash::HashString,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
+use holochain_wasm_utils::api_serialization::update_entry::UpdateEntryArgs;
+use nucleus::{
+    actions::{build_validation_package::*, get_entry::get_entry, validate::*},
+    ribosome::{
+        api::{bundle::outside_bundle, ZomeApiResult},
+        Runtime,
+    },
+};
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::UpdateEntry function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: UpdateEntryArgs
+/// Returns the address of the new version of the entry
+pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args)?;
+    let update_entry_args = match UpdateEntryArgs::try_from(args_str.clone()) {
+        Ok(entry_input) => entry_input,
+        // Exit on error
+        Err(_) => {
+            println!(
+                "invoke_update_entry failed to deserialize UpdateEntryArgs: {:?}",
+                args_str
+            );
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // Only commits can be staged in a bundle
+    if let Err(hc_err) = outside_bundle(&runtime.bundle) {
+        return runtime.store_result::<Address>(Err(hc_err));
+    }
+
+    let new_entry = Entry::from(update_entry_args.new_entry);
+
+    // Get the latest version of the entry to update
+    let maybe_old_entry = block_on(get_entry(&runtime.context, update_entry_args.address))
+        .and_then(|maybe_entry| {
+            maybe_entry.ok_or_else(|| {
+                HolochainError::ErrorGeneric(String::from("Entry to update not found"))
+            })
+        })
+        .and_then(|old_entry| {
+            if old_entry.entry_type() == new_entry.entry_type() {
+                Ok(old_entry)
+            } else {
+                Err(HolochainError::ErrorGeneric(String::from(
+                    "Entry type of the new version does not match the updated entry",
+                )))
+            }
+        });
+    let old_entry = match maybe_old_entry {
+        Ok(old_entry) => old_entry,
+        Err(hc_err) => return runtime.store_result::<Address>(Err(hc_err)),
+    };
+    let old_entry_header = chain_header(old_entry.clone(), &runtime.context);
+
+    // Wait for future to be resolved
+    let task_result: Result<Address, HolochainError> = block_on(
+        // 1. Build the context needed for validation of the new version
+        build_validation_package(&new_entry, &runtime.context)
+            .and_then(|validation_package| {
+                Ok(ValidationData {
+                    package: validation_package,
+                    sources: vec![HashString::from("<insert your agent key here>")],
+                    lifecycle: EntryLifecycle::Chain,
+                    action: EntryAction::Modify,
+                    bundle: None,
+                    old_entry: Some(old_entry.clone().into()),
+                    old_entry_header,
+                    membrane_proof: None,
+                    link_action: None,
+                })
+            })
+            // 2. Validate the new version
+            .and_then(|validation_data| {
+                validate_entry(
+                    new_entry.entry_type().clone(),
+                    new_entry.clone(),
+                    validation_data,
+                    &runtime.context,
+                )
+            })
+            // 3. Commit the valid new version to chain and DHT
+            .and_then(|_| {
+                commit_entry(
+                    new_entry.clone(),
+                    &runtime.context.action_channel,
+                    &runtime.context,
+                )
+            })
+            // 4. Mark the old version as MODIFIED and link it to the new one
+            .and_then(|new_address| {
+                update_entry(old_entry.address(), new_address.clone(), &runtime.context)
+                    .map(|_| new_address)
+            }),
+    );
+
+    runtime.store_result(task_result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        entry::{test_entry, Entry, SerializedEntry},
+        error::ZomeApiInternalResult,
+        json::JsonString,
+    };
+    use holochain_wasm_utils::api_serialization::update_entry::UpdateEntryArgs;
+    use instance::tests::{test_context_and_logger, test_instance};
+    use nucleus::{
+        ribosome::{
+            self,
+            api::{
+                commit::tests::test_commit_args_bytes,
+                tests::{
+                    test_capability, test_parameters, test_zome_api_function_wasm, test_zome_name,
+                },
+                ZomeApiFunction,
+            },
+            Defn,
+        },
+        ZomeFnCall,
+    };
+    use std::sync::Arc;
+
+    /// the new version of the standard test entry
+    pub fn test_updated_entry() -> Entry {
+        Entry::new(
+            test_entry().entry_type().clone(),
+            "updated test entry value",
+        )
+    }
+
+    /// dummy update args from standard test entry
+    pub fn test_update_args_bytes() -> Vec<u8> {
+        let args = UpdateEntryArgs {
+            new_entry: SerializedEntry::from(test_updated_entry()),
+            address: test_entry().address(),
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that we can update a committed entry and get the new address back from WASM
+    fn test_update_round_trip() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::UpdateEntry.as_str());
+        let commit_wasm = test_zome_api_function_wasm(ZomeApiFunction::CommitAppEntry.as_str());
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability(),
+            wasm.clone(),
+        );
+        let instance = test_instance(dna.clone()).expect("Could not initialize test instance");
+        let (context, _) = test_context_and_logger("joan");
+        let context = instance.initialize_context(context);
+
+        let call = ZomeFnCall::new(
+            &test_zome_name(),
+            &test_capability(),
+            "test",
+            test_parameters(),
+        );
+
+        ribosome::run_dna(
+            &dna.name.to_string(),
+            Arc::clone(&context),
+            commit_wasm,
+            &call,
+            Some(test_commit_args_bytes()),
+        ).expect("test should be callable");
+
+        let call_result = ribosome::run_dna(
+            &dna.name.to_string(),
+            Arc::clone(&context),
+            wasm,
+            &call,
+            Some(test_update_args_bytes()),
+        ).expect("test should be callable");
+
+        assert_eq!(
+            call_result,
+            JsonString::from(
+                String::from(JsonString::from(ZomeApiInternalResult::success(
+                    test_updated_entry().address()
+                ))) + "\u{0}"
+            ),
+        );
+        assert_eq!(
+            Ok(Some(test_updated_entry().address())),
+            context
+                .state()
+                .unwrap()
+                .dht()
+                .get_latest_address(&test_entry().address()),
+        );
+    }
+
+}
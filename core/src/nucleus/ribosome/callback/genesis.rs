@@ -3,12 +3,7 @@ use context::Context;
 use nucleus::ribosome::callback::{Callback, CallbackParams, CallbackResult};
 use std::sync::Arc;
 
-pub fn genesis(
-    context: Arc<Context>,
-    zome: &str,
-    // we ignore params for genesis
-    params: &CallbackParams,
-) -> CallbackResult {
+pub fn genesis(context: Arc<Context>, zome: &str, params: &CallbackParams) -> CallbackResult {
     call(context, zome, &Callback::Genesis, params)
 }
 
@@ -29,7 +24,7 @@ pub mod tests {
             .expect("Test callback instance could not be initialized");
         let context = instance.initialize_context(test_context("test"));
 
-        let result = genesis(context, zome, &CallbackParams::Genesis);
+        let result = genesis(context, zome, &CallbackParams::Genesis(None));
 
         assert_eq!(CallbackResult::Pass, result);
     }
@@ -46,7 +41,7 @@ pub mod tests {
 
         let context = instance.initialize_context(test_context("test"));
 
-        let result = genesis(context, zome, &CallbackParams::Genesis);
+        let result = genesis(context, zome, &CallbackParams::Genesis(None));
 
         assert_eq!(CallbackResult::NotImplemented, result);
     }
@@ -57,7 +52,7 @@ pub mod tests {
         let instance = test_callback_instance(zome, Callback::Genesis.as_str(), 1);
         assert!(instance.is_err());
         let error = instance.err().unwrap();
-        assert_eq!("\"".to_string(), error);
+        assert_eq!("{".to_string(), error);
     }
 
 }
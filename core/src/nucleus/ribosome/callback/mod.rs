@@ -4,16 +4,19 @@
 pub mod genesis;
 pub mod receive;
 pub mod validate_entry;
+pub mod validate_link;
+pub mod validate_sys_entry;
 pub mod validation_package;
 
 use context::Context;
 use holochain_core_types::{
     entry::SerializedEntry,
-    error::{HolochainError, RibosomeReturnCode},
+    error::{DnaError, HolochainError, RibosomeReturnCode},
     json::{default_to_json, JsonString},
     validation::ValidationPackageDefinition,
 };
 use holochain_dna::{wasm::DnaWasm, zome::capabilities::ReservedCapabilityNames, Dna};
+use holochain_wasm_utils::api_serialization::send::ReceiveParams;
 use nucleus::{
     ribosome::{
         self,
@@ -74,8 +77,6 @@ impl Callback {
         match *self {
             Callback::MissingNo => noop,
             Callback::Genesis => genesis,
-            // @TODO call this from somewhere
-            // @see https://github.com/holochain/holochain-rust/issues/201
             Callback::Receive => receive,
         }
     }
@@ -108,8 +109,6 @@ impl Defn for Callback {
         match *self {
             Callback::MissingNo => ReservedCapabilityNames::MissingNo,
             Callback::Genesis => ReservedCapabilityNames::LifeCycle,
-            // @TODO call this from somewhere
-            // @see https://github.com/holochain/holochain-rust/issues/201
             Callback::Receive => ReservedCapabilityNames::Communication,
         }
     }
@@ -117,21 +116,22 @@ impl Defn for Callback {
 
 #[derive(Debug, Serialize, Deserialize, DefaultJson)]
 pub enum CallbackParams {
-    Genesis,
+    /// The membrane proof the agent joins with, if any
+    Genesis(Option<String>),
     ValidateCommit(SerializedEntry),
-    // @TODO call this from somewhere
-    // @see https://github.com/holochain/holochain-rust/issues/201
-    Receive,
+    Receive(ReceiveParams),
 }
 
 impl ToString for CallbackParams {
     fn to_string(&self) -> String {
         match self {
-            CallbackParams::Genesis => String::new(),
+            CallbackParams::Genesis(membrane_proof) => membrane_proof.clone().unwrap_or_default(),
             CallbackParams::ValidateCommit(serialized_entry) => {
                 String::from(JsonString::from(serialized_entry.to_owned()))
             }
-            CallbackParams::Receive => String::new(),
+            CallbackParams::Receive(receive_params) => {
+                String::from(JsonString::from(receive_params.to_owned()))
+            }
         }
     }
 }
@@ -149,6 +149,8 @@ pub enum CallbackResult {
     Fail(String),
     NotImplemented,
     ValidationPackageDefinition(ValidationPackageDefinition),
+    /// Response of the receive callback to a direct message
+    ReceiveResult(String),
 }
 
 impl From<CallbackResult> for JsonString {
@@ -197,7 +199,11 @@ pub(crate) fn run_callback(
         } else {
             CallbackResult::Fail(call_result.to_string())
         },
-        Err(_) => CallbackResult::NotImplemented,
+        // The zome does not define this callback
+        Err(HolochainError::Dna(DnaError::ZomeFunctionNotFound(_))) => {
+            CallbackResult::NotImplemented
+        }
+        Err(err) => CallbackResult::Fail(err.to_string()),
     }
 }
 
@@ -277,7 +283,7 @@ pub mod tests {
 
     /// generates the wasm to dispatch any zome API function with a single memomry managed runtime
     /// and bytes argument
-    pub fn test_callback_wasm(canonical_name: &str, result: i32) -> Vec<u8> {
+    pub fn test_callback_wasm(canonical_name: &str, result: i64) -> Vec<u8> {
         Wat2Wasm::new()
             .canonicalize_lebs(false)
             .write_debug_names(true)
@@ -296,8 +302,8 @@ pub mod tests {
                 // define the signature as 1 input, 1 output
                 // (import "env" "<canonical name>"
                 //      (func $zome_api_function
-                //          (param i32)
-                //          (result i32)
+                //          (param i64)
+                //          (result i64)
                 //      )
                 // )
                 //
@@ -314,10 +320,10 @@ pub mod tests {
                 // (func (export "test") ...)
                 //
                 // define the memory allocation for the memory manager that the serialized input
-                // struct can be found across as an i32 to the exported function, also the function
-                // return type is i32
-                // (param $allocation i32)
-                // (result i32)
+                // struct can be found across as an i64 to the exported function, also the function
+                // return type is i64
+                // (param $allocation i64)
+                // (result i64)
                 //
                 // call the imported function and pass the exported function arguments straight
                 // through, let the return also fall straight through
@@ -335,10 +341,10 @@ pub mod tests {
 
     (func
         (export "{}")
-        (param $allocation i32)
-        (result i32)
+        (param $allocation i64)
+        (result i64)
 
-        (i32.const {})
+        (i64.const {})
     )
 )
                 "#,
@@ -353,7 +359,7 @@ pub mod tests {
     pub fn test_callback_instance(
         zome: &str,
         canonical_name: &str,
-        result: i32,
+        result: i64,
     ) -> Result<Instance, String> {
         let dna = test_utils::create_test_dna_with_wasm(
             zome,
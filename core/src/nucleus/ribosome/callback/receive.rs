@@ -1,27 +1,120 @@
-use super::call;
 use context::Context;
-use nucleus::ribosome::callback::{Callback, CallbackParams, CallbackResult};
-use std::sync::Arc;
-
-pub fn receive(
-    context: Arc<Context>,
-    zome: &str,
-    // we ignore params for genesis
-    params: &CallbackParams,
-) -> CallbackResult {
-    call(context, zome, &Callback::Receive, params)
+use holochain_core_types::{
+    error::{DnaError, HolochainError},
+    json::RawString,
+};
+use nucleus::{
+    ribosome::{
+        self,
+        callback::{get_dna, get_wasm, Callback, CallbackParams, CallbackResult},
+        Defn,
+    },
+    Provenance, ZomeFnCall,
+};
+use std::{convert::TryFrom, sync::Arc};
+
+/// Runs the receive callback of a zome with a direct message from another agent.
+/// The response of the zome is returned as CallbackResult::ReceiveResult,
+/// a zome without a receive callback gives CallbackResult::NotImplemented.
+pub fn receive(context: Arc<Context>, zome: &str, params: &CallbackParams) -> CallbackResult {
+    let receive_params = match params {
+        CallbackParams::Receive(receive_params) => receive_params.clone(),
+        _ => return CallbackResult::Fail("receive called without a message".to_string()),
+    };
+
+    let dna = get_dna(&context).expect("Callback called without DNA set!");
+    let wasm = match get_wasm(&context, zome) {
+        Some(wasm) => wasm,
+        None => return CallbackResult::NotImplemented,
+    };
+
+    let mut zome_call = ZomeFnCall::new(
+        zome,
+        Callback::Receive.capability().as_str(),
+        Callback::Receive.as_str(),
+        receive_params.clone(),
+    );
+    zome_call.provenance = Provenance::RemoteAgent(receive_params.from);
+
+    match ribosome::run_dna(
+        &dna.name,
+        context,
+        wasm.code,
+        &zome_call,
+        Some(zome_call.clone().parameters.into_bytes()),
+    ) {
+        // a zome that has nothing to say responds with an empty string
+        Ok(response) => if response.is_null() {
+            CallbackResult::ReceiveResult(String::new())
+        } else {
+            match RawString::try_from(response.clone()) {
+                Ok(raw_string) => CallbackResult::ReceiveResult(String::from(raw_string)),
+                Err(_) => CallbackResult::Fail(format!(
+                    "receive callback did not respond with a string: {}",
+                    response
+                )),
+            }
+        },
+        // The zome does not define the callback
+        Err(HolochainError::Dna(DnaError::ZomeFunctionNotFound(_))) => {
+            CallbackResult::NotImplemented
+        }
+        Err(err) => CallbackResult::Fail(err.to_string()),
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
 
+    use self::wabt::Wat2Wasm;
     use super::receive;
-    use instance::tests::test_context;
+    use holochain_core_types::{cas::content::Address, error::RibosomeErrorCode};
+    use holochain_wasm_utils::api_serialization::send::ReceiveParams;
+    use instance::tests::{test_context, test_instance};
     use nucleus::ribosome::{
         callback::{tests::test_callback_instance, Callback, CallbackParams, CallbackResult},
         Defn,
     };
 
+    fn test_receive_params() -> CallbackParams {
+        CallbackParams::Receive(ReceiveParams {
+            from: Address::from("bob"),
+            payload: "ping".to_string(),
+        })
+    }
+
+    /// wasm with a receive callback that responds with "pong",
+    /// stored out of the way of the input written to the start of the memory
+    fn test_pong_wasm() -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(
+                r#"
+(module
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (data (i32.const 1024) "\"pong\"")
+
+    (func
+        (export "receive")
+        (param $allocation i64)
+        (result i64)
+
+        ;; allocation of 6 bytes at offset 1024
+        (i64.const 4398046511110)
+    )
+)
+                "#,
+            )
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec()
+    }
+
     #[test]
     fn not_implemented() {
         let zome = "test_zome";
@@ -33,35 +126,66 @@ pub mod tests {
         ).expect("Test callback instance could not be initialized");
         let context = instance.initialize_context(test_context("test"));
 
-        let result = receive(context, zome, &CallbackParams::Receive);
+        let result = receive(context, zome, &test_receive_params());
 
         assert_eq!(CallbackResult::NotImplemented, result);
     }
 
     #[test]
-    fn pass_test() {
+    fn empty_response_test() {
         let zome = "test_zome";
         let instance = test_callback_instance(zome, Callback::Receive.as_str(), 0)
             .expect("Test callback instance could not be initialized");
         let context = instance.initialize_context(test_context("test"));
 
-        let result = receive(context, zome, &CallbackParams::Receive);
+        let result = receive(context, zome, &test_receive_params());
 
-        assert_eq!(CallbackResult::Pass, result);
+        assert_eq!(CallbackResult::ReceiveResult(String::new()), result);
+    }
+
+    #[test]
+    fn response_test() {
+        let zome = "test_zome";
+        let dna = test_utils::create_test_dna_with_wasm(
+            zome,
+            Callback::Receive.capability().as_str(),
+            test_pong_wasm(),
+        );
+        let instance = test_instance(dna).expect("Test instance could not be initialized");
+        let context = instance.initialize_context(test_context("test"));
+
+        let result = receive(context, zome, &test_receive_params());
+
+        assert_eq!(CallbackResult::ReceiveResult("pong".to_string()), result);
     }
 
     #[test]
     fn fail_test() {
         let zome = "test_zome";
-        let instance = test_callback_instance(zome, Callback::Receive.as_str(), 1)
-            .expect("Test callback instance could not be initialized");
+        let instance = test_callback_instance(
+            zome,
+            Callback::Receive.as_str(),
+            RibosomeErrorCode::Unspecified as i64,
+        ).expect("Test callback instance could not be initialized");
         let context = instance.initialize_context(test_context("test"));
 
-        let result = receive(context, zome, &CallbackParams::Receive);
+        let result = receive(context, zome, &test_receive_params());
 
-        // @TODO how to get fail strings back out?
-        // @see https://github.com/holochain/holochain-rust/issues/205
-        assert_eq!(CallbackResult::Fail("\"".to_string()), result);
+        assert_eq!(CallbackResult::Fail("Unspecified".to_string()), result);
     }
 
+    #[test]
+    fn fails_without_message() {
+        let zome = "test_zome";
+        let instance = test_callback_instance(zome, Callback::Receive.as_str(), 0)
+            .expect("Test callback instance could not be initialized");
+        let context = instance.initialize_context(test_context("test"));
+
+        let result = receive(context, zome, &CallbackParams::Genesis(None));
+
+        assert_eq!(
+            CallbackResult::Fail("receive called without a message".to_string()),
+            result
+        );
+    }
 }
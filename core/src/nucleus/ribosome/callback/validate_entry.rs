@@ -8,7 +8,10 @@ use holochain_wasm_utils::api_serialization::validation::EntryValidationArgs;
 use nucleus::{
     ribosome::{
         self,
-        callback::{get_dna, get_wasm, CallbackResult},
+        callback::{
+            get_dna, get_wasm, validate_link::validate_link_entry,
+            validate_sys_entry::validate_sys_entry, CallbackResult,
+        },
     },
     ZomeFnCall,
 };
@@ -27,7 +30,18 @@ pub fn validate_entry(
             validation_data,
             context,
         )?),
-        EntryType::Dna => Ok(CallbackResult::Pass),
+        EntryType::Link => Ok(validate_link_entry(entry, validation_data, context)?),
+        EntryType::Dna
+        | EntryType::AgentId
+        | EntryType::ChainHeader
+        | EntryType::Key
+        | EntryType::Deletion
+        | EntryType::MembraneProof => Ok(validate_sys_entry(
+            entry,
+            entry_type,
+            validation_data,
+            context,
+        )?),
         _ => Ok(CallbackResult::NotImplemented),
     }
 }
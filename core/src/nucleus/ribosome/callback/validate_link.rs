@@ -0,0 +1,274 @@
+extern crate serde_json;
+use context::Context;
+use holochain_core_types::{
+    cas::{content::Address, storage::ContentAddressableStorage},
+    entry::{Entry, ToEntry},
+    error::HolochainError,
+    links_entry::{Link, LinkEntry},
+    validation::ValidationData,
+};
+use holochain_dna::Dna;
+use holochain_wasm_utils::api_serialization::validation::{LinkDirection, LinkValidationArgs};
+use nucleus::{
+    ribosome::callback::{get_dna, get_wasm, run_callback, CallbackResult},
+    validation_cache::{note_read, DhtRead},
+    ZomeFnCall,
+};
+use std::sync::Arc;
+
+/// Validates a link entry.
+/// The link has to be declared in the DNA, either in the `links_to` list of the base's
+/// entry type or in the `linked_from` list of the target's entry type.
+/// For every matching declaration the `__hdk_validate_link` callback of the declaring
+/// zome gets called and has to accept the link. The HDK rejects links for which the zome
+/// didn't register a validator.
+pub fn validate_link_entry(
+    entry: Entry,
+    validation_data: ValidationData,
+    context: Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    let link_entry = LinkEntry::from_entry(&entry);
+    let link = link_entry.link().clone();
+    let dna = get_dna(&context).expect("Callback called without DNA set!");
+
+    let base_type = match entry_type_name(&context, link.base())? {
+        Some(base_type) => base_type,
+        None => {
+            return Ok(CallbackResult::Fail(String::from(
+                "Base for link not found",
+            )))
+        }
+    };
+    let target_type = match entry_type_name(&context, link.target())? {
+        Some(target_type) => target_type,
+        None => {
+            return Ok(CallbackResult::Fail(String::from(
+                "Target for link not found",
+            )))
+        }
+    };
+
+    let declarations = link_declarations(&dna, &link, &base_type, &target_type);
+    if declarations.is_empty() {
+        return Ok(CallbackResult::Fail(format!(
+            "Link with tag '{}' from '{}' to '{}' is not declared in the DNA",
+            link.tag(),
+            base_type,
+            target_type,
+        )));
+    }
+
+    for (entry_type, direction, other_entry_type) in declarations {
+        let zome_name = match dna.get_zome_name_for_entry_type(&entry_type) {
+            Some(zome_name) => zome_name,
+            None => continue,
+        };
+        let wasm = match get_wasm(&context, &zome_name) {
+            Some(wasm) => wasm,
+            None => continue,
+        };
+        let validation_call = ZomeFnCall::new(
+            &zome_name,
+            "no capability, since this is a link validation call",
+            "__hdk_validate_link",
+            LinkValidationArgs {
+                entry_type,
+                direction,
+                other_entry_type,
+                link: link.clone(),
+                validation_data: validation_data.clone(),
+            },
+        );
+        // Only zomes that don't export `__hdk_validate_link` at all accept every declared link.
+        // HDK zomes export it and fail links they have no validator registered for.
+        if let CallbackResult::Fail(error_string) =
+            run_callback(context.clone(), validation_call, &wasm, dna.name.clone())
+        {
+            return Ok(CallbackResult::Fail(error_string));
+        }
+    }
+
+    Ok(CallbackResult::Pass)
+}
+
+/// Returns the name of the entry type of the entry stored under the given address
+fn entry_type_name(
+    context: &Arc<Context>,
+    address: &Address,
+) -> Result<Option<String>, HolochainError> {
+    note_read(DhtRead::Entry(address.clone()));
+    let maybe_entry: Option<Entry> = context
+        .state()
+        .expect("Callback called without application state!")
+        .dht()
+        .content_storage()
+        .fetch(address)?;
+    Ok(maybe_entry.map(|entry| entry.entry_type().to_string()))
+}
+
+/// Returns all DNA declarations that allow the given link as
+/// (declaring entry type, direction, other entry type)
+fn link_declarations(
+    dna: &Dna,
+    link: &Link,
+    base_type: &str,
+    target_type: &str,
+) -> Vec<(String, LinkDirection, String)> {
+    let mut declarations = Vec::new();
+
+    if let Some(base_def) = dna.get_entry_type_def(base_type) {
+        if base_def
+            .links_to
+            .iter()
+            .any(|links_to| links_to.target_type == target_type && &links_to.tag == link.tag())
+        {
+            declarations.push((
+                base_type.to_string(),
+                LinkDirection::To,
+                target_type.to_string(),
+            ));
+        }
+    }
+
+    if let Some(target_def) = dna.get_entry_type_def(target_type) {
+        if target_def
+            .linked_from
+            .iter()
+            .any(|linked_from| linked_from.base_type == base_type && &linked_from.tag == link.tag())
+        {
+            declarations.push((
+                target_type.to_string(),
+                LinkDirection::From,
+                base_type.to_string(),
+            ));
+        }
+    }
+
+    declarations
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        entry::test_entry,
+        links_entry::LinkActionKind,
+        validation::{EntryAction, EntryLifecycle, ValidationPackage},
+    };
+    use holochain_dna::zome::entry_types::{LinkedFrom, LinksTo};
+    use instance::{tests::test_instance_and_context, Instance};
+    use nucleus::actions::tests::commit;
+
+    fn test_validation_data() -> ValidationData {
+        ValidationData {
+            package: ValidationPackage::only_header(
+                ::holochain_core_types::chain_header::test_chain_header(),
+            ),
+            sources: Vec::new(),
+            lifecycle: EntryLifecycle::Chain,
+            action: EntryAction::Commit,
+            bundle: None,
+            old_entry: None,
+            old_entry_header: None,
+            membrane_proof: None,
+            link_action: None,
+        }
+    }
+
+    fn test_link_entry(tag: &str) -> Entry {
+        let entry = test_entry();
+        LinkEntry::new(LinkActionKind::ADD, &entry.address(), &entry.address(), tag).to_entry()
+    }
+
+    fn context_with_link_declarations(
+        links_to: Vec<LinksTo>,
+        linked_from: Vec<LinkedFrom>,
+    ) -> (Instance, Arc<Context>) {
+        let mut dna = ::test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        {
+            let entry_type_def = dna
+                .zomes
+                .get_mut("test_zome")
+                .unwrap()
+                .entry_types
+                .get_mut("testEntryType")
+                .unwrap();
+            entry_type_def.links_to = links_to;
+            entry_type_def.linked_from = linked_from;
+        }
+        let (instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        commit(test_entry(), &context);
+        (instance, context)
+    }
+
+    #[test]
+    fn fails_for_undeclared_link() {
+        let (_instance, context) = context_with_link_declarations(Vec::new(), Vec::new());
+
+        let result =
+            validate_link_entry(test_link_entry("test-tag"), test_validation_data(), context);
+
+        assert_eq!(
+            result,
+            Ok(CallbackResult::Fail(String::from(
+                "Link with tag 'test-tag' from 'testEntryType' to 'testEntryType' is not declared in the DNA"
+            ))),
+        );
+    }
+
+    #[test]
+    fn passes_for_declared_links() {
+        let links_to = LinksTo {
+            target_type: String::from("testEntryType"),
+            tag: String::from("links-to-tag"),
+        };
+        let linked_from = LinkedFrom {
+            base_type: String::from("testEntryType"),
+            tag: String::from("linked-from-tag"),
+        };
+        let (_instance, context) =
+            context_with_link_declarations(vec![links_to], vec![linked_from]);
+
+        for tag in &["links-to-tag", "linked-from-tag"] {
+            let result = validate_link_entry(
+                test_link_entry(tag),
+                test_validation_data(),
+                context.clone(),
+            );
+            assert_eq!(result, Ok(CallbackResult::Pass));
+        }
+
+        let result = validate_link_entry(
+            test_link_entry("other-tag"),
+            test_validation_data(),
+            context,
+        );
+        assert!(match result {
+            Ok(CallbackResult::Fail(_)) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn fails_for_missing_base() {
+        let (_instance, context) = context_with_link_declarations(Vec::new(), Vec::new());
+        let entry = test_entry();
+        let link_entry = LinkEntry::new(
+            LinkActionKind::ADD,
+            &Address::from("not a committed entry"),
+            &entry.address(),
+            "test-tag",
+        ).to_entry();
+
+        let result = validate_link_entry(link_entry, test_validation_data(), context);
+
+        assert_eq!(
+            result,
+            Ok(CallbackResult::Fail(String::from(
+                "Base for link not found"
+            ))),
+        );
+    }
+}
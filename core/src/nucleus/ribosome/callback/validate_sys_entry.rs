@@ -0,0 +1,397 @@
+use context::Context;
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
+    delete_entry::DeletionEntry,
+    entry::Entry,
+    entry_type::EntryType,
+    error::HolochainError,
+    validation::ValidationData,
+};
+use holochain_wasm_utils::api_serialization::validation::AgentIdValidationArgs;
+use nucleus::{
+    ribosome::callback::{get_dna, get_wasm, run_callback, CallbackResult},
+    ZomeFnCall,
+};
+use std::{convert::TryFrom, sync::Arc};
+
+/// Validates a system entry with the rules built into core, since no zome defines them:
+/// * every system entry has to sit on the source chain with consistent header links,
+///   and only the DNA can start a chain,
+/// * the DNA can only be the first entry of a chain,
+/// * the agent id has to follow right after the DNA,
+///   and every zome with a `__hdk_validate_agent_id` callback has to admit the agent,
+/// * a membrane proof has to follow right after the agent id,
+/// * a chain header entry has to link to headers that exist,
+/// * a deletion has to remove an entry that exists.
+pub fn validate_sys_entry(
+    entry: Entry,
+    entry_type: EntryType,
+    validation_data: ValidationData,
+    context: Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    let header = match validation_data.package.chain_header {
+        Some(ref header) => header.clone(),
+        None => {
+            return Ok(CallbackResult::Fail(format!(
+                "System entry of type '{}' can not be validated without its chain header",
+                entry_type
+            )))
+        }
+    };
+    if header.entry_address() != &entry.address() || header.entry_type() != &entry_type {
+        return Ok(CallbackResult::Fail(String::from(
+            "Chain header does not belong to the entry",
+        )));
+    }
+    if let CallbackResult::Fail(error) = check_header_links(&header, &context)? {
+        return Ok(CallbackResult::Fail(error));
+    }
+
+    match entry_type {
+        EntryType::Dna => Ok(match header.link() {
+            None => CallbackResult::Pass,
+            Some(_) => CallbackResult::Fail(String::from(
+                "The DNA can only be the first entry of a source chain",
+            )),
+        }),
+        EntryType::AgentId => validate_agent_id(&entry, &header, validation_data, context),
+        EntryType::MembraneProof => Ok(if follows(&header, &EntryType::AgentId, &context)? {
+            CallbackResult::Pass
+        } else {
+            CallbackResult::Fail(String::from(
+                "The membrane proof has to follow right after the agent id on the source chain",
+            ))
+        }),
+        EntryType::ChainHeader => match ChainHeader::try_from(entry.value().to_owned()) {
+            Ok(chain_header) => check_header_links(&chain_header, &context),
+            Err(_) => Ok(CallbackResult::Fail(String::from(
+                "Chain header entry is not a chain header",
+            ))),
+        },
+        EntryType::Deletion => validate_deletion(&entry, &context),
+        // Keys have no rules besides sitting on the chain correctly
+        EntryType::Key => Ok(CallbackResult::Pass),
+        _ => Ok(CallbackResult::NotImplemented),
+    }
+}
+
+/// Checks that the header links to headers that exist, and that
+/// `link_same_type` points to a header of the same entry type.
+/// A header without a previous header has to be the one of the DNA.
+fn check_header_links(
+    header: &ChainHeader,
+    context: &Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    match header.link() {
+        None => {
+            if header.entry_type() != &EntryType::Dna {
+                return Ok(CallbackResult::Fail(String::from(
+                    "Only the DNA can start a source chain",
+                )));
+            }
+        }
+        Some(address) => {
+            if fetch_header(context, &address)?.is_none() {
+                return Ok(CallbackResult::Fail(format!(
+                    "Chain header links to unknown header '{}'",
+                    address
+                )));
+            }
+        }
+    }
+
+    if let Some(address) = header.link_same_type() {
+        match fetch_header(context, &address)? {
+            None => {
+                return Ok(CallbackResult::Fail(format!(
+                    "Chain header links to unknown header '{}' of the same type",
+                    address
+                )))
+            }
+            Some(same_type) => {
+                if same_type.entry_type() != header.entry_type() {
+                    return Ok(CallbackResult::Fail(format!(
+                        "Chain header of type '{}' links to a header of type '{}' as the same type",
+                        header.entry_type(),
+                        same_type.entry_type()
+                    )));
+                }
+            }
+        }
+    }
+
+    Ok(CallbackResult::Pass)
+}
+
+fn validate_agent_id(
+    entry: &Entry,
+    header: &ChainHeader,
+    validation_data: ValidationData,
+    context: Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    if !follows(header, &EntryType::Dna, &context)? {
+        return Ok(CallbackResult::Fail(String::from(
+            "The agent id has to follow right after the DNA on the source chain",
+        )));
+    }
+
+    // Zomes can add their own rules for admitting agents
+    let dna = get_dna(&context).expect("Callback called without DNA set!");
+    for zome_name in dna.zomes.keys() {
+        let wasm = match get_wasm(&context, zome_name) {
+            Some(wasm) => wasm,
+            None => continue,
+        };
+        let validation_call = ZomeFnCall::new(
+            zome_name,
+            "no capability, since this is an agent validation call",
+            "__hdk_validate_agent_id",
+            AgentIdValidationArgs {
+                agent_id: String::from(entry.value().to_owned()),
+                validation_data: validation_data.clone(),
+            },
+        );
+        // Zomes without the callback admit every agent
+        if let CallbackResult::Fail(error_string) =
+            run_callback(context.clone(), validation_call, &wasm, dna.name.clone())
+        {
+            return Ok(CallbackResult::Fail(error_string));
+        }
+    }
+
+    Ok(CallbackResult::Pass)
+}
+
+fn validate_deletion(
+    entry: &Entry,
+    context: &Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    let deletion = match DeletionEntry::try_from(entry.value().to_owned()) {
+        Ok(deletion) => deletion,
+        Err(_) => {
+            return Ok(CallbackResult::Fail(String::from(
+                "Deletion entry does not name the entry it deletes",
+            )))
+        }
+    };
+    let maybe_deleted: Option<Content> = context
+        .state()
+        .expect("Callback called without application state!")
+        .dht()
+        .content_storage()
+        .fetch(deletion.deleted_entry_address())?;
+    Ok(match maybe_deleted {
+        Some(_) => CallbackResult::Pass,
+        None => CallbackResult::Fail(format!(
+            "Entry to delete '{}' does not exist",
+            deletion.deleted_entry_address()
+        )),
+    })
+}
+
+/// True if the header comes right after a header of the given entry type
+fn follows(
+    header: &ChainHeader,
+    entry_type: &EntryType,
+    context: &Arc<Context>,
+) -> Result<bool, HolochainError> {
+    Ok(match header.link() {
+        Some(address) => fetch_header(context, &address)?
+            .map(|previous| previous.entry_type() == entry_type)
+            .unwrap_or(false),
+        None => false,
+    })
+}
+
+/// The chain header stored under the given address, None if there is none
+fn fetch_header(
+    context: &Arc<Context>,
+    address: &Address,
+) -> Result<Option<ChainHeader>, HolochainError> {
+    let maybe_content: Option<Content> = context
+        .state()
+        .expect("Callback called without application state!")
+        .agent()
+        .chain()
+        .content_storage()
+        .fetch(address)?;
+    Ok(maybe_content.and_then(|content| ChainHeader::try_from(content).ok()))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use agent::state::create_new_chain_header;
+    use holochain_core_types::{
+        chain_header::test_chain_header,
+        entry::{test_entry, ToEntry},
+        json::JsonString,
+        signature::Signature,
+        time::Iso8601,
+        validation::{EntryAction, EntryLifecycle, ValidationPackage},
+    };
+    use instance::tests::test_instance_and_context;
+    use nucleus::actions::tests::commit;
+
+    fn validation_data(header: ChainHeader) -> ValidationData {
+        ValidationData {
+            package: ValidationPackage::only_header(header),
+            sources: Vec::new(),
+            lifecycle: EntryLifecycle::Chain,
+            action: EntryAction::Commit,
+            bundle: None,
+            old_entry: None,
+            old_entry_header: None,
+            membrane_proof: None,
+            link_action: None,
+        }
+    }
+
+    /// Validates the entry as if it was committed next on the chain of the context
+    fn validate_next(entry: Entry, context: &Arc<Context>) -> CallbackResult {
+        let header = create_new_chain_header(&entry, &*context.state().unwrap().agent());
+        validate_sys_entry(
+            entry.clone(),
+            entry.entry_type().clone(),
+            validation_data(header),
+            context.clone(),
+        ).unwrap()
+    }
+
+    fn test_context() -> Arc<Context> {
+        let dna = ::test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (_, context) = test_instance_and_context(dna).expect("Could not create test instance");
+        context
+    }
+
+    #[test]
+    fn accepts_genesis_entries_of_a_new_chain() {
+        // test instances only initialize if the DNA and the agent id pass validation
+        let context = test_context();
+        let chain = context.state().unwrap().agent().chain();
+        let top_header = context.state().unwrap().agent().top_chain_header();
+        let entry_types: Vec<EntryType> = chain
+            .iter(&top_header)
+            .map(|header| header.entry_type().clone())
+            .collect();
+        assert_eq!(vec![EntryType::AgentId, EntryType::Dna], entry_types);
+    }
+
+    #[test]
+    fn rejects_dna_after_first_position() {
+        let context = test_context();
+        let dna = context.state().unwrap().nucleus().dna().unwrap();
+
+        assert_eq!(
+            CallbackResult::Fail(String::from(
+                "The DNA can only be the first entry of a source chain"
+            )),
+            validate_next(dna.to_entry(), &context)
+        );
+    }
+
+    #[test]
+    fn rejects_agent_id_not_following_dna() {
+        let context = test_context();
+
+        assert_eq!(
+            CallbackResult::Fail(String::from(
+                "The agent id has to follow right after the DNA on the source chain"
+            )),
+            validate_next(context.agent.to_entry(), &context)
+        );
+    }
+
+    #[test]
+    fn accepts_membrane_proof_only_after_agent_id() {
+        let context = test_context();
+        let proof = Entry::new(EntryType::MembraneProof, JsonString::from("invite"));
+        assert_eq!(CallbackResult::Pass, validate_next(proof.clone(), &context));
+
+        commit(test_entry(), &context);
+        assert_eq!(
+            CallbackResult::Fail(String::from(
+                "The membrane proof has to follow right after the agent id on the source chain"
+            )),
+            validate_next(proof, &context)
+        );
+    }
+
+    #[test]
+    fn rejects_inconsistent_header_links() {
+        let context = test_context();
+        let entry = test_chain_header().to_entry();
+        let unknown = Address::from("unknown header");
+
+        // a chain header entry has to link to headers that exist
+        let dangling = ChainHeader::new(
+            test_chain_header().entry_type(),
+            test_chain_header().entry_address(),
+            &Signature::from(""),
+            &Some(unknown.clone()),
+            &None,
+            &Iso8601::from(""),
+        );
+        assert_eq!(
+            CallbackResult::Fail(String::from(
+                "Chain header links to unknown header 'unknown header'"
+            )),
+            validate_next(dangling.to_entry(), &context)
+        );
+
+        // only the DNA can start a chain
+        let first_header = ChainHeader::new(
+            entry.entry_type(),
+            &entry.address(),
+            &Signature::from(""),
+            &None,
+            &None,
+            &Iso8601::from(""),
+        );
+        assert_eq!(
+            Ok(CallbackResult::Fail(String::from(
+                "Only the DNA can start a source chain"
+            ))),
+            validate_sys_entry(
+                entry.clone(),
+                EntryType::ChainHeader,
+                validation_data(first_header),
+                context.clone(),
+            )
+        );
+
+        // the header has to be the one of the entry
+        assert_eq!(
+            Ok(CallbackResult::Fail(String::from(
+                "Chain header does not belong to the entry"
+            ))),
+            validate_sys_entry(
+                entry,
+                EntryType::ChainHeader,
+                validation_data(test_chain_header()),
+                context.clone(),
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_deletion_of_missing_entry() {
+        let context = test_context();
+        let deletion = DeletionEntry::new(test_entry().address()).to_entry();
+
+        assert_eq!(
+            CallbackResult::Fail(format!(
+                "Entry to delete '{}' does not exist",
+                test_entry().address()
+            )),
+            validate_next(deletion.clone(), &context)
+        );
+
+        commit(test_entry(), &context);
+        assert_eq!(CallbackResult::Pass, validate_next(deletion, &context));
+    }
+}
@@ -1,37 +1,38 @@
 use holochain_core_types::error::RibosomeErrorCode;
-use holochain_wasm_utils::memory_allocation::{SinglePageAllocation, SinglePageStack, U16_MAX};
+use holochain_wasm_utils::memory_allocation::{WasmAllocation, WasmStack, U32_MAX, WASM_PAGE_SIZE};
 
-use wasmi::{MemoryRef, ModuleRef};
+use wasmi::{memory_units::Pages, MemoryRef, ModuleRef};
 
 //--------------------------------------------------------------------------------------------------
 // WASM Memory Manager
 //--------------------------------------------------------------------------------------------------
 
 #[derive(Clone, Debug)]
-/// Struct for managing a WASM Memory Instance as a single page memory stack
-pub struct SinglePageManager {
-    stack: SinglePageStack,
+/// Struct for managing a WASM Memory Instance as a memory stack spanning multiple pages
+pub struct WasmPageManager {
+    stack: WasmStack,
     wasm_memory: MemoryRef,
 }
 
-/// A Memory Manager limited to one wasm memory page that works like a stack.
+/// A Memory Manager that works like a stack and grows the WASM memory by as many pages as needed
+/// whenever an allocation does not fit in the pages the stack already has.
+/// The stack lives in pages added after the module's own memory, so the module's data,
+/// shadow stack and heap never get overwritten.
 /// With this Memory Manager, the WASM host (i.e. the Ribosome) and WASM module (i.e. the Zome)
-/// only need to pass around an i32 to communicate any data.
-/// That i32 is the last memory allocation on the stack:
-/// it is split in an i16 'offset' in the upper bits and an i16 'length' in the lower bits.
-/// This fits with the 64KiB sized of a memory Page.
+/// only need to pass around an i64 to communicate any data.
+/// That i64 is the last memory allocation on the stack:
+/// it is split in an i32 'offset' in the upper bits and an i32 'length' in the lower bits.
+/// This covers the whole 4GiB address space of WASM linear memory.
 /// Complex input arguments should be stored on the latest allocation on the stack.
 /// Complex output arguments can be stored anywhere on stack.
 /// Since zero sized allocations are not allowed,
 /// it is possible to pass around a return and/or error code with the following convention:
-/// using the i16 'offset' as return code and i16 'length' set to zero
+/// using the i32 'offset' as return code and i32 'length' set to zero
 /// to indicate its a return code.
 /// Return code of 0 means success, while any other value means a failure and gives the error code.
-/// In the future, to handle bigger memory needs, we could do same with an i64 instead
-/// and handle multiple memory Pages.
 #[allow(unknown_lints)]
 #[allow(cast_lossless)]
-impl SinglePageManager {
+impl WasmPageManager {
     pub fn new(wasm_instance: &ModuleRef) -> Self {
         // get wasm memory reference from module
         let wasm_memory = wasm_instance
@@ -41,34 +42,41 @@ impl SinglePageManager {
             .expect("in module generated by rustc export named 'memory' should be a memory; qed")
             .clone();
 
-        return SinglePageManager {
-            stack: SinglePageStack::default(),
+        return WasmPageManager {
+            stack: WasmStack::default(),
             wasm_memory: wasm_memory.clone(),
         };
     }
 
-    /// Allocate on stack without writing in it
-    pub fn allocate(&mut self, length: u16) -> Result<SinglePageAllocation, RibosomeErrorCode> {
-        if self.stack.top() as u32 + length as u32 > U16_MAX {
-            return Err(RibosomeErrorCode::OutOfMemory);
-        }
-        let offset = self.stack.allocate(length);
-        SinglePageAllocation::new(offset, length)
+    /// Allocate on stack without writing in it.
+    /// Grows the WASM memory if the allocation does not fit in the pages of the stack.
+    pub fn allocate(&mut self, length: u32) -> Result<WasmAllocation, RibosomeErrorCode> {
+        let memory_size = self.wasm_memory.current_size().0 as u64 * WASM_PAGE_SIZE;
+        let wasm_memory = &self.wasm_memory;
+        let offset = self
+            .stack
+            .allocate_in_memory(length, memory_size, |pages| {
+                wasm_memory
+                    .grow(Pages(pages as usize))
+                    .map(|_| ())
+                    .map_err(|_| RibosomeErrorCode::OutOfMemory)
+            })?;
+        WasmAllocation::new(offset, length)
     }
 
     /// Write data on top of stack
-    pub fn write(&mut self, data: &[u8]) -> Result<SinglePageAllocation, RibosomeErrorCode> {
+    pub fn write(&mut self, data: &[u8]) -> Result<WasmAllocation, RibosomeErrorCode> {
         let data_len = data.len();
-        if data_len > <u16>::max_value() as usize {
+        if data_len as u64 > U32_MAX {
             return Err(RibosomeErrorCode::OutOfMemory);
         }
         if data_len == 0 {
             return Err(RibosomeErrorCode::ZeroSizedAllocation);
         }
         // scope for mutable borrow of self
-        let mem_buf: SinglePageAllocation;
+        let mem_buf: WasmAllocation;
         {
-            let res = self.allocate(data_len as u16);
+            let res = self.allocate(data_len as u32);
             if let Err(err_code) = res {
                 return Err(err_code);
             }
@@ -76,16 +84,80 @@ impl SinglePageManager {
         }
 
         self.wasm_memory
-            .set(mem_buf.offset() as u32, &data)
+            .set(mem_buf.offset(), &data)
             .expect("memory should be writable");
         Ok(mem_buf)
     }
 
     /// Read data somewhere in stack
-    pub fn read(&self, allocation: SinglePageAllocation) -> Vec<u8> {
-        return self
-            .wasm_memory
-            .get(allocation.offset() as u32, allocation.length() as usize)
-            .expect("Successfully retrieve the result");
+    /// Fails if the allocation does not fit in the WASM memory.
+    pub fn read(&self, allocation: WasmAllocation) -> Result<Vec<u8>, RibosomeErrorCode> {
+        self.wasm_memory
+            .get(allocation.offset(), allocation.length() as usize)
+            .map_err(|_| RibosomeErrorCode::NotAnAllocation)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate wabt;
+    use self::wabt::Wat2Wasm;
+    use super::*;
+    use wasmi::{self, ImportsBuilder, ModuleInstance};
+
+    /// instantiates a module that only exports a single page of memory
+    fn test_wasm_instance() -> ModuleRef {
+        let wasm = Wat2Wasm::new()
+            .convert(r#"(module (memory 1) (export "memory" (memory 0)))"#)
+            .expect("string literal should be valid WAT");
+        let module = wasmi::Module::from_buffer(wasm.as_ref()).expect("wasm should be valid");
+        ModuleInstance::new(&module, &ImportsBuilder::default())
+            .expect("module should instantiate")
+            .assert_no_start()
+    }
+
+    #[test]
+    fn can_write_and_read_across_pages() {
+        let instance = test_wasm_instance();
+        let mut memory_manager = WasmPageManager::new(&instance);
+
+        let small = vec![1; 10];
+        let large = vec![2; 100_000];
+        let small_allocation = memory_manager.write(&small).unwrap();
+        let large_allocation = memory_manager.write(&large).unwrap();
+
+        // the stack starts after the page of the module
+        assert_eq!(65_536, small_allocation.offset());
+        assert_eq!(65_546, large_allocation.offset());
+        assert_eq!(100_000, large_allocation.length());
+        assert_eq!(Pages(3), memory_manager.wasm_memory.current_size());
+        assert_eq!(Ok(small), memory_manager.read(small_allocation));
+        assert_eq!(Ok(large), memory_manager.read(large_allocation));
+    }
+
+    #[test]
+    fn errors_on_out_of_bounds_read() {
+        let instance = test_wasm_instance();
+        let memory_manager = WasmPageManager::new(&instance);
+
+        // the module only has a single page of memory
+        let allocation = WasmAllocation::new(65_000, 1_000).unwrap();
+        assert_eq!(
+            Err(RibosomeErrorCode::NotAnAllocation),
+            memory_manager.read(allocation),
+        );
+    }
+
+    #[test]
+    fn errors_on_zero_sized_write() {
+        let instance = test_wasm_instance();
+        let mut memory_manager = WasmPageManager::new(&instance);
+
+        assert_eq!(
+            Err(RibosomeErrorCode::ZeroSizedAllocation),
+            memory_manager
+                .write(&[])
+                .map(|allocation| allocation.encode()),
+        );
     }
 }
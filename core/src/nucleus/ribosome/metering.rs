@@ -0,0 +1,141 @@
+use holochain_core_types::error::HolochainError;
+use holochain_dna::zome::Config;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Tracks the fuel burned and the time spent by a single zome call
+/// against the execution limits set in the config of its zome.
+/// Every module gets a gas counter injected when it is compiled,
+/// which charges the meter with the cost of each block of instructions before running it.
+#[derive(Clone, Debug)]
+pub struct ExecutionMeter {
+    fuel_limit: Option<u64>,
+    fuel_used: u64,
+    timeout_ms: Option<u64>,
+    deadline: Option<Instant>,
+    cancelled: Option<Arc<AtomicBool>>,
+}
+
+impl ExecutionMeter {
+    /// Starts metering a call with the limits of the given zome config.
+    /// The timeout clock starts running right away.
+    pub fn new(config: &Config) -> Self {
+        ExecutionMeter {
+            fuel_limit: config.fuel_limit,
+            fuel_used: 0,
+            timeout_ms: config.timeout_ms,
+            deadline: config
+                .timeout_ms
+                .map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms)),
+            cancelled: None,
+        }
+    }
+
+    /// Aborts the call as soon as the given flag gets set,
+    /// e.g. by Context::cancel_zome_calls when the instance shuts down.
+    pub fn cancelled_by(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /// Burn the given amount of fuel.
+    /// Fails once the call has used up its fuel, ran past its deadline or got cancelled.
+    pub fn charge(&mut self, fuel: u64) -> Result<(), HolochainError> {
+        if let Some(ref cancelled) = self.cancelled {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(HolochainError::ExecutionLimitExceeded(
+                    "zome call cancelled".to_string(),
+                ));
+            }
+        }
+        self.fuel_used = self.fuel_used.saturating_add(fuel);
+        if let Some(fuel_limit) = self.fuel_limit {
+            if self.fuel_used > fuel_limit {
+                return Err(HolochainError::ExecutionLimitExceeded(format!(
+                    "zome call ran out of fuel (limit: {})",
+                    fuel_limit
+                )));
+            }
+        }
+        if let (Some(deadline), Some(timeout_ms)) = (self.deadline, self.timeout_ms) {
+            if Instant::now() > deadline {
+                return Err(HolochainError::ExecutionLimitExceeded(format!(
+                    "zome call timed out (limit: {}ms)",
+                    timeout_ms
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Amount of fuel burned so far
+    pub fn fuel_used(&self) -> u64 {
+        self.fuel_used
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn unlimited_by_default() {
+        let mut meter = ExecutionMeter::new(&Config::default());
+        assert_eq!(Ok(()), meter.charge(u64::max_value()));
+        assert_eq!(Ok(()), meter.charge(1));
+        assert_eq!(u64::max_value(), meter.fuel_used());
+    }
+
+    #[test]
+    fn runs_out_of_fuel() {
+        let mut config = Config::default();
+        config.fuel_limit = Some(10);
+        let mut meter = ExecutionMeter::new(&config);
+
+        assert_eq!(Ok(()), meter.charge(4));
+        assert_eq!(Ok(()), meter.charge(6));
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call ran out of fuel (limit: 10)".to_string()
+            )),
+            meter.charge(1),
+        );
+    }
+
+    #[test]
+    fn times_out() {
+        let mut config = Config::default();
+        config.timeout_ms = Some(10);
+        let mut meter = ExecutionMeter::new(&config);
+
+        assert_eq!(Ok(()), meter.charge(1));
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call timed out (limit: 10ms)".to_string()
+            )),
+            meter.charge(1),
+        );
+    }
+
+    #[test]
+    fn can_be_cancelled() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut meter = ExecutionMeter::new(&Config::default()).cancelled_by(cancelled.clone());
+
+        assert_eq!(Ok(()), meter.charge(1));
+        cancelled.store(true, Ordering::SeqCst);
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call cancelled".to_string()
+            )),
+            meter.charge(1),
+        );
+    }
+}
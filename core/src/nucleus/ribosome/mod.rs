@@ -3,6 +3,8 @@
 pub mod api;
 pub mod callback;
 pub mod memory;
+pub mod metering;
+pub mod module_cache;
 mod run_dna;
 mod runtime;
 
@@ -0,0 +1,94 @@
+use holochain_core_types::{cas::content::Address, hash::HashString};
+use multihash::Hash as Multihash;
+use parity_wasm;
+use pwasm_utils::{self, rules};
+use std::{collections::HashMap, sync::Arc};
+use wasmi::{self, Module};
+
+/// Cache of parsed and validated WASM modules, keyed by the hash of their code.
+/// Parsing and validating a zome's WASM is the most expensive part of running it,
+/// so every zome call and callback of an instance shares the modules compiled here.
+/// Only modules are cached, not module instances: an instance keeps the memory
+/// and globals of the call that used it, so each call still gets a fresh one.
+/// Every module gets a gas counter injected that calls the "gas" host function before
+/// each block of instructions, which is how zome calls are metered.
+#[derive(Default)]
+pub struct ModuleCache {
+    modules: HashMap<Address, Arc<Module>>,
+}
+
+impl ModuleCache {
+    pub fn new() -> Self {
+        ModuleCache::default()
+    }
+
+    /// Returns the module compiled from the given WASM code.
+    /// The code only gets parsed, instrumented and validated the first time it is seen.
+    pub fn get_or_compile(&mut self, wasm: &[u8]) -> Result<Arc<Module>, wasmi::Error> {
+        let code_hash = HashString::encode_from_bytes(wasm, Multihash::SHA2256);
+        if let Some(module) = self.modules.get(&code_hash) {
+            return Ok(module.clone());
+        }
+        let module = Arc::new(compile_metered(wasm)?);
+        self.modules.insert(code_hash, module.clone());
+        Ok(module)
+    }
+
+    /// Number of compiled modules in the cache
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+/// Parse the WASM code and inject a gas counter costing one unit of fuel per instruction
+fn compile_metered(wasm: &[u8]) -> Result<Module, wasmi::Error> {
+    let parsed = parity_wasm::deserialize_buffer(wasm)
+        .map_err(|err| wasmi::Error::Validation(err.to_string()))?;
+    let metered = pwasm_utils::inject_gas_counter(parsed, &rules::Set::default())
+        .map_err(|_| wasmi::Error::Validation("could not inject gas counter".to_string()))?;
+    Module::from_parity_wasm_module(metered)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use wasmi::{ImportsBuilder, ModuleInstance};
+
+    fn test_wasm(result: i64) -> Vec<u8> {
+        ::nucleus::ribosome::callback::tests::test_callback_wasm("genesis", result)
+    }
+
+    #[test]
+    fn compiles_each_code_once() {
+        let mut cache = ModuleCache::new();
+        assert!(cache.is_empty());
+
+        let first = cache.get_or_compile(&test_wasm(0)).unwrap();
+        let second = cache.get_or_compile(&test_wasm(0)).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(1, cache.len());
+
+        let other = cache.get_or_compile(&test_wasm(1)).unwrap();
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(2, cache.len());
+    }
+
+    #[test]
+    fn does_not_cache_invalid_code() {
+        let mut cache = ModuleCache::new();
+        assert!(cache.get_or_compile(&[1, 2, 3]).is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn injects_gas_counter() {
+        let mut cache = ModuleCache::new();
+        let module = cache.get_or_compile(&test_wasm(0)).unwrap();
+        // the test code has no imports, but the metered module needs the "gas" host function
+        assert!(ModuleInstance::new(&module, &ImportsBuilder::default()).is_err());
+    }
+}
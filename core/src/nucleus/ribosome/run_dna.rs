@@ -1,22 +1,28 @@
 use context::Context;
 use holochain_core_types::{
-    error::{HcResult, HolochainError, RibosomeErrorCode, RibosomeReturnCode},
+    error::{DnaError, HcResult, HolochainError, RibosomeErrorCode, RibosomeReturnCode},
     json::JsonString,
 };
+use holochain_dna::zome::Config;
 use holochain_wasm_utils::memory_allocation::decode_encoded_allocation;
 use nucleus::{
-    ribosome::{api::ZomeApiFunction, memory::SinglePageManager, Runtime},
+    ribosome::{
+        api::ZomeApiFunction, memory::WasmPageManager, metering::ExecutionMeter, HolochainTrap,
+        Runtime,
+    },
     ZomeFnCall, ZomeFnResult,
 };
 use std::{str::FromStr, sync::Arc};
 use wasmi::{
-    self, Error as InterpreterError, FuncInstance, FuncRef, ImportsBuilder, ModuleImportResolver,
+    Error as InterpreterError, FuncInstance, FuncRef, ImportsBuilder, ModuleImportResolver,
     ModuleInstance, NopExternals, RuntimeValue, Signature, ValueType,
 };
 
 /// Executes an exposed zome function in a wasm binary.
 /// Multithreaded function
-/// panics if wasm binary isn't valid.
+/// Returns a HolochainError::RibosomeFailed if the wasm binary isn't valid or traps.
+/// The call is aborted with HolochainError::ExecutionLimitExceeded if it burns more fuel
+/// or runs longer than the limits set in the config of its zome.
 pub fn run_dna(
     dna_name: &str,
     context: Arc<Context>,
@@ -24,8 +30,13 @@ pub fn run_dna(
     zome_call: &ZomeFnCall,
     parameters: Option<Vec<u8>>,
 ) -> ZomeFnResult {
-    // Create wasm module from wasm binary
-    let module = wasmi::Module::from_buffer(wasm).expect("wasm binary should be valid");
+    // Get the wasm module from the cache, the wasm binary is only parsed the first time
+    let module = context
+        .module_cache
+        .lock()
+        .expect("module cache should not be poisoned")
+        .get_or_compile(&wasm)
+        .map_err(|err| HolochainError::RibosomeFailed(format!("Invalid wasm binary: {}", err)))?;
 
     // invoke_index and resolve_func work together to enable callable host functions
     // within WASM modules, which is how the core API functions
@@ -65,9 +76,14 @@ pub fn run_dna(
                     ),
                     api_fn as usize,
                 )),
+                // Gas is called by the injected gas counter with the cost of the next block
+                ZomeApiFunction::Gas => Ok(FuncInstance::alloc_host(
+                    Signature::new(&[ValueType::I32][..], None),
+                    api_fn as usize,
+                )),
                 // All of our Zome API Functions have the same signature
                 _ => Ok(FuncInstance::alloc_host(
-                    Signature::new(&[ValueType::I32][..], Some(ValueType::I32)),
+                    Signature::new(&[ValueType::I64][..], Some(ValueType::I64)),
                     api_fn as usize,
                 )),
             }
@@ -80,24 +96,53 @@ pub fn run_dna(
 
     // Create module instance from wasm module, and start it if start is defined
     let wasm_instance = ModuleInstance::new(&module, &imports)
-        .expect("Failed to instantiate module")
+        .map_err(|err| {
+            HolochainError::RibosomeFailed(format!("Failed to instantiate module: {}", err))
+        })?
         .run_start(&mut NopExternals)
         .map_err(|_| HolochainError::RibosomeFailed("Module failed to start".to_string()))?;
 
+    // Don't bother running anything if the called function isn't there
+    if wasm_instance
+        .export_by_name(&zome_call.fn_name)
+        .and_then(|export| export.as_func().cloned())
+        .is_none()
+    {
+        return Err(HolochainError::Dna(DnaError::ZomeFunctionNotFound(
+            format!("Zome function '{}' not found", zome_call.fn_name),
+        )));
+    }
+
     // write input arguments for module call in memory Buffer
     let input_parameters: Vec<_> = parameters.unwrap_or_default();
 
+    // Execution limits of the called zome, no limits if the zome can't be found in the DNA
+    let zome_config = context
+        .state()
+        .and_then(|state| state.nucleus().dna())
+        .and_then(|dna| {
+            dna.zomes
+                .get(&zome_call.zome_name)
+                .map(|zome| zome.config.clone())
+        })
+        .unwrap_or_else(Config::new);
+
+    // the call gets aborted once it exceeds these limits or the instance shuts down
+    let meter = ExecutionMeter::new(&zome_config).cancelled_by(context.zome_calls_cancellation());
+
     // instantiate runtime struct for passing external state data over wasm but not to wasm
     let mut runtime = Runtime {
-        memory_manager: SinglePageManager::new(&wasm_instance),
+        memory_manager: WasmPageManager::new(&wasm_instance),
         context,
         zome_call: zome_call.clone(),
         dna_name: dna_name.to_string(),
+        meter,
+        bundle: None,
     };
 
     // Write input arguments in wasm memory
     // scope for mutable borrow of runtime
-    let encoded_allocation_of_input: u32;
+    let encoded_allocation_of_input: u64;
     {
         let mut_runtime = &mut runtime;
         let maybe_allocation_of_input = mut_runtime.memory_manager.write(&input_parameters);
@@ -114,7 +159,7 @@ pub fn run_dna(
     }
 
     // scope for mutable borrow of runtime
-    let returned_encoded_allocation: u32;
+    let returned_encoded_allocation: u64;
     {
         let mut_runtime = &mut runtime;
 
@@ -124,13 +169,26 @@ pub fn run_dna(
         returned_encoded_allocation = wasm_instance
             .invoke_export(
                 zome_call.fn_name.clone().as_str(),
-                &[RuntimeValue::I32(encoded_allocation_of_input as i32)],
+                &[RuntimeValue::I64(encoded_allocation_of_input as i64)],
                 mut_runtime,
             )
-            .map_err(|err| HolochainError::RibosomeFailed(err.to_string()))?
-            .unwrap()
-            .try_into()
-            .unwrap();
+            .map_err(|err| {
+                // Unwrap the error a zome API function trapped with
+                match err
+                    .as_host_error()
+                    .and_then(|host_err| host_err.downcast_ref::<HolochainTrap>())
+                {
+                    Some(HolochainTrap(hc_err)) => hc_err.clone(),
+                    None => HolochainError::RibosomeFailed(err.to_string()),
+                }
+            })?
+            .and_then(|value| value.try_into::<u64>())
+            .ok_or_else(|| {
+                HolochainError::RibosomeFailed(format!(
+                    "Zome function '{}' did not return an encoded allocation",
+                    zome_call.fn_name
+                ))
+            })?;
     }
 
     // Handle result returned by called zome function
@@ -150,7 +208,10 @@ pub fn run_dna(
         }
         // Something in memory, try to read and return it
         Ok(valid_allocation) => {
-            let result = runtime.memory_manager.read(valid_allocation);
+            let result = runtime
+                .memory_manager
+                .read(valid_allocation)
+                .map_err(|err_code| HolochainError::RibosomeFailed(err_code.to_string()))?;
             let maybe_zome_result = String::from_utf8(result);
             match maybe_zome_result {
                 Err(err) => {
@@ -174,3 +235,170 @@ pub fn run_dna(
         .expect("Logger should work");
     return return_result;
 }
+
+#[cfg(test)]
+pub mod tests {
+    extern crate wabt;
+
+    use self::wabt::Wat2Wasm;
+    use super::*;
+    use instance::tests::test_context;
+
+    /// calls the "test" function of the given wasm
+    fn run_test_wasm(wasm: Vec<u8>) -> ZomeFnResult {
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "test", "");
+        run_dna("TestApp", test_context("test"), wasm, &zome_call, None)
+    }
+
+    /// wasm with a "test" function that writes the given byte at offset 0 of its memory
+    /// and passes the given encoded allocation to hc_debug
+    fn test_debug_wasm(byte: u8, encoded_allocation: u64) -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(format!(
+                r#"
+(module
+    (import "env" "hc_debug"
+        (func $hc_debug
+            (param i64)
+            (result i64)
+        )
+    )
+
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (func
+        (export "test")
+        (param $allocation i64)
+        (result i64)
+
+        (i32.store8 (i32.const 0) (i32.const {}))
+        (call $hc_debug (i64.const {}))
+    )
+)
+                "#,
+                byte, encoded_allocation
+            ))
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec()
+    }
+
+    /// wasm with a "test" function that returns the given encoded allocation
+    fn test_return_wasm(encoded_allocation: u64) -> Vec<u8> {
+        Wat2Wasm::new()
+            .canonicalize_lebs(false)
+            .write_debug_names(true)
+            .convert(format!(
+                r#"
+(module
+    (memory 1)
+    (export "memory" (memory 0))
+
+    (func
+        (export "test")
+        (param $allocation i64)
+        (result i64)
+
+        (i64.const {})
+    )
+)
+                "#,
+                encoded_allocation
+            ))
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec()
+    }
+
+    #[test]
+    fn errors_on_corrupt_wasm() {
+        // valid wasm header followed by garbage
+        let result = run_test_wasm(vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0xff]);
+        match result {
+            Err(HolochainError::RibosomeFailed(msg)) => {
+                assert!(msg.starts_with("Invalid wasm binary"))
+            }
+            _ => panic!("expected invalid wasm binary error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn errors_on_unknown_import() {
+        let wasm = Wat2Wasm::new()
+            .convert(
+                r#"
+(module
+    (import "env" "hc_unknown" (func $hc_unknown (param i64) (result i64)))
+    (memory 1)
+    (export "memory" (memory 0))
+)
+                "#,
+            )
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec();
+        match run_test_wasm(wasm) {
+            Err(HolochainError::RibosomeFailed(msg)) => {
+                assert!(msg.starts_with("Failed to instantiate module"))
+            }
+            result => panic!("expected instantiation error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn errors_on_missing_function() {
+        let wasm = Wat2Wasm::new()
+            .convert(r#"(module (memory 1) (export "memory" (memory 0)))"#)
+            .expect("string literal should be valid WAT")
+            .as_ref()
+            .to_vec();
+        assert_eq!(
+            Err(HolochainError::Dna(DnaError::ZomeFunctionNotFound(
+                "Zome function 'test' not found".to_string()
+            ))),
+            run_test_wasm(wasm),
+        );
+    }
+
+    #[test]
+    fn errors_on_error_code_as_argument() {
+        // offset 1 with length 0 is a return code, not an allocation
+        assert_eq!(
+            Err(HolochainError::Ribosome(RibosomeErrorCode::NotAnAllocation)),
+            run_test_wasm(test_debug_wasm(0, 1 << 32)),
+        );
+    }
+
+    #[test]
+    fn errors_on_out_of_bounds_argument() {
+        // 256 bytes at the very end of the 4GiB address space, the module only has 1 page
+        assert_eq!(
+            Err(HolochainError::Ribosome(RibosomeErrorCode::NotAnAllocation)),
+            run_test_wasm(test_debug_wasm(0, (0xFFFF_FE00 << 32) + 0x100)),
+        );
+    }
+
+    #[test]
+    fn errors_on_invalid_utf8_argument() {
+        // a single 0xFF byte at offset 0
+        assert_eq!(
+            Err(HolochainError::Ribosome(
+                RibosomeErrorCode::ArgumentDeserializationFailed
+            )),
+            run_test_wasm(test_debug_wasm(0xFF, 1)),
+        );
+    }
+
+    #[test]
+    fn errors_on_out_of_bounds_result() {
+        assert_eq!(
+            Err(HolochainError::RibosomeFailed(
+                RibosomeErrorCode::NotAnAllocation.to_string()
+            )),
+            run_test_wasm(test_return_wasm((0xFFFF_FE00 << 32) + 0x100)),
+        );
+    }
+}
@@ -1,31 +1,36 @@
 use context::Context;
 use holochain_core_types::{
-    error::{HolochainError, RibosomeReturnCode, ZomeApiInternalResult},
+    error::{HolochainError, RibosomeErrorCode, RibosomeReturnCode, ZomeApiInternalResult},
     json::JsonString,
 };
 use holochain_wasm_utils::memory_allocation::decode_encoded_allocation;
 use nucleus::{
     ribosome::{
-        api::{ZomeApiFunction, ZomeApiResult},
-        memory::SinglePageManager,
+        api::{bundle::Bundle, ZomeApiFunction, ZomeApiResult},
+        memory::WasmPageManager,
+        metering::ExecutionMeter,
         Defn,
     },
     ZomeFnCall,
 };
-use std::sync::Arc;
-use wasmi::{Externals, RuntimeArgs, RuntimeValue};
+use std::{fmt, sync::Arc};
+use wasmi::{Externals, HostError, RuntimeArgs, RuntimeValue, Trap, TrapKind};
 
 /// Object holding data to pass around to invoked Zome API functions
 #[derive(Clone)]
 pub struct Runtime {
     /// Memory state tracker between ribosome and wasm.
-    pub memory_manager: SinglePageManager,
+    pub memory_manager: WasmPageManager,
     /// Context of Holochain. Required for operating.
     pub context: Arc<Context>,
     /// Name of the DNA that is being hosted.
     pub dna_name: String,
     /// The zome function call that initiated the Ribosome.
     pub zome_call: ZomeFnCall,
+    /// Fuel and time used by the call so far, against the limits of its zome.
+    pub meter: ExecutionMeter,
+    /// Commits staged since start_bundle, until the bundle gets closed.
+    pub bundle: Option<Bundle>,
 }
 
 impl Runtime {
@@ -33,29 +38,35 @@ impl Runtime {
     /// Input RuntimeArgs should only have one input which is the encoded allocation holding
     /// the complex data as an utf8 string.
     /// Returns the utf8 string.
-    pub fn load_json_string_from_args(&self, args: &RuntimeArgs) -> JsonString {
-        // @TODO don't panic in WASM
-        // @see https://github.com/holochain/holochain-rust/issues/159
-        assert_eq!(1, args.len());
+    /// Traps the zome call with a HolochainTrap if the input is not an allocation of utf8 data.
+    pub fn load_json_string_from_args(&self, args: &RuntimeArgs) -> Result<JsonString, Trap> {
+        if args.len() != 1 {
+            return Err(
+                HolochainTrap::from(RibosomeErrorCode::ArgumentDeserializationFailed).into(),
+            );
+        }
 
         // Read complex argument serialized in memory
-        let encoded_allocation: u32 = args.nth(0);
-        let maybe_allocation = decode_encoded_allocation(encoded_allocation);
-        let allocation = match maybe_allocation {
+        let encoded_allocation: u64 = args.nth_checked(0)?;
+        let allocation = match decode_encoded_allocation(encoded_allocation) {
             // Handle empty allocation edge case
-            Err(RibosomeReturnCode::Success) => return JsonString::null(),
+            Err(RibosomeReturnCode::Success) => return Ok(JsonString::null()),
             // Handle error code
-            Err(_) => panic!("received error code instead of valid encoded allocation"),
+            Err(_) => return Err(HolochainTrap::from(RibosomeErrorCode::NotAnAllocation).into()),
             // Handle normal allocation
             Ok(allocation) => allocation,
         };
-        let bin_arg = self.memory_manager.read(allocation);
+        let bin_arg = self
+            .memory_manager
+            .read(allocation)
+            .map_err(HolochainTrap::from)?;
 
         // convert complex argument
         String::from_utf8(bin_arg)
-            // @TODO don't panic in WASM
-            // @see https://github.com/holochain/holochain-rust/issues/159
-            .unwrap().into()
+            .map(JsonString::from)
+            .map_err(|_| {
+                HolochainTrap::from(RibosomeErrorCode::ArgumentDeserializationFailed).into()
+            })
     }
 
     /// Store anything that implements Into<JsonString> in wasm memory.
@@ -70,7 +81,7 @@ impl Runtime {
 
         match self.memory_manager.write(&s_bytes) {
             Err(_) => ribosome_error_code!(Unspecified),
-            Ok(allocation) => Ok(Some(RuntimeValue::I32(allocation.encode() as i32))),
+            Ok(allocation) => Ok(Some(RuntimeValue::I64(allocation.encode() as i64))),
         }
     }
 
@@ -91,9 +102,36 @@ impl Externals for Runtime {
     fn invoke_index(&mut self, index: usize, args: RuntimeArgs) -> ZomeApiResult {
         let zf = ZomeApiFunction::from_index(index);
         match zf {
-            ZomeApiFunction::MissingNo => panic!("unknown function index"),
+            ZomeApiFunction::MissingNo => Err(HolochainTrap(HolochainError::RibosomeFailed(
+                format!("unknown zome API function index {}", index),
+            )).into()),
             // convert the function to its callable form and call it with the given arguments
             _ => zf.as_fn()(self, &args),
         }
     }
 }
+
+/// Host error for trapping out of a zome call with a HolochainError,
+/// run_dna unwraps it back into the HolochainError it holds and returns that to the caller.
+#[derive(Debug)]
+pub struct HolochainTrap(pub HolochainError);
+
+impl fmt::Display for HolochainTrap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl HostError for HolochainTrap {}
+
+impl From<RibosomeErrorCode> for HolochainTrap {
+    fn from(error_code: RibosomeErrorCode) -> Self {
+        HolochainTrap(HolochainError::Ribosome(error_code))
+    }
+}
+
+impl From<HolochainTrap> for Trap {
+    fn from(holochain_trap: HolochainTrap) -> Self {
+        Trap::new(TrapKind::Host(Box::new(holochain_trap)))
+    }
+}
@@ -58,6 +58,14 @@ impl NucleusState {
             .and_then(|value| value.clone())
     }
 
+    /// Number of zome calls that have been started but have no result yet
+    pub fn running_zome_calls(&self) -> usize {
+        self.zome_calls
+            .values()
+            .filter(|result| result.is_none())
+            .count()
+    }
+
     pub fn has_initialized(&self) -> bool {
         self.status == NucleusStatus::Initialized
     }
@@ -0,0 +1,505 @@
+//! Persisted outcomes of entry validations.
+//! Every validation that ran the validation callback of a zome is recorded per entry address
+//! and DNA hash, together with the hash of everything else the validation depended on:
+//! the entry type and the validation data (package, action, old entry, bundle...).
+//! Validations may also read the DHT, like the base and target of a link or entries
+//! the validation callback gets. Those reads are recorded with what they found,
+//! and a record is only used again while the DHT still holds the same for all of them.
+//! As long as those dependencies don't change, an entry doesn't get validated again.
+//! Rejected entries are also added to a registry so nodes can skip them right away,
+//! unless their rejection depends on the DHT and could change with it.
+
+use context::Context;
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    eav::{EntityAttributeValue, EntityAttributeValueStorage},
+    entry::ToEntry,
+    entry_type::EntryType,
+    error::HolochainError,
+    json::JsonString,
+    validation::ValidationData,
+};
+use holochain_dna::Dna;
+use multihash::Hash;
+use nucleus::state::ValidationResult;
+use serde_json;
+use std::{cell::RefCell, convert::TryFrom, sync::Arc};
+
+/// EAV attribute linking the DNA hash to the addresses of entries it rejected
+const REJECTED_ATTRIBUTE: &str = "rejected";
+
+/// EAV attribute linking an entry address to its validation records for a DNA
+fn validation_attribute(dna_hash: &Address) -> String {
+    format!("validation/{}", dna_hash)
+}
+
+/// EAV attribute linking an address read from the DHT to the validation records
+/// of a DNA that depend on it
+fn dependency_attribute(dna_hash: &Address) -> String {
+    format!("validation_dependency/{}", dna_hash)
+}
+
+thread_local! {
+    /// DHT reads of the validation running on this thread, if one is running
+    static DHT_READS: RefCell<Option<Vec<DhtRead>>> = RefCell::new(None);
+}
+
+/// A read of the DHT that the outcome of a validation can depend on
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DhtRead {
+    /// the entry stored under the address, and its CRUD status
+    Entry(Address),
+    /// the links with the tag from the address
+    Links(Address, String),
+}
+
+impl DhtRead {
+    pub fn address(&self) -> &Address {
+        match self {
+            DhtRead::Entry(address) => address,
+            DhtRead::Links(address, _) => address,
+        }
+    }
+
+    /// What the DHT currently holds for this read
+    fn state(&self, context: &Arc<Context>) -> Result<String, HolochainError> {
+        let dht = context
+            .state()
+            .ok_or_else(|| HolochainError::ErrorGeneric("state not initialized".to_string()))?
+            .dht();
+        let state = match self {
+            DhtRead::Entry(address) => {
+                let latest = dht.get_latest_version(address)?;
+                serde_json::to_string(&(
+                    dht.content_storage().contains(address)?,
+                    dht.get_crud_status(address)?,
+                    dht.get_crud_link(address)?,
+                    dht.get_crud_status(&latest)?,
+                    latest,
+                ))?
+            }
+            DhtRead::Links(address, tag) => {
+                serde_json::to_string(&dht.get_link_targets(address.clone(), tag.clone())?)?
+            }
+        };
+        Ok(state)
+    }
+}
+
+/// Notes a read of the DHT, if a validation is running on this thread
+pub fn note_read(read: DhtRead) {
+    DHT_READS.with(|reads| {
+        if let Some(ref mut reads) = *reads.borrow_mut() {
+            if !reads.contains(&read) {
+                reads.push(read);
+            }
+        }
+    })
+}
+
+/// Runs the validation and returns the reads of the DHT it made next to its outcome.
+/// Calls the validation makes run on the same thread, so their reads are included.
+pub fn recording_reads<T, F: FnOnce() -> T>(validate: F) -> (T, Vec<DhtRead>) {
+    let outer_reads = DHT_READS.with(|reads| reads.replace(Some(Vec::new())));
+    let outcome = validate();
+    let reads = DHT_READS
+        .with(|reads| reads.replace(outer_reads))
+        .unwrap_or_default();
+    (outcome, reads)
+}
+
+/// A read of the DHT a validation made, and what it found
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DhtDependency {
+    pub read: DhtRead,
+    pub state: String,
+}
+
+/// Captures what the DHT holds for the given reads
+pub fn dht_dependencies(
+    context: &Arc<Context>,
+    reads: Vec<DhtRead>,
+) -> Result<Vec<DhtDependency>, HolochainError> {
+    reads
+        .into_iter()
+        .map(|read| {
+            read.state(context)
+                .map(|state| DhtDependency { read, state })
+        })
+        .collect()
+}
+
+/// True if the DHT still holds the same for all the dependencies
+fn dht_unchanged(
+    context: &Arc<Context>,
+    dependencies: &[DhtDependency],
+) -> Result<bool, HolochainError> {
+    for dependency in dependencies {
+        if dependency.read.state(context)? != dependency.state {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The outcome of one validation of an entry
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, DefaultJson)]
+pub struct ValidationRecord {
+    pub entry_address: Address,
+    pub dna_hash: Address,
+    /// hash of the entry type and the validation data the entry got validated with
+    pub dependencies: Address,
+    /// what the validation read from the DHT
+    #[serde(default)]
+    pub dht_dependencies: Vec<DhtDependency>,
+    pub result: ValidationResult,
+}
+
+impl AddressableContent for ValidationRecord {
+    fn content(&self) -> Content {
+        self.to_owned().into()
+    }
+
+    fn from_content(content: &Content) -> Self {
+        Self::try_from(content.to_owned()).expect("could not deserialize ValidationRecord")
+    }
+}
+
+/// The hash the validation records of a DNA are stored under
+pub fn dna_hash(dna: &Dna) -> Address {
+    dna.to_entry().address()
+}
+
+/// Hash of everything a validation depends on besides the entry itself
+pub fn dependencies_hash(
+    entry_type: &EntryType,
+    validation_data: &ValidationData,
+) -> Result<Address, HolochainError> {
+    let dependencies = serde_json::to_string(&(entry_type.to_string(), validation_data))?;
+    Ok(Address::encode_from_str(&dependencies, Hash::SHA2256))
+}
+
+/// The result of an earlier validation of the entry with the same dependencies, if any.
+/// Records of validations that read something the DHT holds differently by now are ignored.
+pub fn cached_result(
+    context: &Arc<Context>,
+    dna_hash: &Address,
+    entry_address: &Address,
+    dependencies: &Address,
+) -> Result<Option<ValidationResult>, HolochainError> {
+    for eav in context.eav_storage.fetch_eav(
+        Some(entry_address.clone()),
+        Some(validation_attribute(dna_hash)),
+        None,
+    )? {
+        let maybe_record: Option<ValidationRecord> = context.file_storage.fetch(&eav.value())?;
+        if let Some(record) = maybe_record {
+            if &record.dependencies == dependencies
+                && dht_unchanged(context, &record.dht_dependencies)?
+            {
+                return Ok(Some(record.result));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Stores the outcome of a validation and adds rejected entries to the registry,
+/// if their rejection does not depend on the DHT
+pub fn record_result(
+    context: &Arc<Context>,
+    record: &ValidationRecord,
+) -> Result<(), HolochainError> {
+    let mut cas = context.file_storage.clone();
+    let mut eav_storage = context.eav_storage.clone();
+
+    cas.add(record)?;
+    eav_storage.add_eav(&EntityAttributeValue::new(
+        &record.entry_address,
+        &validation_attribute(&record.dna_hash),
+        &record.address(),
+    ))?;
+    for dependency in &record.dht_dependencies {
+        eav_storage.add_eav(&EntityAttributeValue::new(
+            dependency.read.address(),
+            &dependency_attribute(&record.dna_hash),
+            &record.address(),
+        ))?;
+    }
+    if record.result.is_err() && record.dht_dependencies.is_empty() {
+        eav_storage.add_eav(&EntityAttributeValue::new(
+            &record.dna_hash,
+            &REJECTED_ATTRIBUTE.to_string(),
+            &record.entry_address,
+        ))?;
+    }
+    Ok(())
+}
+
+/// Validation records of the DNA that depend on what the DHT holds for the address
+pub fn dependent_records(
+    context: &Arc<Context>,
+    dna_hash: &Address,
+    address: &Address,
+) -> Result<Vec<ValidationRecord>, HolochainError> {
+    let mut records = Vec::new();
+    for eav in context.eav_storage.fetch_eav(
+        Some(address.clone()),
+        Some(dependency_attribute(dna_hash)),
+        None,
+    )? {
+        let maybe_record: Option<ValidationRecord> = context.file_storage.fetch(&eav.value())?;
+        records.extend(maybe_record);
+    }
+    Ok(records)
+}
+
+/// Addresses of all entries that failed a validation for the given DNA
+pub fn rejected_entries(
+    context: &Arc<Context>,
+    dna_hash: &Address,
+) -> Result<Vec<Address>, HolochainError> {
+    let mut rejected: Vec<Address> = context
+        .eav_storage
+        .fetch_eav(
+            Some(dna_hash.clone()),
+            Some(REJECTED_ATTRIBUTE.to_string()),
+            None,
+        )?
+        .iter()
+        .map(|eav| eav.value())
+        .collect();
+    rejected.sort();
+    rejected.dedup();
+    Ok(rejected)
+}
+
+/// True if the entry failed a validation for the given DNA
+pub fn is_rejected(
+    context: &Arc<Context>,
+    dna_hash: &Address,
+    entry_address: &Address,
+) -> Result<bool, HolochainError> {
+    Ok(!context
+        .eav_storage
+        .fetch_eav(
+            Some(dna_hash.clone()),
+            Some(REJECTED_ATTRIBUTE.to_string()),
+            Some(entry_address.clone()),
+        )?
+        .is_empty())
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+
+    use super::*;
+    use holochain_core_types::{
+        entry::{test_entry, test_entry_b},
+        validation::{EntryAction, EntryLifecycle, ValidationPackage},
+    };
+    use instance::tests::{test_context, test_instance_and_context};
+    use nucleus::actions::tests::commit;
+
+    fn test_validation_data(action: EntryAction) -> ValidationData {
+        ValidationData {
+            package: ValidationPackage {
+                chain_header: None,
+                source_chain_entries: None,
+                source_chain_headers: None,
+                custom: None,
+            },
+            sources: Vec::new(),
+            lifecycle: EntryLifecycle::Chain,
+            action,
+            bundle: None,
+            old_entry: None,
+            old_entry_header: None,
+            membrane_proof: None,
+            link_action: None,
+        }
+    }
+
+    fn test_record(
+        entry_address: Address,
+        dependencies: Address,
+        result: ValidationResult,
+    ) -> ValidationRecord {
+        ValidationRecord {
+            entry_address,
+            dna_hash: Address::from("test dna"),
+            dependencies,
+            dht_dependencies: Vec::new(),
+            result,
+        }
+    }
+
+    #[test]
+    fn caches_results_per_dependencies() {
+        let context = test_context("jane");
+        let dna_hash = Address::from("test dna");
+        let entry_type = test_entry().entry_type().clone();
+        let commit_dependencies =
+            dependencies_hash(&entry_type, &test_validation_data(EntryAction::Commit)).unwrap();
+        let modify_dependencies =
+            dependencies_hash(&entry_type, &test_validation_data(EntryAction::Modify)).unwrap();
+        assert_ne!(commit_dependencies, modify_dependencies);
+
+        assert_eq!(
+            Ok(None),
+            cached_result(
+                &context,
+                &dna_hash,
+                &test_entry().address(),
+                &commit_dependencies
+            )
+        );
+        record_result(
+            &context,
+            &test_record(test_entry().address(), commit_dependencies.clone(), Ok(())),
+        ).unwrap();
+
+        assert_eq!(
+            Ok(Some(Ok(()))),
+            cached_result(
+                &context,
+                &dna_hash,
+                &test_entry().address(),
+                &commit_dependencies
+            )
+        );
+        // changed dependencies need a new validation
+        assert_eq!(
+            Ok(None),
+            cached_result(
+                &context,
+                &dna_hash,
+                &test_entry().address(),
+                &modify_dependencies
+            )
+        );
+        // as does another DNA
+        assert_eq!(
+            Ok(None),
+            cached_result(
+                &context,
+                &Address::from("other dna"),
+                &test_entry().address(),
+                &commit_dependencies
+            )
+        );
+    }
+
+    #[test]
+    fn registers_rejected_entries() {
+        let context = test_context("jane");
+        let dna_hash = Address::from("test dna");
+        let dependencies = Address::from("dependencies");
+
+        record_result(
+            &context,
+            &test_record(test_entry().address(), dependencies.clone(), Ok(())),
+        ).unwrap();
+        record_result(
+            &context,
+            &test_record(
+                test_entry_b().address(),
+                dependencies.clone(),
+                Err("spam".to_string()),
+            ),
+        ).unwrap();
+
+        assert_eq!(
+            Ok(vec![test_entry_b().address()]),
+            rejected_entries(&context, &dna_hash)
+        );
+        assert_eq!(
+            Ok(true),
+            is_rejected(&context, &dna_hash, &test_entry_b().address())
+        );
+        assert_eq!(
+            Ok(false),
+            is_rejected(&context, &dna_hash, &test_entry().address())
+        );
+        assert_eq!(
+            Ok(Some(Err("spam".to_string()))),
+            cached_result(
+                &context,
+                &dna_hash,
+                &test_entry_b().address(),
+                &dependencies
+            )
+        );
+    }
+
+    #[test]
+    fn records_reads_of_validations() {
+        let entry_read = DhtRead::Entry(test_entry().address());
+        let links_read = DhtRead::Links(test_entry().address(), "test-tag".to_string());
+        // nothing gets recorded outside of a validation
+        note_read(entry_read.clone());
+
+        let ((), reads) = recording_reads(|| {
+            note_read(entry_read.clone());
+            note_read(links_read.clone());
+            note_read(entry_read.clone());
+        });
+        assert_eq!(vec![entry_read, links_read], reads);
+
+        let ((), reads) = recording_reads(|| ());
+        assert!(reads.is_empty());
+    }
+
+    #[test]
+    fn ignores_results_when_the_dht_changed() {
+        let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (_instance, context) =
+            test_instance_and_context(dna).expect("Could not create test instance");
+        let dna_hash = Address::from("test dna");
+        let dependencies = Address::from("dependencies");
+
+        // the validation failed because the entry it read was not there yet
+        let mut record = test_record(
+            test_entry_b().address(),
+            dependencies.clone(),
+            Err("missing".to_string()),
+        );
+        record.dht_dependencies =
+            dht_dependencies(&context, vec![DhtRead::Entry(test_entry().address())]).unwrap();
+        record_result(&context, &record).unwrap();
+
+        assert_eq!(
+            Ok(Some(Err("missing".to_string()))),
+            cached_result(
+                &context,
+                &dna_hash,
+                &test_entry_b().address(),
+                &dependencies
+            )
+        );
+        assert_eq!(
+            Ok(vec![record.clone()]),
+            dependent_records(&context, &dna_hash, &test_entry().address())
+        );
+        // a rejection that depends on the DHT is not registered
+        assert_eq!(
+            Ok(false),
+            is_rejected(&context, &dna_hash, &test_entry_b().address())
+        );
+
+        commit(test_entry(), &context);
+        assert_eq!(
+            Ok(None),
+            cached_result(
+                &context,
+                &dna_hash,
+                &test_entry_b().address(),
+                &dependencies
+            )
+        );
+    }
+}
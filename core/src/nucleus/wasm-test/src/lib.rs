@@ -13,7 +13,7 @@ extern crate holochain_core_types_derive;
 use holochain_wasm_utils::{memory_allocation::*, memory_serialization::*};
 
 extern {
-  fn hc_debug(encoded_allocation_of_input: i32) -> i32;
+  fn hc_debug(encoded_allocation_of_input: i64) -> i64;
 }
 
 //-------------------------------------------------------------------------------------------------
@@ -22,7 +22,7 @@ extern {
 
 /// Call HC API DEBUG function with proper input struct: a string
 /// return error code
-fn hdk_debug(mem_stack: &mut SinglePageStack, json_string: &JsonString) {
+fn hdk_debug(mem_stack: &mut WasmStack, json_string: &JsonString) {
   // Write input string on stack
   let maybe_allocation = store_as_json(mem_stack, json_string.to_owned());
   if let Err(_) = maybe_allocation {
@@ -31,7 +31,7 @@ fn hdk_debug(mem_stack: &mut SinglePageStack, json_string: &JsonString) {
   let allocation_of_input = maybe_allocation.unwrap();
   // Call WASMI-able DEBUG
   unsafe {
-    hc_debug(allocation_of_input.encode() as i32);
+    hc_debug(allocation_of_input.encode() as i64);
   }
   // Free input allocation and all allocations made inside print()
   mem_stack.deallocate(allocation_of_input).expect("deallocate failed");
@@ -46,22 +46,22 @@ fn hdk_debug(mem_stack: &mut SinglePageStack, json_string: &JsonString) {
 /// encoded_allocation_of_input : encoded memory offset and length of the memory allocation
 /// holding input arguments
 #[no_mangle]
-pub extern "C" fn debug_hello(encoded_allocation_of_input: usize) -> i32 {
-  let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
+pub extern "C" fn debug_hello(encoded_allocation_of_input: u64) -> i64 {
+  let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
   hdk_debug(&mut mem_stack, &JsonString::from(RawString::from("Hello world!")));
-  i32::from(RibosomeReturnCode::Success)
+  i64::from(RibosomeReturnCode::Success)
 }
 
 /// Function called by Holochain Instance
 /// encoded_allocation_of_input : encoded memory offset and length of the memory allocation
 /// holding input arguments
 #[no_mangle]
-pub extern "C" fn debug_multiple(encoded_allocation_of_input: usize) -> i32 {
-  let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
+pub extern "C" fn debug_multiple(encoded_allocation_of_input: u64) -> i64 {
+  let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
   hdk_debug(&mut mem_stack, &JsonString::from(RawString::from("Hello")));
   hdk_debug(&mut mem_stack, &JsonString::from(RawString::from("world")));
   hdk_debug(&mut mem_stack, &JsonString::from(RawString::from("!")));
-  i32::from(RibosomeReturnCode::Success)
+  i64::from(RibosomeReturnCode::Success)
 }
 
 //-------------------------------------------------------------------------------------------------
@@ -74,8 +74,8 @@ struct TestStruct {
 }
 
 #[no_mangle]
-pub extern "C" fn debug_stacked_hello(encoded_allocation_of_input: usize) -> i32 {
-  let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
+pub extern "C" fn debug_stacked_hello(encoded_allocation_of_input: u64) -> i64 {
+  let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
   let fish = store_as_json_into_encoded_allocation(&mut mem_stack, TestStruct {
     value: "fish".to_string(),
   });
@@ -73,19 +73,38 @@ use error::{HolochainInstanceError, HolochainResult};
 use futures::executor::block_on;
 use holochain_core::{
     context::Context,
+    executor::{Pool, PoolMetrics},
     instance::Instance,
-    nucleus::{actions::initialize::initialize_application, call_and_wait_for_result, ZomeFnCall},
+    network::{register_message_receiver, unregister_message_receiver},
+    nucleus::{
+        actions::initialize::initialize_application,
+        call_and_wait_for_result, call_and_wait_for_result_with_timeout,
+        validation_cache::{dna_hash, rejected_entries},
+        Provenance, ZomeFnCall,
+    },
     persister::{Persister, SimplePersister},
     state::State,
 };
-use holochain_core_types::{error::HolochainError, json::JsonString};
+use holochain_core_types::{
+    cap_grant::CapToken, cas::content::Address, error::HolochainError, json::JsonString,
+};
 use holochain_dna::Dna;
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often a shutdown checks whether the running zome calls have finished
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long a shutdown waits for cancelled zome calls to return.
+/// They stop right away while running wasm, but not while blocked in a zome API function.
+const SHUTDOWN_CANCEL_TIMEOUT: Duration = Duration::from_millis(1000);
 
 /// contains a Holochain application instance
 pub struct Holochain {
     instance: Instance,
-    #[allow(dead_code)]
     context: Arc<Context>,
     active: bool,
 }
@@ -93,11 +112,21 @@ pub struct Holochain {
 impl Holochain {
     /// create a new Holochain instance
     pub fn new(dna: Dna, context: Arc<Context>) -> HolochainResult<Self> {
+        Holochain::new_with_membrane_proof(dna, None, context)
+    }
+
+    /// create a new Holochain instance for an agent that joins with a membrane proof,
+    /// e.g. an invite code, which the zomes check before admitting the agent
+    pub fn new_with_membrane_proof(
+        dna: Dna,
+        membrane_proof: Option<JsonString>,
+        context: Arc<Context>,
+    ) -> HolochainResult<Self> {
         let mut instance = Instance::new(context.clone());
         let name = dna.name.clone();
         instance.start_action_loop(context.clone());
         let context = instance.initialize_context(context);
-        match block_on(initialize_application(dna, context.clone())) {
+        match block_on(initialize_application(dna, membrane_proof, context.clone())) {
             Ok(_) => {
                 context.log(&format!("{} instantiated", name))?;
                 let hc = Holochain {
@@ -121,6 +150,11 @@ impl Holochain {
             .unwrap();
         let mut instance = Instance::from_state(loaded_state);
         instance.start_action_loop(context.clone());
+        // Initialization already happened, only start answering direct messages again
+        let maybe_dna = instance.state().nucleus().dna();
+        if let Some(dna) = maybe_dna {
+            register_message_receiver(&instance.initialize_context(context.clone()), &dna)?;
+        }
         Ok(Holochain {
             instance,
             context: context.clone(),
@@ -146,7 +180,65 @@ impl Holochain {
         Ok(())
     }
 
-    /// call a function in a zome
+    /// shut the Holochain instance down for good:
+    /// * no new zome calls are accepted and direct messages from other agents
+    ///   are not answered anymore,
+    /// * zome calls that are running already get the given time to finish,
+    ///   the ones still running after that are cancelled and their callers get
+    ///   HolochainError::ExecutionLimitExceeded,
+    /// * the action loop is stopped and its thread joined,
+    /// * the state is saved with the persister of the context,
+    /// * the storage actors are stopped, unless a cancelled zome call is still stuck
+    ///   in a zome API function and could use them, these are left to be dropped
+    ///   with the last clone of the context.
+    pub fn shutdown(mut self, drain_timeout: Duration) -> HolochainResult<()> {
+        self.active = false;
+        let maybe_dna = self.instance.state().nucleus().dna();
+        if let Some(dna) = maybe_dna {
+            unregister_message_receiver(&self.context, &dna)?;
+        }
+
+        if !self.wait_for_zome_calls(drain_timeout) {
+            self.context.cancel_zome_calls();
+            self.wait_for_zome_calls(SHUTDOWN_CANCEL_TIMEOUT);
+        }
+
+        self.instance.stop_action_loop()?;
+        let state = self.instance.state().clone();
+        // an instance without a source chain has nothing to persist
+        if state.agent().top_chain_header().is_some() {
+            self.context
+                .persister
+                .lock()
+                .map_err(|_| HolochainError::ErrorGeneric(String::from("persister lock poisoned")))?
+                .save(state)?;
+        }
+        // calls that did not return after being cancelled could still be waiting
+        // on the storage actors and would block for ever once these are gone
+        if self.instance.state().nucleus().running_zome_calls() == 0 {
+            self.context.file_storage.stop();
+            self.context.eav_storage.stop();
+        }
+        self.context.log("instance shut down")?;
+        Ok(())
+    }
+
+    /// Waits until no zome calls are running anymore, at most for the given time.
+    /// Returns whether all of them finished.
+    fn wait_for_zome_calls(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.instance.state().nucleus().running_zome_calls() > 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(SHUTDOWN_POLL_INTERVAL);
+        }
+        true
+    }
+
+    /// call a function in a zome, waiting for its result at most RECV_DEFAULT_TIMEOUT_MS.
+    /// Fails with HolochainError::Timeout after that, use call_with_timeout for calls
+    /// that take longer.
     pub fn call(
         &mut self,
         zome: &str,
@@ -157,7 +249,50 @@ impl Holochain {
         if !self.active {
             return Err(HolochainInstanceError::InstanceNotActiveYet);
         }
-        let zome_call = ZomeFnCall::new(&zome, &cap, &fn_name, String::from(params));
+        let mut zome_call = ZomeFnCall::new(&zome, &cap, &fn_name, String::from(params));
+        zome_call.provenance = Provenance::External;
+        Ok(call_and_wait_for_result(zome_call, &mut self.instance)?)
+    }
+
+    /// call a function in a zome, giving up on the result once the timeout has passed.
+    /// Fails with HolochainError::Timeout in that case, the call itself keeps running
+    /// but its result is dropped.
+    pub fn call_with_timeout(
+        &mut self,
+        zome: &str,
+        cap: &str,
+        fn_name: &str,
+        params: &str,
+        timeout: Duration,
+    ) -> HolochainResult<JsonString> {
+        if !self.active {
+            return Err(HolochainInstanceError::InstanceNotActiveYet);
+        }
+        let mut zome_call = ZomeFnCall::new(&zome, &cap, &fn_name, String::from(params));
+        zome_call.provenance = Provenance::External;
+        Ok(call_and_wait_for_result_with_timeout(
+            zome_call,
+            &mut self.instance,
+            timeout,
+        )?)
+    }
+
+    /// call a function in a zome presenting a capability token, for capabilities whose
+    /// membrane only lets callers through that hold a grant
+    pub fn call_with_token(
+        &mut self,
+        zome: &str,
+        cap: &str,
+        token: CapToken,
+        fn_name: &str,
+        params: &str,
+    ) -> HolochainResult<JsonString> {
+        if !self.active {
+            return Err(HolochainInstanceError::InstanceNotActiveYet);
+        }
+        let mut zome_call = ZomeFnCall::new(&zome, &cap, &fn_name, String::from(params));
+        zome_call.provenance = Provenance::External;
+        zome_call.token = Some(token);
         Ok(call_and_wait_for_result(zome_call, &mut self.instance)?)
     }
 
@@ -170,6 +305,22 @@ impl Holochain {
     pub fn state(&mut self) -> Result<State, HolochainInstanceError> {
         Ok(self.instance.state().clone())
     }
+
+    /// current load of one of the worker pools of the instance
+    pub fn pool_metrics(&self, pool: Pool) -> PoolMetrics {
+        self.context.executor.metrics(pool)
+    }
+
+    /// addresses of all entries that failed validation for the DNA of this instance
+    pub fn rejected_entries(&self) -> HolochainResult<Vec<Address>> {
+        let dna = self
+            .instance
+            .state()
+            .nucleus()
+            .dna()
+            .ok_or(HolochainError::DnaMissing)?;
+        Ok(rejected_entries(&self.context, &dna_hash(&dna))?)
+    }
 }
 
 #[cfg(test)]
@@ -190,8 +341,8 @@ mod tests {
     use std::sync::{Arc, Mutex};
     use tempfile::tempdir;
     use test_utils::{
-        create_test_cap_with_fn_name, create_test_dna_with_cap, create_test_dna_with_wat,
-        create_wasm_from_file, hc_setup_and_call_zome_fn,
+        create_test_cap_with_fn_name, create_test_dna_with_blocking_main, create_test_dna_with_cap,
+        create_test_dna_with_wat, create_wasm_from_file, hc_setup_and_call_zome_fn,
     };
 
     // TODO: TestLogger duplicated in test_utils because:
@@ -243,8 +394,8 @@ mod tests {
                 r#"
             (module
                 (memory (;0;) 17)
-                (func (export "genesis") (param $p0 i32) (result i32)
-                    i32.const 9
+                (func (export "genesis") (param $p0 i64) (result i64)
+                    i64.const 9
                 )
                 (data (i32.const 0)
                     "fail"
@@ -259,7 +410,7 @@ mod tests {
         let result = Holochain::new(dna.clone(), context.clone());
         assert!(result.is_err());
         assert_eq!(
-            HolochainInstanceError::from(HolochainError::ErrorGeneric("\"Genesis\"".to_string())),
+            HolochainInstanceError::from(HolochainError::ErrorGeneric("{\"Genesis".to_string())),
             result.err().unwrap(),
         );
     }
@@ -273,9 +424,9 @@ mod tests {
                 r#"
             (module
                 (memory (;0;) 17)
-                (func (export "genesis") (param $p0 i32) (result i32)
+                (func (export "genesis") (param $p0 i64) (result i64)
                     (loop (br 0))
-                    i32.const 0
+                    i64.const 0
                 )
                 (export "memory" (memory 0))
             )
@@ -325,6 +476,66 @@ mod tests {
         assert!(!hc.active());
     }
 
+    #[test]
+    fn can_shutdown() {
+        let dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (context, test_logger) = test_context("bob");
+        let mut hc = Holochain::new(dna.clone(), context).unwrap();
+        hc.start().expect("couldn't start");
+        assert!(hc.call("test_zome", "test_cap", "main", "").is_ok());
+
+        let mut instance = hc.instance.clone();
+        assert_eq!(Ok(()), hc.shutdown(Duration::from_secs(1)));
+
+        // the action loop is gone, so nothing gets processed anymore
+        let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "");
+        assert!(call_and_wait_for_result(zome_call, &mut instance).is_err());
+
+        let test_logger = test_logger.lock().unwrap();
+        assert_eq!(
+            Some(&String::from("instance shut down")),
+            test_logger.log.last()
+        );
+    }
+
+    #[test]
+    fn shutdown_cancels_calls_that_do_not_finish_in_time() {
+        // main only returns when it gets cancelled, its timeout is way beyond the test's
+        let dna = create_test_dna_with_blocking_main("test_zome", "test_cap", 60000);
+        let (context, test_logger) = test_context("bob");
+        let mut hc = Holochain::new(dna.clone(), context).unwrap();
+        hc.start().expect("couldn't start");
+
+        let mut instance = hc.instance.clone();
+        let caller = thread::spawn(move || {
+            let zome_call = ZomeFnCall::new("test_zome", "test_cap", "main", "");
+            call_and_wait_for_result_with_timeout(zome_call, &mut instance, Duration::from_secs(10))
+        });
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while hc.instance.state().nucleus().running_zome_calls() == 0 {
+            assert!(Instant::now() < deadline, "zome call did not start");
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let instance = hc.instance.clone();
+        let start = Instant::now();
+        assert_eq!(Ok(()), hc.shutdown(Duration::from_millis(100)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        assert_eq!(
+            Err(HolochainError::ExecutionLimitExceeded(
+                "zome call cancelled".to_string()
+            )),
+            caller.join().unwrap(),
+        );
+        // nothing uses the storage anymore, so its actors were stopped
+        assert_eq!(0, instance.state().nucleus().running_zome_calls());
+        assert_eq!(
+            Some(&String::from("instance shut down")),
+            test_logger.lock().unwrap().log.last()
+        );
+    }
+
     #[test]
     fn can_call() {
         let wat = r#"
@@ -332,8 +543,8 @@ mod tests {
  (memory 1)
  (export "memory" (memory 0))
  (export "main" (func $func0))
- (func $func0 (param $p0 i32) (result i32)
-       i32.const 16
+ (func $func0 (param $p0 i64) (result i64)
+       i64.const 16
        )
  (data (i32.const 0)
        "{\"holo\":\"world\"}"
@@ -362,6 +573,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_call_with_timeout() {
+        // main only returns when the zome aborts it after a second
+        let dna = create_test_dna_with_blocking_main("test_zome", "test_cap", 1000);
+        let (context, _) = test_context("bob");
+        let mut hc = Holochain::new(dna.clone(), context).unwrap();
+        hc.start().expect("couldn't start");
+
+        let result = hc.call_with_timeout(
+            "test_zome",
+            "test_cap",
+            "main",
+            "",
+            Duration::from_millis(100),
+        );
+        assert_eq!(
+            Err(HolochainInstanceError::InternalFailure(
+                HolochainError::Timeout
+            )),
+            result
+        );
+
+        // later calls are not affected by the one that timed out
+        let result =
+            hc.call_with_timeout("test_zome", "test_cap", "main", "", Duration::from_secs(10));
+        assert_eq!(
+            Err(HolochainInstanceError::InternalFailure(
+                HolochainError::ExecutionLimitExceeded(
+                    "zome call timed out (limit: 1000ms)".to_string()
+                )
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn runs_zome_calls_on_worker_pool() {
+        let dna = create_test_dna_with_wat("test_zome", "test_cap", None);
+        let (context, _) = test_context("bob");
+        let mut hc = Holochain::new(dna.clone(), context).unwrap();
+        hc.start().expect("couldn't start");
+
+        for _ in 0..10 {
+            let result = hc.call("test_zome", "test_cap", "main", "");
+            assert!(result.is_ok(), "result = {:?}", result);
+        }
+
+        let metrics = hc.pool_metrics(Pool::ZomeCalls);
+        assert!(metrics.workers >= 1);
+        assert!(metrics.workers <= metrics.max_workers);
+        assert_eq!(0, metrics.rejected);
+    }
+
     #[test]
     fn can_get_state() {
         let dna = Dna::new();
@@ -12,7 +12,7 @@ use holochain_core_types::{
 use std::convert::TryInto;
 
 extern {
-  fn hc_commit_entry(encoded_allocation_of_input: i32) -> i32;
+  fn hc_commit_entry(encoded_allocation_of_input: i64) -> i64;
 }
 
 //-------------------------------------------------------------------------------------------------
@@ -21,7 +21,7 @@ extern {
 
 /// Call HC API COMMIT function with proper input struct
 /// return hash of entry added source chain
-fn hdk_commit(mem_stack: &mut SinglePageStack, entry_type_name: &str, entry_value: &str)
+fn hdk_commit(mem_stack: &mut WasmStack, entry_type_name: &str, entry_value: &str)
   -> Result<Address, String>
 {
   // Put args in struct and serialize into memory
@@ -32,12 +32,12 @@ fn hdk_commit(mem_stack: &mut SinglePageStack, entry_type_name: &str, entry_valu
   let allocation_of_input =  store_as_json(mem_stack, JsonString::from(serialized_entry))?;
 
   // Call WASMI-able commit
-  let encoded_allocation_of_result: i32;
+  let encoded_allocation_of_result: i64;
   unsafe {
-    encoded_allocation_of_result = hc_commit_entry(allocation_of_input.encode() as i32);
+    encoded_allocation_of_result = hc_commit_entry(allocation_of_input.encode() as i64);
   }
   // Deserialize complex result stored in memory
-  let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+  let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u64)?;
 
   // Free result & input allocations and all allocations made inside commit()
   mem_stack
@@ -56,7 +56,7 @@ fn hdk_commit(mem_stack: &mut SinglePageStack, entry_type_name: &str, entry_valu
 //-------------------------------------------------------------------------------------------------
 
 // Simulate error in commit function by inputing output struct as input
-fn hdk_commit_fail(mem_stack: &mut SinglePageStack)
+fn hdk_commit_fail(mem_stack: &mut WasmStack)
   -> Result<Address, String>
 {
   // Put args in struct and serialize into memory
@@ -64,12 +64,12 @@ fn hdk_commit_fail(mem_stack: &mut SinglePageStack)
   let allocation_of_input =  store_as_json(mem_stack, input)?;
 
   // Call WASMI-able commit
-  let encoded_allocation_of_result: i32;
+  let encoded_allocation_of_result: i64;
   unsafe {
-    encoded_allocation_of_result = hc_commit_entry(allocation_of_input.encode() as i32);
+    encoded_allocation_of_result = hc_commit_entry(allocation_of_input.encode() as i64);
   }
   // Deserialize complex result stored in memory
-  let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+  let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u64)?;
 
   // Free result & input allocations and all allocations made inside commit()
   mem_stack
@@ -92,8 +92,8 @@ fn hdk_commit_fail(mem_stack: &mut SinglePageStack)
 /// holding input arguments
 /// returns encoded allocation used to store output
 #[no_mangle]
-pub extern "C" fn test(encoded_allocation_of_input: usize) -> i32 {
-  let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
+pub extern "C" fn test(encoded_allocation_of_input: u64) -> i64 {
+  let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
   let result = hdk_commit(&mut mem_stack, "testEntryType", "hello");
   store_as_json_into_encoded_allocation(&mut mem_stack, result)
 }
@@ -103,14 +103,14 @@ pub extern "C" fn test(encoded_allocation_of_input: usize) -> i32 {
 /// holding input arguments
 /// returns encoded allocation used to store output
 #[no_mangle]
-pub extern "C" fn test_fail(encoded_allocation_of_input: usize) -> i32 {
-  let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
+pub extern "C" fn test_fail(encoded_allocation_of_input: u64) -> i64 {
+  let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
   let result = hdk_commit_fail(&mut mem_stack);
   store_as_json_into_encoded_allocation(&mut mem_stack, result)
 }
 
 #[no_mangle]
-pub extern fn __hdk_get_validation_package_for_entry_type(encoded_allocation_of_input: usize) -> i32 {
-  let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
+pub extern fn __hdk_get_validation_package_for_entry_type(encoded_allocation_of_input: u64) -> i64 {
+  let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
   store_string_into_encoded_allocation(&mut mem_stack, "\"ChainFull\"")
 }
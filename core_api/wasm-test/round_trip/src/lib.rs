@@ -43,9 +43,9 @@ fn test_inner(input: InputStruct) -> OutputStruct {
 /// holding input arguments
 /// returns encoded allocation used to store output
 #[no_mangle]
-pub extern "C" fn test(encoded_allocation_of_input: usize) -> i32 {
-    let mut mem_stack = SinglePageStack::from_encoded_allocation(encoded_allocation_of_input as u32).unwrap();
-    let input = load_json(encoded_allocation_of_input as u32).unwrap();
+pub extern "C" fn test(encoded_allocation_of_input: u64) -> i64 {
+    let mut mem_stack = WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap();
+    let input = load_json(encoded_allocation_of_input).unwrap();
     let output = test_inner(input);
     return store_as_json_into_encoded_allocation(&mut mem_stack, JsonString::from(output));
 }
@@ -0,0 +1,195 @@
+use cas::content::Address;
+use entry::{Entry, ToEntry};
+use entry_type::EntryType;
+use error::HolochainError;
+use json::JsonString;
+use std::convert::TryInto;
+
+/// Token to present with a zome function call to get through the membrane of a capability,
+/// the address of the CapTokenGrant that grants the access
+pub type CapToken = Address;
+
+//-------------------------------------------------------------------------------------------------
+// CapTokenGrant
+//-------------------------------------------------------------------------------------------------
+
+/// The system entry an agent commits to its source chain to grant access to
+/// some functions of a capability of one of its zomes.
+/// Its address is the token that has to be presented with the calls.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
+pub struct CapTokenGrant {
+    zome: String,
+    capability: String,
+    functions: Vec<String>,
+    /// The only agents the token is good for, anyone holding the token if None
+    assignees: Option<Vec<Address>>,
+    /// Random value that makes the token of every grant unique and unguessable,
+    /// even if the same access gets granted again
+    nonce: String,
+}
+
+impl CapTokenGrant {
+    pub fn new(
+        zome: &str,
+        capability: &str,
+        functions: Vec<String>,
+        assignees: Option<Vec<Address>>,
+        nonce: &str,
+    ) -> Self {
+        CapTokenGrant {
+            zome: zome.to_string(),
+            capability: capability.to_string(),
+            functions,
+            assignees,
+            nonce: nonce.to_string(),
+        }
+    }
+
+    pub fn zome(&self) -> &str {
+        &self.zome
+    }
+
+    pub fn capability(&self) -> &str {
+        &self.capability
+    }
+
+    pub fn functions(&self) -> &Vec<String> {
+        &self.functions
+    }
+
+    pub fn assignees(&self) -> &Option<Vec<Address>> {
+        &self.assignees
+    }
+
+    /// true if the grant gives access to the given function
+    pub fn grants(&self, zome: &str, capability: &str, function: &str) -> bool {
+        self.zome == zome
+            && self.capability == capability
+            && self.functions.iter().any(|granted| granted == function)
+    }
+
+    /// true if the given agent may use the token
+    pub fn is_assigned_to(&self, agent: &Address) -> bool {
+        match self.assignees {
+            None => true,
+            Some(ref assignees) => assignees.contains(agent),
+        }
+    }
+}
+
+impl ToEntry for CapTokenGrant {
+    fn to_entry(&self) -> Entry {
+        Entry::new(EntryType::CapTokenGrant, self.to_owned())
+    }
+
+    fn from_entry(entry: &Entry) -> Self {
+        assert_eq!(&EntryType::CapTokenGrant, entry.entry_type());
+        entry
+            .value()
+            .to_owned()
+            .try_into()
+            .expect("could not convert Entry to CapTokenGrant")
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+// CapTokenRevocation
+//-------------------------------------------------------------------------------------------------
+
+/// The system entry committed to the source chain when a grant gets revoked.
+/// Calls presenting the token of the grant are rejected from then on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
+pub struct CapTokenRevocation {
+    revoked_token: CapToken,
+}
+
+impl CapTokenRevocation {
+    pub fn new(revoked_token: CapToken) -> Self {
+        CapTokenRevocation { revoked_token }
+    }
+
+    pub fn revoked_token(&self) -> &CapToken {
+        &self.revoked_token
+    }
+}
+
+impl ToEntry for CapTokenRevocation {
+    fn to_entry(&self) -> Entry {
+        Entry::new(EntryType::CapTokenRevocation, self.to_owned())
+    }
+
+    fn from_entry(entry: &Entry) -> Self {
+        assert_eq!(&EntryType::CapTokenRevocation, entry.entry_type());
+        entry
+            .value()
+            .to_owned()
+            .try_into()
+            .expect("could not convert Entry to CapTokenRevocation")
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use cas::content::AddressableContent;
+
+    pub fn test_cap_token_grant() -> CapTokenGrant {
+        CapTokenGrant::new(
+            "test_zome",
+            "test_cap",
+            vec!["test".to_string()],
+            Some(vec![Address::from("bob")]),
+            "test_nonce",
+        )
+    }
+
+    #[test]
+    fn cap_token_grant_grants_test() {
+        let grant = test_cap_token_grant();
+        assert!(grant.grants("test_zome", "test_cap", "test"));
+        assert!(!grant.grants("test_zome", "test_cap", "other"));
+        assert!(!grant.grants("test_zome", "other_cap", "test"));
+        assert!(!grant.grants("other_zome", "test_cap", "test"));
+    }
+
+    #[test]
+    fn cap_token_grant_assignees_test() {
+        let grant = test_cap_token_grant();
+        assert!(grant.is_assigned_to(&Address::from("bob")));
+        assert!(!grant.is_assigned_to(&Address::from("eve")));
+
+        let open_grant = CapTokenGrant::new("test_zome", "test_cap", vec![], None, "test_nonce");
+        assert!(open_grant.is_assigned_to(&Address::from("eve")));
+    }
+
+    #[test]
+    fn cap_token_grant_to_entry_test() {
+        let entry = test_cap_token_grant().to_entry();
+        assert_eq!(&EntryType::CapTokenGrant, entry.entry_type());
+        assert_eq!(test_cap_token_grant(), CapTokenGrant::from_entry(&entry));
+    }
+
+    #[test]
+    fn cap_token_grant_nonce_test() {
+        let grant = test_cap_token_grant();
+        let other_grant = CapTokenGrant::new(
+            "test_zome",
+            "test_cap",
+            vec!["test".to_string()],
+            Some(vec![Address::from("bob")]),
+            "other_nonce",
+        );
+        assert_ne!(grant.to_entry().address(), other_grant.to_entry().address());
+    }
+
+    #[test]
+    fn cap_token_revocation_to_entry_test() {
+        let token = test_cap_token_grant().to_entry().address();
+        let revocation = CapTokenRevocation::new(token.clone());
+        assert_eq!(&token, revocation.revoked_token());
+
+        let entry = revocation.to_entry();
+        assert_eq!(&EntryType::CapTokenRevocation, entry.entry_type());
+        assert_eq!(revocation, CapTokenRevocation::from_entry(&entry));
+    }
+}
@@ -0,0 +1,84 @@
+use cas::content::Address;
+use entry::{Entry, ToEntry};
+use entry_type::EntryType;
+use error::HolochainError;
+use json::JsonString;
+use std::convert::TryInto;
+
+//-------------------------------------------------------------------------------------------------
+// DeletionEntry
+//-------------------------------------------------------------------------------------------------
+
+/// The system entry committed to the source chain when an entry gets removed.
+/// It only references the entry it deletes; the DHT marks that entry as DELETED.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
+pub struct DeletionEntry {
+    deleted_entry_address: Address,
+}
+
+impl DeletionEntry {
+    pub fn new(deleted_entry_address: Address) -> Self {
+        DeletionEntry {
+            deleted_entry_address,
+        }
+    }
+
+    pub fn deleted_entry_address(&self) -> &Address {
+        &self.deleted_entry_address
+    }
+}
+
+impl ToEntry for DeletionEntry {
+    fn to_entry(&self) -> Entry {
+        Entry::new(EntryType::Deletion, self.to_owned())
+    }
+
+    fn from_entry(entry: &Entry) -> Self {
+        assert_eq!(&EntryType::Deletion, entry.entry_type());
+        entry
+            .value()
+            .to_owned()
+            .try_into()
+            .expect("could not convert Entry to DeletionEntry")
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use cas::content::AddressableContent;
+    use delete_entry::DeletionEntry;
+    use entry::{test_entry, ToEntry};
+    use entry_type::EntryType;
+    use json::JsonString;
+
+    pub fn test_deletion_entry() -> DeletionEntry {
+        DeletionEntry::new(test_entry().address())
+    }
+
+    #[test]
+    fn deletion_entry_address_test() {
+        assert_eq!(
+            &test_entry().address(),
+            test_deletion_entry().deleted_entry_address(),
+        );
+    }
+
+    #[test]
+    fn deletion_entry_json_test() {
+        assert_eq!(
+            JsonString::from(format!(
+                "{{\"deleted_entry_address\":\"{}\"}}",
+                test_entry().address(),
+            )),
+            JsonString::from(test_deletion_entry()),
+        );
+    }
+
+    #[test]
+    fn deletion_entry_to_entry_test() {
+        let entry = test_deletion_entry().to_entry();
+        assert_eq!(&EntryType::Deletion, entry.entry_type());
+        assert_eq!(test_deletion_entry(), DeletionEntry::from_entry(&entry));
+    }
+}
@@ -26,6 +26,10 @@ pub enum EntryType {
     /// Should be moved into a different enum for DHT entry types.
     LinkList,
     AgentState,
+    CapTokenGrant,
+    CapTokenRevocation,
+    /// Credential an agent joined the network with, committed right after its AgentId
+    MembraneProof,
 }
 
 impl EntryType {
@@ -39,8 +43,12 @@ impl EntryType {
         !self.is_app()
     }
 
+    /// The DNA and capability grants stay private to the agent
     pub fn can_publish(&self) -> bool {
-        *self != EntryType::Dna
+        match self {
+            EntryType::Dna | EntryType::CapTokenGrant | EntryType::CapTokenRevocation => false,
+            _ => true,
+        }
     }
 
     /// Checks entry_type_name is valid
@@ -67,6 +75,9 @@ impl FromStr for EntryType {
             sys_prefix!("link_list") => Ok(EntryType::LinkList),
             sys_prefix!("migration") => Ok(EntryType::Migration),
             sys_prefix!("agent_state") => Ok(EntryType::AgentState),
+            sys_prefix!("cap_token_grant") => Ok(EntryType::CapTokenGrant),
+            sys_prefix!("cap_token_revocation") => Ok(EntryType::CapTokenRevocation),
+            sys_prefix!("membrane_proof") => Ok(EntryType::MembraneProof),
             _ => Ok(EntryType::App(s.to_string())),
         }
     }
@@ -85,6 +96,9 @@ impl From<EntryType> for String {
             EntryType::LinkList => sys_prefix!("link_list"),
             EntryType::Migration => sys_prefix!("migration"),
             EntryType::AgentState => sys_prefix!("agent_state"),
+            EntryType::CapTokenGrant => sys_prefix!("cap_token_grant"),
+            EntryType::CapTokenRevocation => sys_prefix!("cap_token_revocation"),
+            EntryType::MembraneProof => sys_prefix!("membrane_proof"),
         })
     }
 }
@@ -150,6 +164,9 @@ pub mod tests {
             EntryType::Link,
             EntryType::Migration,
             EntryType::LinkList,
+            EntryType::CapTokenGrant,
+            EntryType::CapTokenRevocation,
+            EntryType::MembraneProof,
         ]
     }
 
@@ -185,6 +202,12 @@ pub mod tests {
             (sys_prefix!("key"), EntryType::Key),
             (sys_prefix!("link"), EntryType::Link),
             (sys_prefix!("migration"), EntryType::Migration),
+            (sys_prefix!("cap_token_grant"), EntryType::CapTokenGrant),
+            (
+                sys_prefix!("cap_token_revocation"),
+                EntryType::CapTokenRevocation,
+            ),
+            (sys_prefix!("membrane_proof"), EntryType::MembraneProof),
         ] {
             assert_eq!(
                 variant,
@@ -199,7 +222,9 @@ pub mod tests {
     fn can_publish_test() {
         for t in test_types() {
             match t {
-                EntryType::Dna => assert!(!t.can_publish()),
+                EntryType::Dna | EntryType::CapTokenGrant | EntryType::CapTokenRevocation => {
+                    assert!(!t.can_publish())
+                }
                 _ => assert!(t.can_publish()),
             }
         }
@@ -5,6 +5,8 @@ pub enum DnaError {
     ZomeNotFound(String),
     CapabilityNotFound(String),
     ZomeFunctionNotFound(String),
+    ZomeFunctionInputInvalid(String),
+    ZomeFunctionOutputInvalid(String),
 }
 
 impl Error for DnaError {
@@ -13,6 +15,8 @@ impl Error for DnaError {
             DnaError::ZomeNotFound(err_msg) => &err_msg,
             DnaError::CapabilityNotFound(err_msg) => &err_msg,
             DnaError::ZomeFunctionNotFound(err_msg) => &err_msg,
+            DnaError::ZomeFunctionInputInvalid(err_msg) => &err_msg,
+            DnaError::ZomeFunctionOutputInvalid(err_msg) => &err_msg,
         }
     }
 }
@@ -76,6 +76,30 @@ impl fmt::Display for CoreError {
     }
 }
 
+//--------------------------------------------------------------------------------------------------
+// ZomeAbort
+//--------------------------------------------------------------------------------------------------
+
+/// Diagnostics a zome passed to the abort host function when it gave up on a call,
+/// as emitted by AssemblyScript on a failed assertion or a thrown error.
+#[derive(Clone, Debug, Serialize, Deserialize, DefaultJson, PartialEq, Eq, Hash)]
+pub struct ZomeAbort {
+    pub message: String,
+    pub filename: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for ZomeAbort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Zome aborted: {}\n  --> {}:{}:{}\n",
+            self.message, self.filename, self.line, self.column,
+        )
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // HolochainError
 //--------------------------------------------------------------------------------------------------
@@ -96,6 +120,11 @@ pub enum HolochainError {
     ValidationFailed(String),
     Ribosome(RibosomeErrorCode),
     RibosomeFailed(String),
+    ExecutionLimitExceeded(String),
+    ZomeAborted(ZomeAbort),
+    NetworkError(String),
+    Timeout,
+    WorkerPoolBusy(String),
 }
 
 pub type HcResult<T> = Result<T, HolochainError>;
@@ -108,7 +137,11 @@ impl HolochainError {
 
 impl fmt::Display for HolochainError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.description())
+        match self {
+            // the description of an abort only holds its message, show where it happened too
+            ZomeAborted(abort) => write!(f, "{}", abort),
+            _ => write!(f, "{}", self.description()),
+        }
     }
 }
 
@@ -127,6 +160,11 @@ impl Error for HolochainError {
             ValidationFailed(fail_msg) => &fail_msg,
             Ribosome(err_code) => err_code.as_str(),
             RibosomeFailed(fail_msg) => &fail_msg,
+            ExecutionLimitExceeded(limit_msg) => &limit_msg,
+            ZomeAborted(abort) => &abort.message,
+            NetworkError(err_msg) => &err_msg,
+            Timeout => "timeout",
+            WorkerPoolBusy(err_msg) => &err_msg,
         }
     }
 }
@@ -267,6 +305,14 @@ mod tests {
                 HolochainError::Dna(DnaError::ZomeFunctionNotFound(String::from("foo"))),
                 "foo",
             ),
+            (
+                HolochainError::Dna(DnaError::ZomeFunctionInputInvalid(String::from("foo"))),
+                "foo",
+            ),
+            (
+                HolochainError::Dna(DnaError::ZomeFunctionOutputInvalid(String::from("foo"))),
+                "foo",
+            ),
             (HolochainError::IoError(String::from("foo")), "foo"),
             (
                 HolochainError::SerializationError(String::from("foo")),
@@ -280,11 +326,38 @@ mod tests {
                 HolochainError::DoesNotHaveCapabilityToken,
                 "Caller does not have Capability to make that call",
             ),
+            (
+                HolochainError::ExecutionLimitExceeded(String::from("foo")),
+                "foo",
+            ),
+            (
+                HolochainError::ZomeAborted(ZomeAbort {
+                    message: String::from("foo"),
+                    filename: String::from("bar.ts"),
+                    line: 1,
+                    column: 2,
+                }),
+                "foo",
+            ),
+            (HolochainError::NetworkError(String::from("foo")), "foo"),
+            (HolochainError::Timeout, "timeout"),
+            (HolochainError::WorkerPoolBusy(String::from("foo")), "foo"),
         ] {
             assert_eq!(output, input.description());
         }
     }
 
+    #[test]
+    fn zome_aborted_to_string() {
+        let error = HolochainError::ZomeAborted(ZomeAbort {
+            message: String::from("foo"),
+            filename: String::from("bar.ts"),
+            line: 1,
+            column: 2,
+        });
+        assert_eq!("Zome aborted: foo\n  --> bar.ts:1:2\n", error.to_string());
+    }
+
     #[test]
     fn core_error_to_string() {
         let error =
@@ -6,28 +6,28 @@ use std::{convert::TryFrom, str::FromStr};
 
 /// Enum of all possible RETURN codes that a Zome API Function could return.
 /// Represents an encoded allocation of zero length with the return code as offset.
-/// @see SinglePageAllocation
-#[repr(u32)]
+/// @see WasmAllocation
+#[repr(u64)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum RibosomeReturnCode {
     Success,
     Failure(RibosomeErrorCode),
 }
 
-impl From<RibosomeReturnCode> for i32 {
-    fn from(ribosome_return_code: RibosomeReturnCode) -> i32 {
+impl From<RibosomeReturnCode> for i64 {
+    fn from(ribosome_return_code: RibosomeReturnCode) -> i64 {
         match ribosome_return_code {
             RibosomeReturnCode::Success => 0,
-            RibosomeReturnCode::Failure(code) => code as i32,
+            RibosomeReturnCode::Failure(code) => code as i64,
         }
     }
 }
 
-impl From<RibosomeReturnCode> for u32 {
-    fn from(ribosome_return_code: RibosomeReturnCode) -> u32 {
+impl From<RibosomeReturnCode> for u64 {
+    fn from(ribosome_return_code: RibosomeReturnCode) -> u64 {
         match ribosome_return_code {
             RibosomeReturnCode::Success => 0,
-            RibosomeReturnCode::Failure(code) => code as i32 as u32,
+            RibosomeReturnCode::Failure(code) => code as u64,
         }
     }
 }
@@ -71,7 +71,7 @@ impl RibosomeReturnCode {
         Failure(err_code)
     }
 
-    pub fn from_offset(offset: u16) -> Self {
+    pub fn from_offset(offset: u32) -> Self {
         match offset {
             0 => Success,
             _ => Failure(RibosomeErrorCode::from_offset(offset)),
@@ -80,20 +80,21 @@ impl RibosomeReturnCode {
 }
 
 /// Enum of all possible ERROR codes that a Zome API Function could return.
-#[repr(u32)]
+/// Each code is an encoded allocation with the code as offset and a length of zero.
+#[repr(u64)]
 #[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultJson)]
 #[cfg_attr(rustfmt, rustfmt_skip)]
 pub enum RibosomeErrorCode {
-    Unspecified                     = 1 << 16,
-    ArgumentDeserializationFailed   = 2 << 16,
-    OutOfMemory                     = 3 << 16,
-    ReceivedWrongActionResult       = 4 << 16,
-    CallbackFailed                  = 5 << 16,
-    RecursiveCallForbidden          = 6 << 16,
-    ResponseSerializationFailed     = 7 << 16,
-    NotAnAllocation                 = 8 << 16,
-    ZeroSizedAllocation             = 9 << 16,
-    UnknownEntryType                = 10 << 16,
+    Unspecified                     = 1 << 32,
+    ArgumentDeserializationFailed   = 2 << 32,
+    OutOfMemory                     = 3 << 32,
+    ReceivedWrongActionResult       = 4 << 32,
+    CallbackFailed                  = 5 << 32,
+    RecursiveCallForbidden          = 6 << 32,
+    ResponseSerializationFailed     = 7 << 32,
+    NotAnAllocation                 = 8 << 32,
+    ZeroSizedAllocation             = 9 << 32,
+    UnknownEntryType                = 10 << 32,
 }
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
@@ -127,7 +128,7 @@ impl From<RibosomeErrorCode> for String {
 }
 
 impl RibosomeErrorCode {
-    pub fn from_offset(offset: u16) -> Self {
+    pub fn from_offset(offset: u32) -> Self {
         match offset {
             0 => unreachable!(),
             2 => ArgumentDeserializationFailed,
@@ -202,7 +203,7 @@ pub mod tests {
     #[test]
     fn ribosome_return_code_round_trip() {
         let oom =
-            RibosomeReturnCode::from_offset(((RibosomeErrorCode::OutOfMemory as u32) >> 16) as u16);
+            RibosomeReturnCode::from_offset(((RibosomeErrorCode::OutOfMemory as u64) >> 32) as u32);
         assert_eq!(Failure(RibosomeErrorCode::OutOfMemory), oom);
         assert_eq!(RibosomeErrorCode::OutOfMemory.to_string(), oom.to_string());
     }
@@ -210,7 +211,7 @@ pub mod tests {
     #[test]
     fn ribosome_error_code_round_trip() {
         let oom =
-            RibosomeErrorCode::from_offset(((RibosomeErrorCode::OutOfMemory as u32) >> 16) as u16);
+            RibosomeErrorCode::from_offset(((RibosomeErrorCode::OutOfMemory as u64) >> 32) as u32);
         assert_eq!(RibosomeErrorCode::OutOfMemory, oom);
         assert_eq!(RibosomeErrorCode::OutOfMemory.to_string(), oom.to_string());
     }
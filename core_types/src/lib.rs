@@ -23,9 +23,11 @@ extern crate holochain_core_types_derive;
 
 #[macro_use]
 extern crate serde_derive;
+pub mod cap_grant;
 pub mod cas;
 pub mod chain_header;
 pub mod crud_status;
+pub mod delete_entry;
 pub mod eav;
 pub mod entry;
 pub mod entry_type;
@@ -26,11 +26,25 @@ impl ValidationPackage {
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, DefaultJson)]
 pub enum ValidationPackageDefinition {
-    Entry,          //sending only the entry
-    ChainEntries,   //sending all (public?) source chain entries
-    ChainHeaders,   //sending all source chain headers
-    ChainFull,      //sending the whole chain, entries and headers
-    Custom(String), //sending something custom
+    Entry,                    //sending only the entry
+    ChainEntries,             //sending all (public?) source chain entries
+    ChainHeaders,             //sending all source chain headers
+    ChainFull,                //sending the whole chain, entries and headers
+    Custom(String),           //sending something custom
+    Selection(ChainSelector), //sending the selected part of the chain, entries and headers
+}
+
+/// The part of the source chain a zome wants in the validation packages of an entry type.
+/// Only public entries and their headers get selected.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum ChainSelector {
+    /// All entries of the given entry types
+    EntryTypes(Vec<String>),
+    /// The latest entries, up to the given number
+    LastEntries(usize),
+    /// The entries between the headers with the given addresses, both included,
+    /// `from` being the older one
+    HeaderRange { from: HashString, to: HashString },
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -39,6 +53,31 @@ pub struct ValidationData {
     pub sources: Vec<HashString>,
     pub lifecycle: EntryLifecycle,
     pub action: EntryAction,
+    /// The bundle the entry gets committed with, if any
+    #[serde(default)]
+    pub bundle: Option<ValidationBundle>,
+    /// The entry that gets replaced by a Modify or removed by a Delete action
+    #[serde(default)]
+    pub old_entry: Option<SerializedEntry>,
+    /// The header of the old entry, if it is on the source chain of this agent
+    #[serde(default)]
+    pub old_entry_header: Option<ChainHeader>,
+    /// The membrane proof the agent joined with, when validating its AgentId
+    #[serde(default)]
+    pub membrane_proof: Option<String>,
+    /// Whether a link gets added or removed, when validating a link entry
+    #[serde(default)]
+    pub link_action: Option<LinkAction>,
+}
+
+/// Entries that are committed together in one bundle,
+/// so validation can check them as a whole
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ValidationBundle {
+    /// All entries of the bundle in the order they were committed
+    pub entries: Vec<SerializedEntry>,
+    /// The user_param the bundle was started with
+    pub user_param: serde_json::Value,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
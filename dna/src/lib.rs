@@ -313,7 +313,9 @@ pub mod tests {
                     "test": {
                         "description": "test",
                         "config": {
-                            "error_handling": "throw-errors"
+                            "error_handling": "throw-errors",
+                            "fuel_limit": null,
+                            "timeout_ms": null
                         },
                         "entry_types": {
                             "test": {
@@ -339,7 +341,8 @@ pub mod tests {
                                         "inputs": [],
                                         "outputs": []
                                     }
-                                ]
+                                ],
+                                "check_outputs": false
                             }
                         },
                         "code": {
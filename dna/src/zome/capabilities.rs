@@ -1,5 +1,7 @@
 //! File holding all the structs for handling capabilities defined in DNA.
 
+use holochain_core_types::error::DnaError;
+use serde_json::{self, Map, Value};
 use std::str::FromStr;
 
 //--------------------------------------------------------------------------------------------------
@@ -50,12 +52,16 @@ impl ReservedCapabilityNames {
 /// Enum for Zome Capability "membrane" property.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
 pub enum Membrane {
+    /// Callable by anyone, including other agents
     #[serde(rename = "public")]
     Public,
+    /// Callable by the agent running the instance, its clients and its zomes
     #[serde(rename = "agent")]
     Agent,
+    /// Callable by callers holding an api key
     #[serde(rename = "api-key")]
     ApiKey,
+    /// Callable by the zomes of the same DNA only
     #[serde(rename = "zome")]
     Zome,
 }
@@ -106,6 +112,53 @@ impl FnParameter {
             parameter_type: t.into(),
         }
     }
+
+    /// Checks a JSON value against the type of the parameter.
+    /// Types can be JSON type names ("string", "number", "boolean", "array", "object")
+    /// or the Rust types written in define_zome!. Types that can't be mapped to JSON,
+    /// like structs or JsonString, accept any value.
+    pub fn accepts(&self, value: &Value) -> bool {
+        let parameter_type: String = self
+            .parameter_type
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        type_accepts(&parameter_type, value)
+    }
+}
+
+fn type_accepts(parameter_type: &str, value: &Value) -> bool {
+    if let Some(inner) = generic_argument(parameter_type, "Option") {
+        return value.is_null() || type_accepts(inner, value);
+    }
+    if let Some(inner) = generic_argument(parameter_type, "Vec") {
+        return value
+            .as_array()
+            .map(|items| items.iter().all(|item| type_accepts(inner, item)))
+            .unwrap_or(false);
+    }
+    match parameter_type.rsplit("::").next().unwrap_or(parameter_type) {
+        "string" | "String" | "&str" | "Address" | "HashString" => value.is_string(),
+        "boolean" | "bool" => value.is_boolean(),
+        "number" | "f32" | "f64" => value.is_number(),
+        "u8" | "u16" | "u32" | "u64" | "usize" => value.is_u64(),
+        "i8" | "i16" | "i32" | "i64" | "isize" => value.is_i64(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+/// "T" for a type "Generic<T>"
+fn generic_argument<'a>(parameter_type: &'a str, generic: &str) -> Option<&'a str> {
+    if parameter_type.starts_with(generic)
+        && parameter_type[generic.len()..].starts_with('<')
+        && parameter_type.ends_with('>')
+    {
+        Some(&parameter_type[generic.len() + 1..parameter_type.len() - 1])
+    } else {
+        None
+    }
 }
 
 /// Represents a zome "fn_declarations" object.
@@ -134,6 +187,59 @@ impl FnDeclaration {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Checks the JSON parameters of a call against the declared inputs.
+    /// The parameters have to be a JSON object holding a value of the declared type
+    /// for every input, only optional inputs can be left out.
+    /// Functions without declared inputs accept any parameters.
+    pub fn check_inputs(&self, parameters: &str) -> Result<(), DnaError> {
+        if self.inputs.is_empty() {
+            return Ok(());
+        }
+        let parameters: Map<String, Value> = serde_json::from_str(parameters).map_err(|_| {
+            DnaError::ZomeFunctionInputInvalid(format!(
+                "Parameters of zome function '{}' are not a JSON object",
+                self.name
+            ))
+        })?;
+        for input in self.inputs.iter() {
+            let value = parameters.get(&input.name).unwrap_or(&Value::Null);
+            if !input.accepts(value) {
+                return Err(DnaError::ZomeFunctionInputInvalid(
+                    match parameters.get(&input.name) {
+                        None => format!(
+                            "Input '{}' of zome function '{}' is missing",
+                            input.name, self.name
+                        ),
+                        Some(_) => format!(
+                            "Input '{}' of zome function '{}' is not of type '{}'",
+                            input.name, self.name, input.parameter_type
+                        ),
+                    },
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the JSON result of a call against the declared output.
+    /// Only functions that declare exactly one output get checked.
+    /// Zome calls only check their results for capabilities that enable `check_outputs`.
+    pub fn check_output(&self, result: &str) -> Result<(), DnaError> {
+        if self.outputs.len() != 1 {
+            return Ok(());
+        }
+        let output = &self.outputs[0];
+        let value: Value = serde_json::from_str(result).unwrap_or(Value::Null);
+        if output.accepts(&value) {
+            Ok(())
+        } else {
+            Err(DnaError::ZomeFunctionOutputInvalid(format!(
+                "Output '{}' of zome function '{}' is not of type '{}'",
+                output.name, self.name, output.parameter_type
+            )))
+        }
+    }
 }
 
 /// Represents an individual object in the "zome" "capabilities" array.
@@ -146,6 +252,10 @@ pub struct Capability {
     /// "fn_declarations" array
     #[serde(default)]
     pub functions: Vec<FnDeclaration>,
+
+    /// Whether the results of the functions get checked against their declared outputs
+    #[serde(default)]
+    pub check_outputs: bool,
 }
 
 impl Default for Capability {
@@ -154,6 +264,7 @@ impl Default for Capability {
         Capability {
             cap_type: CapabilityType::new(),
             functions: Vec::new(),
+            check_outputs: false,
         }
     }
 }
@@ -235,4 +346,85 @@ mod tests {
 
         assert_eq!(fixture, cap);
     }
+
+    fn test_fn_declaration() -> FnDeclaration {
+        let mut fn_dec = FnDeclaration::new();
+        fn_dec.name = String::from("test");
+        fn_dec.inputs = vec![
+            FnParameter::new("post", "String"),
+            FnParameter::new("count", "u32"),
+            // as stringified by define_zome!
+            FnParameter::new("tags", "Option < Vec < String > >"),
+            FnParameter::new("data", "serde_json::Value"),
+        ];
+        fn_dec.outputs = vec![FnParameter::new("hash", "Address")];
+        fn_dec
+    }
+
+    #[test]
+    fn test_fn_parameter_accepts() {
+        for (parameter_type, accepted, rejected) in vec![
+            ("string", r#""foo""#, "1"),
+            ("HashString", r#""foo""#, "null"),
+            ("u32", "1", "-1"),
+            ("i64", "-1", "1.5"),
+            ("f64", "1.5", r#""1""#),
+            ("bool", "true", "0"),
+            ("Vec<u8>", "[1, 2]", r#"[1, "2"]"#),
+            ("Option<String>", "null", "1"),
+            ("object", "{}", "[]"),
+        ] {
+            let parameter = FnParameter::new("test", parameter_type);
+            assert!(parameter.accepts(&serde_json::from_str(accepted).unwrap()));
+            assert!(!parameter.accepts(&serde_json::from_str(rejected).unwrap()));
+        }
+        assert!(FnParameter::new("test", "MyStruct").accepts(&Value::Null));
+    }
+
+    #[test]
+    fn test_check_inputs() {
+        let fn_dec = test_fn_declaration();
+
+        assert_eq!(
+            Ok(()),
+            fn_dec.check_inputs(r#"{"post": "foo", "count": 2, "data": {}}"#)
+        );
+        assert_eq!(
+            Ok(()),
+            fn_dec.check_inputs(r#"{"post": "foo", "count": 2, "tags": ["bar"], "data": 1}"#)
+        );
+        assert_eq!(
+            Err(DnaError::ZomeFunctionInputInvalid(String::from(
+                "Input 'count' of zome function 'test' is missing"
+            ))),
+            fn_dec.check_inputs(r#"{"post": "foo"}"#)
+        );
+        assert_eq!(
+            Err(DnaError::ZomeFunctionInputInvalid(String::from(
+                "Input 'post' of zome function 'test' is not of type 'String'"
+            ))),
+            fn_dec.check_inputs(r#"{"post": 1, "count": 2}"#)
+        );
+        assert_eq!(
+            Err(DnaError::ZomeFunctionInputInvalid(String::from(
+                "Parameters of zome function 'test' are not a JSON object"
+            ))),
+            fn_dec.check_inputs("foo")
+        );
+        assert_eq!(Ok(()), FnDeclaration::new().check_inputs("foo"));
+    }
+
+    #[test]
+    fn test_check_output() {
+        let fn_dec = test_fn_declaration();
+
+        assert_eq!(Ok(()), fn_dec.check_output(r#""QmHash""#));
+        assert_eq!(
+            Err(DnaError::ZomeFunctionOutputInvalid(String::from(
+                "Output 'hash' of zome function 'test' is not of type 'Address'"
+            ))),
+            fn_dec.check_output(r#"{"Ok": "QmHash"}"#)
+        );
+        assert_eq!(Ok(()), FnDeclaration::new().check_output("foo"));
+    }
 }
@@ -26,6 +26,17 @@ pub struct Config {
     /// How errors should be handled within this zome.
     #[serde(default)]
     pub error_handling: ErrorHandling,
+
+    /// Maximum amount of fuel a single call into this zome may burn.
+    /// Every executed WASM instruction costs one unit of fuel.
+    /// No limit if not set.
+    #[serde(default)]
+    pub fuel_limit: Option<u64>,
+
+    /// Maximum wall-clock time in milliseconds a single call into this zome may run.
+    /// No limit if not set.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 impl Default for Config {
@@ -33,6 +44,8 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             error_handling: ErrorHandling::ThrowErrors,
+            fuel_limit: None,
+            timeout_ms: None,
         }
     }
 }
@@ -133,4 +146,23 @@ pub mod tests {
 
         assert_eq!(fixture, zome);
     }
+
+    #[test]
+    fn can_set_execution_limits() {
+        let fixture: Config = serde_json::from_str(
+            r#"{
+                "error_handling": "throw-errors",
+                "fuel_limit": 1000000,
+                "timeout_ms": 500
+            }"#,
+        ).unwrap();
+
+        let mut config = Config::new();
+        assert_eq!(None, config.fuel_limit);
+        assert_eq!(None, config.timeout_ms);
+        config.fuel_limit = Some(1_000_000);
+        config.timeout_ms = Some(500);
+
+        assert_eq!(fixture, config);
+    }
 }
@@ -1,15 +1,23 @@
 use error::{ZomeApiError, ZomeApiResult};
 use globals::*;
+pub use holochain_core_types::crud_status::CrudStatus as EntryStatus;
 use holochain_core_types::{
     cas::content::Address,
-    entry::{Entry, SerializedEntry},
+    entry::Entry,
     error::{CoreError, HolochainError, RibosomeReturnCode, ZomeApiInternalResult},
 };
-pub use holochain_wasm_utils::api_serialization::validation::*;
+pub use holochain_wasm_utils::api_serialization::{
+    bundle::BundleOnClose,
+    get_entry::{EntryMeta, GetEntryOptions, GetEntryResult},
+    get_links::{GetLinksOptions, GetLinksResult, LinkResult},
+    send::SendOptions,
+    validation::*,
+};
 use holochain_wasm_utils::{
     api_serialization::{
-        get_entry::GetEntryOptions, get_links::GetLinksArgs, link_entries::LinkEntriesArgs,
-        QueryArgs, QueryResult, ZomeFnCallArgs,
+        bundle::StartBundleArgs, cap_grant::GrantCapabilityArgs, get_entry::GetEntryArgs,
+        get_links::GetLinksArgs, link_entries::LinkEntriesArgs, property::PropertyArgs,
+        send::SendArgs, update_entry::UpdateEntryArgs, QueryArgs, QueryResult, ZomeFnCallArgs,
     },
     holochain_core_types::{
         hash::HashString,
@@ -51,6 +59,10 @@ lazy_static! {
   /// Starts with the same value as AGENT_INITIAL_HASH.
   /// After a call to `update_agent` it will have the value of the hash of the newly committed identity entry.
   pub static ref AGENT_LATEST_HASH: &'static HashString = &GLOBALS.agent_latest_hash;
+
+  /// The membrane proof, e.g. an invite code, the agent joined the network with.
+  /// It is committed right after the identity entry and is None if the agent brought none.
+  pub static ref MEMBRANE_PROOF: &'static Option<String> = &GLOBALS.membrane_proof;
 }
 
 impl From<DNA_NAME> for JsonString {
@@ -88,32 +100,6 @@ impl From<AGENT_LATEST_HASH> for JsonString {
 // SYSTEM CONSTS
 //--------------------------------------------------------------------------------------------------
 
-// HC.Status
-// WARNING keep in sync with CRUDStatus
-bitflags! {
-  pub struct EntryStatus: u8 {
-    const LIVE     = 1 << 0;
-    const REJECTED = 1 << 1;
-    const DELETED  = 1 << 2;
-    const MODIFIED = 1 << 3;
-  }
-}
-
-// HC.GetMask
-bitflags! {
-  pub struct GetEntryMask: u8 {
-    const ENTRY      = 1 << 0;
-    const ENTRY_TYPE = 1 << 1;
-    const SOURCES    = 1 << 2;
-  }
-}
-// explicit `Default` implementation
-impl Default for GetEntryMask {
-    fn default() -> GetEntryMask {
-        GetEntryMask::ENTRY
-    }
-}
-
 // TODOs
 //// HC.LinkAction
 //pub enum LinkAction {
@@ -165,12 +151,6 @@ impl Default for GetEntryMask {
 //    }
 //}
 
-// Allowed input for close_bundle()
-pub enum BundleOnClose {
-    Commit,
-    Discard,
-}
-
 //--------------------------------------------------------------------------------------------------
 // API FUNCTIONS
 //--------------------------------------------------------------------------------------------------
@@ -323,7 +303,7 @@ pub fn call<S: Into<String>>(
     fn_name: S,
     fn_args: JsonString,
 ) -> ZomeApiResult<JsonString> {
-    let mut mem_stack: SinglePageStack;
+    let mut mem_stack: WasmStack;
     unsafe {
         mem_stack = G_MEM_STACK.unwrap();
     }
@@ -340,12 +320,12 @@ pub fn call<S: Into<String>>(
     )?;
 
     // Call WASMI-able commit
-    let encoded_allocation_of_result: u32;
+    let encoded_allocation_of_result: u64;
     unsafe {
-        encoded_allocation_of_result = hc_call(allocation_of_input.encode() as u32);
+        encoded_allocation_of_result = hc_call(allocation_of_input.encode());
     }
     // Deserialize complex result stored in memory and check for ERROR in encoding
-    let result = load_string(encoded_allocation_of_result as u32)?;
+    let result = load_string(encoded_allocation_of_result)?;
 
     // Free result & input allocations.
     mem_stack
@@ -399,7 +379,7 @@ pub fn call<S: Into<String>>(
 /// # }
 /// ```
 pub fn commit_entry(entry: &Entry) -> ZomeApiResult<Address> {
-    let mut mem_stack: SinglePageStack;
+    let mut mem_stack: WasmStack;
     unsafe {
         mem_stack = G_MEM_STACK.unwrap();
     }
@@ -407,13 +387,13 @@ pub fn commit_entry(entry: &Entry) -> ZomeApiResult<Address> {
     let allocation_of_input = store_as_json(&mut mem_stack, entry.serialize())?;
 
     // Call Ribosome's commit_entry()
-    let encoded_allocation_of_result: u32;
+    let encoded_allocation_of_result: u64;
     unsafe {
-        encoded_allocation_of_result = hc_commit_entry(allocation_of_input.encode() as u32);
+        encoded_allocation_of_result = hc_commit_entry(allocation_of_input.encode());
     }
 
     // Deserialize complex result stored in wasm memory
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
     // Free result & input allocations
     mem_stack
         .deallocate(allocation_of_input)
@@ -448,30 +428,57 @@ pub fn commit_entry(entry: &Entry) -> ZomeApiResult<Address> {
 /// # }
 /// ```
 pub fn get_entry(address: Address) -> ZomeApiResult<Option<Entry>> {
-    Ok(get_entry_result(address, GetEntryOptions {})?
+    Ok(get_entry_result(address, GetEntryOptions::default())?
+        .entry
         .and_then(|serialized_entry| Some(serialized_entry.deserialize())))
 }
 
 /// Retrieves an entry and meta data from the local chain or the DHT, by looking it up using
-/// its address, and a the full options to specify exactly what data to return
+/// its address, and a the full options to specify exactly what data to return.
+/// The options select which CRUD statuses are acceptable, whether the update chain
+/// of modified entries gets followed, and whether the header, sources and CRUD meta data
+/// of the entry are included in the result.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::cas::content::Address;
+/// # use hdk::{EntryStatus, GetEntryOptions};
+/// # fn main() {
+/// pub fn handle_get_post_history(post_address: Address) -> JsonString {
+///     // get exactly this version of the post, even if it has been updated or removed since
+///     let options = GetEntryOptions {
+///         status_mask: EntryStatus::all(),
+///         follow_updates: false,
+///         include_meta: true,
+///         ..Default::default()
+///     };
+///     match hdk::get_entry_result(post_address, options) {
+///         Ok(result) => result.into(),
+///         Err(e) => e.into(),
+///     }
+/// }
+/// # }
+/// ```
 pub fn get_entry_result(
     address: Address,
-    _options: GetEntryOptions,
-) -> ZomeApiResult<Option<SerializedEntry>> {
-    let mut mem_stack: SinglePageStack;
+    options: GetEntryOptions,
+) -> ZomeApiResult<GetEntryResult> {
+    let mut mem_stack: WasmStack;
     unsafe {
         mem_stack = G_MEM_STACK.unwrap();
     }
     // Put args in struct and serialize into memory
-    let allocation_of_input = store_as_json(&mut mem_stack, address)?;
+    let allocation_of_input = store_as_json(&mut mem_stack, GetEntryArgs { address, options })?;
 
     // Call WASMI-able get_entry
-    let encoded_allocation_of_result: u32;
+    let encoded_allocation_of_result: u64;
     unsafe {
-        encoded_allocation_of_result = hc_get_entry(allocation_of_input.encode() as u32);
+        encoded_allocation_of_result = hc_get_entry(allocation_of_input.encode());
     }
     // Deserialize complex result stored in memory
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
     // Free result & input allocations
     mem_stack
         .deallocate(allocation_of_input)
@@ -487,6 +494,9 @@ pub fn get_entry_result(
 /// Consumes three values, two of which are the addresses of entries, and one of which is a string that defines a
 /// relationship between them, called a `tag`. Later, lists of entries can be looked up by using [get_links](fn.get_links.html). Entries
 /// can only be looked up in the direction from the `base`, which is the first argument, to the `target`.
+/// The link has to be declared with the [link](macro.link.html) macro by the entry type of the `base`
+/// or of the `target`, and its validation callback has to accept it.
+/// The link gets committed to the source chain before it is added to the DHT.
 /// # Examples
 /// ```rust
 /// # #![feature(try_from)]
@@ -550,11 +560,11 @@ pub fn link_entries<S: Into<String>>(
         },
     )?;
 
-    let encoded_allocation_of_result: u32 =
-        unsafe { hc_link_entries(allocation_of_input.encode() as u32) };
+    let encoded_allocation_of_result: u64 =
+        unsafe { hc_link_entries(allocation_of_input.encode()) };
 
     // Deserialize complex result stored in memory and check for ERROR in encoding
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
     // Free result & input allocations
     mem_stack
         .deallocate(allocation_of_input)
@@ -567,13 +577,106 @@ pub fn link_entries<S: Into<String>>(
     }
 }
 
-/// Not Yet Available
-// Returns a DNA property, which are defined by the DNA developer.
-// They are custom values that are defined in the DNA file
-// that can be used in the zome code for defining configurable behaviors.
-// (e.g. Name, Language, Description, Author, etc.).
-pub fn property<S: Into<String>>(_name: S) -> ZomeApiResult<String> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Removes a link that was added with [link_entries](fn.link_entries.html).
+/// The removal is committed to the source chain as a link entry with the `DELETE` action
+/// and the link is retracted in the DHT, so [get_links](fn.get_links.html) no longer returns it.
+/// The link itself is never erased, which keeps the history of links auditable.
+/// Removing a link that does not exist, or that has already been removed, returns an error.
+/// The removal is validated by the validators of the link, which see `LinkAction::Delete`
+/// as the `link_action` of their validation data.
+/// A removed link can be added again with [link_entries](fn.link_entries.html).
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::cas::content::Address;
+/// # use hdk::AGENT_ADDRESS;
+/// # fn main() {
+///
+/// pub fn handle_unlink_post(post_address: Address) -> JsonString {
+///     match hdk::remove_link(&AGENT_ADDRESS, &post_address, "authored_posts") {
+///         Ok(()) => post_address.into(),
+///         Err(hdk_error) => hdk_error.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn remove_link<S: Into<String>>(
+    base: &HashString,
+    target: &HashString,
+    tag: S,
+) -> ZomeApiResult<()> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        LinkEntriesArgs {
+            base: base.clone(),
+            target: target.clone(),
+            tag: tag.into(),
+        },
+    )?;
+
+    let encoded_allocation_of_result: u64 = unsafe { hc_remove_link(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
+/// Returns a DNA property, which are defined by the DNA developer.
+/// They are custom values that are defined in the `properties` of the DNA file
+/// that can be used in the zome code for defining configurable behaviors.
+/// (e.g. Name, Language, Description, Author, etc.).
+/// `name` is either a top level key of the properties,
+/// or a JSON pointer into them if it starts with a `/` (e.g. `/limits/max_posts`).
+/// The property is returned as JSON, and it is an error if the DNA does not define it.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # fn main() {
+///
+/// pub fn handle_get_language() -> JsonString {
+///     match hdk::property("language") {
+///         Ok(language) => language,
+///         Err(hdk_error) => hdk_error.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn property<S: Into<String>>(name: S) -> ZomeApiResult<JsonString> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(&mut mem_stack, PropertyArgs { name: name.into() })?;
+
+    let encoded_allocation_of_result: u64 = unsafe { hc_property(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value))
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
 /// Reconstructs an address of the given entry data.
@@ -619,20 +722,20 @@ pub fn property<S: Into<String>>(_name: S) -> ZomeApiResult<String> {
 /// # }
 /// ```
 pub fn hash_entry(entry: &Entry) -> ZomeApiResult<Address> {
-    let mut mem_stack: SinglePageStack;
+    let mut mem_stack: WasmStack;
     unsafe {
         mem_stack = G_MEM_STACK.unwrap();
     }
     // Put args in struct and serialize into memory
     let allocation_of_input = store_as_json(&mut mem_stack, entry.serialize())?;
 
-    let encoded_allocation_of_result: u32;
+    let encoded_allocation_of_result: u64;
     unsafe {
-        encoded_allocation_of_result = hc_hash_entry(allocation_of_input.encode() as u32);
+        encoded_allocation_of_result = hc_hash_entry(allocation_of_input.encode());
     }
 
     // Deserialize complex result stored in memory and check for ERROR in encoding
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
     // Free result & input allocations
     mem_stack
         .deallocate(allocation_of_input)
@@ -659,14 +762,83 @@ pub fn verify_signature<S: Into<String>>(
     Err(ZomeApiError::FunctionNotImplemented)
 }
 
-/// Not Yet Available
-pub fn update_entry<S: Into<String>>(
-    _entry_type: S,
-    _entry: serde_json::Value,
-    _replaces: HashString,
-) -> ZomeApiResult<HashString> {
-    // FIXME
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Commits a new version of the entry found at `address` to the local source chain.
+/// The new version has to be of the same entry type and will have to pass its validation rules.
+/// The old version gets marked as modified in the DHT so that [get_entry](fn.get_entry.html)
+/// on any of the previous addresses will return the latest version.
+/// Returns either the address of the new version, or an error.
+/// # Examples
+/// ```rust
+/// # #![feature(try_from)]
+/// # extern crate hdk;
+/// # extern crate serde_json;
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// # extern crate holochain_core_types;
+/// # #[macro_use]
+/// # extern crate holochain_core_types_derive;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::error::HolochainError;
+/// # use holochain_core_types::entry_type::EntryType;
+/// # use holochain_core_types::entry::Entry;
+/// # use holochain_core_types::cas::content::Address;
+/// # fn main() {
+///
+/// #[derive(Serialize, Deserialize, Debug, DefaultJson)]
+/// pub struct Post {
+///     content: String,
+///     date_created: String,
+/// }
+///
+/// pub fn handle_update_post(post_address: Address, new_content: String) -> JsonString {
+///
+///     let new_post_entry = Entry::new(EntryType::App("post".into()), Post{
+///         content: new_content,
+///         date_created: "now".into(),
+///     });
+///
+///     match hdk::update_entry(&new_post_entry, &post_address) {
+///         Ok(new_address) => new_address.into(),
+///         Err(e) => e.into(),
+///     }
+///
+/// }
+///
+/// # }
+/// ```
+pub fn update_entry(new_entry: &Entry, address: &Address) -> ZomeApiResult<Address> {
+    let mut mem_stack: WasmStack;
+    unsafe {
+        mem_stack = G_MEM_STACK.unwrap();
+    }
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        UpdateEntryArgs {
+            new_entry: new_entry.serialize(),
+            address: address.clone(),
+        },
+    )?;
+
+    // Call Ribosome's update_entry()
+    let encoded_allocation_of_result: u64;
+    unsafe {
+        encoded_allocation_of_result = hc_update_entry(allocation_of_input.encode());
+    }
+
+    // Deserialize complex result stored in wasm memory
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
 /// Not Yet Available
@@ -674,15 +846,57 @@ pub fn update_agent() -> ZomeApiResult<HashString> {
     Err(ZomeApiError::FunctionNotImplemented)
 }
 
-/// Not Yet Available
-pub fn remove_entry<S: Into<String>>(_entry: HashString, _message: S) -> ZomeApiResult<HashString> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Removes the entry found at `address` by committing a deletion entry to the local source chain.
+/// The removal has to pass the validation rules of the removed entry's type.
+/// Afterwards the entry, and all of its previous versions, can not be retrieved with
+/// [get_entry](fn.get_entry.html) anymore.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::cas::content::Address;
+/// # fn main() {
+/// pub fn handle_remove_post(post_address: Address) -> JsonString {
+///     match hdk::remove_entry(&post_address) {
+///         Ok(_) => JsonString::null(),
+///         Err(e) => e.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn remove_entry(address: &Address) -> ZomeApiResult<()> {
+    let mut mem_stack: WasmStack;
+    unsafe {
+        mem_stack = G_MEM_STACK.unwrap();
+    }
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(&mut mem_stack, address.clone())?;
+
+    let encoded_allocation_of_result: u64;
+    unsafe {
+        encoded_allocation_of_result = hc_remove_entry(allocation_of_input.encode());
+    }
+    // Deserialize complex result stored in memory
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
 /// Consumes two values, the first of which is the address of an entry, `base`, and the second of which is a string, `tag`,
 /// used to describe the relationship between the `base` and other entries you wish to lookup. Returns a list of addresses of other
 /// entries which matched as being linked by the given `tag`. Links are created in the first place using the Zome API function [link_entries](fn.link_entries.html).
 /// Once you have the addresses, there is a good likelihood that you will wish to call [get_entry](fn.get_entry.html) for each of them.
+/// Removed links are not returned. Use [get_links_result](fn.get_links_result.html) to page through
+/// bases with many links, to filter them or to get the target entries at the same time.
 /// # Examples
 /// ```rust
 /// # extern crate hdk;
@@ -700,6 +914,41 @@ pub fn remove_entry<S: Into<String>>(_entry: HashString, _message: S) -> ZomeApi
 /// # }
 /// ```
 pub fn get_links<S: Into<String>>(base: &HashString, tag: S) -> ZomeApiResult<Vec<Address>> {
+    Ok(get_links_result(base, tag, GetLinksOptions::default())?.addresses())
+}
+
+/// Retrieves a page of the links from `base` with the given `tag`, ordered by target address.
+/// The options limit the number of returned links, continue after the `next_cursor` of a
+/// previous page, filter the links by the entry type of their target and by their status,
+/// and can include the target entries in the result.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::cas::content::Address;
+/// # use hdk::GetLinksOptions;
+/// # fn main() {
+/// pub fn handle_recent_posts_by_agent(agent: Address, cursor: Option<Address>) -> JsonString {
+///     let options = GetLinksOptions {
+///         limit: Some(20),
+///         cursor,
+///         target_type: Some("post".into()),
+///         include_entries: true,
+///         ..Default::default()
+///     };
+///     match hdk::get_links_result(&agent, "authored_posts", options) {
+///         Ok(result) => result.into(),
+///         Err(hdk_error) => hdk_error.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn get_links_result<S: Into<String>>(
+    base: &HashString,
+    tag: S,
+    options: GetLinksOptions,
+) -> ZomeApiResult<GetLinksResult> {
     let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
     // Put args in struct and serialize into memory
 
@@ -708,15 +957,15 @@ pub fn get_links<S: Into<String>>(base: &HashString, tag: S) -> ZomeApiResult<Ve
         GetLinksArgs {
             entry_address: base.clone(),
             tag: tag.into(),
+            options,
         },
     )?;
 
     // Call Ribosome
-    let encoded_allocation_of_result: u32 =
-        unsafe { hc_get_links(allocation_of_input.encode() as u32) };
+    let encoded_allocation_of_result: u64 = unsafe { hc_get_links(allocation_of_input.encode()) };
 
     // Deserialize complex result stored in memory
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
 
     // Free result & input allocations
     mem_stack
@@ -734,7 +983,7 @@ pub fn get_links<S: Into<String>>(base: &HashString, tag: S) -> ZomeApiResult<Ve
 /// entry_type_name: Specify type of entry to retrieve
 /// limit: Max number of entries to retrieve
 pub fn query(entry_type_name: &str, limit: u32) -> ZomeApiResult<QueryResult> {
-    let mut mem_stack: SinglePageStack;
+    let mut mem_stack: WasmStack;
     unsafe {
         mem_stack = G_MEM_STACK.unwrap();
     }
@@ -747,12 +996,12 @@ pub fn query(entry_type_name: &str, limit: u32) -> ZomeApiResult<QueryResult> {
         },
     )?;
 
-    let encoded_allocation_of_result: u32;
+    let encoded_allocation_of_result: u64;
     unsafe {
-        encoded_allocation_of_result = hc_query(allocation_of_input.encode() as u32);
+        encoded_allocation_of_result = hc_query(allocation_of_input.encode());
     }
     // Deserialize complex result stored in memory
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
     // Free result & input allocations
     mem_stack
         .deallocate(allocation_of_input)
@@ -765,26 +1014,241 @@ pub fn query(entry_type_name: &str, limit: u32) -> ZomeApiResult<QueryResult> {
     }
 }
 
-/// Not Yet Available
-pub fn send(_to: HashString, _message: serde_json::Value) -> ZomeApiResult<serde_json::Value> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Sends a direct message to another agent of the same DNA and returns its response.
+/// The message is handled by the `receive` callback of the same Zome in the instance of the
+/// receiving agent, as defined in [define_zome](../macro.define_zome.html).
+/// Gives up on the response after the default timeout of a minute.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::cas::content::Address;
+/// # fn main() {
+/// pub fn handle_ping(agent: Address) -> JsonString {
+///     match hdk::send(agent, "ping".into()) {
+///         Ok(response) => response.into(),
+///         Err(hdk_error) => hdk_error.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn send(to_agent: Address, payload: String) -> ZomeApiResult<String> {
+    send_with_options(to_agent, payload, SendOptions::default())
 }
 
-/// Not Yet Available
-pub fn start_bundle(_timeout: usize, _user_param: serde_json::Value) -> ZomeApiResult<()> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Sends a direct message to another agent of the same DNA and returns its response,
+/// see [send](fn.send.html).
+/// The options set how long to wait for the response, sending fails with a timeout error
+/// if the receiving agent did not answer in time.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::cas::content::Address;
+/// # use hdk::SendOptions;
+/// # fn main() {
+/// pub fn handle_quick_ping(agent: Address) -> JsonString {
+///     match hdk::send_with_options(agent, "ping".into(), SendOptions { timeout_ms: 1000 }) {
+///         Ok(response) => response.into(),
+///         Err(hdk_error) => hdk_error.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn send_with_options(
+    to_agent: Address,
+    payload: String,
+    options: SendOptions,
+) -> ZomeApiResult<String> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        SendArgs {
+            to_agent,
+            payload,
+            options,
+        },
+    )?;
+
+    let encoded_allocation_of_result: u64 = unsafe { hc_send(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        let response: RawString = JsonString::from(result.value).try_into()?;
+        Ok(String::from(response))
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
-/// Not Yet Available
-pub fn close_bundle(_action: BundleOnClose) -> ZomeApiResult<()> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Starts a bundle: the entries committed after this call are only staged.
+/// They are validated together and added to the source chain when the bundle is closed with
+/// [close_bundle](fn.close_bundle.html), so either all of them get committed or none.
+/// The validation of every entry in the bundle gets to see all its entries and the `user_param`.
+/// Only [commit_entry](fn.commit_entry.html) can be used inside a bundle,
+/// other functions that change the source chain return an error until the bundle is closed.
+/// A bundle that is not closed within `timeout_ms` milliseconds or before the end of the zome call
+/// is thrown away.
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_json;
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::json::JsonString;
+/// # use holochain_core_types::entry::Entry;
+/// # use holochain_core_types::entry_type::EntryType;
+/// # use hdk::BundleOnClose;
+/// # fn main() {
+/// pub fn handle_create_pair(first: String, second: String) -> JsonString {
+///     let commit_pair = || {
+///         hdk::start_bundle(5000, json!({"reason": "pair"}))?;
+///         hdk::commit_entry(&Entry::new(EntryType::App("item".into()), json!(first)))?;
+///         hdk::commit_entry(&Entry::new(EntryType::App("item".into()), json!(second)))?;
+///         hdk::close_bundle(BundleOnClose::Commit)
+///     };
+///     match commit_pair() {
+///         Ok(()) => JsonString::null(),
+///         Err(hdk_error) => hdk_error.into(),
+///     }
+/// }
+/// # }
+/// ```
+pub fn start_bundle(timeout_ms: u64, user_param: serde_json::Value) -> ZomeApiResult<()> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        StartBundleArgs {
+            timeout_ms,
+            user_param,
+        },
+    )?;
+
+    let encoded_allocation_of_result: u64 =
+        unsafe { hc_start_bundle(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
+/// Closes the bundle opened with [start_bundle](fn.start_bundle.html).
+/// `BundleOnClose::Commit` validates all staged entries and commits them if they are all valid,
+/// otherwise none of them is committed and the validation error is returned.
+/// `BundleOnClose::Discard` throws the staged entries away.
+/// Closing a bundle that timed out returns a timeout error and commits nothing.
+pub fn close_bundle(action: BundleOnClose) -> ZomeApiResult<()> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(&mut mem_stack, action)?;
+
+    let encoded_allocation_of_result: u64 =
+        unsafe { hc_close_bundle(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
+/// Grants access to some functions of a capability of the calling zome to callers that can't
+/// pass the membrane of the capability, by committing a grant to the source chain of the agent.
+/// Returns the token callers have to present with their calls.
+/// If `assignees` is given, only those agents can use the token.
+pub fn grant_capability(
+    capability: &str,
+    functions: Vec<String>,
+    assignees: Option<Vec<Address>>,
+) -> ZomeApiResult<Address> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(
+        &mut mem_stack,
+        GrantCapabilityArgs {
+            capability: capability.to_string(),
+            functions,
+            assignees,
+        },
+    )?;
+
+    let encoded_allocation_of_result: u64 =
+        unsafe { hc_grant_capability(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
+}
+
+/// Revokes a grant made with [grant_capability](fn.grant_capability.html),
+/// calls presenting its token are rejected from then on.
+pub fn revoke_capability(token: Address) -> ZomeApiResult<()> {
+    let mut mem_stack = unsafe { G_MEM_STACK.unwrap() };
+
+    // Put args in struct and serialize into memory
+    let allocation_of_input = store_as_json(&mut mem_stack, token)?;
+
+    let encoded_allocation_of_result: u64 =
+        unsafe { hc_revoke_capability(allocation_of_input.encode()) };
+
+    // Deserialize complex result stored in memory and check for ERROR in encoding
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
+    // Free result & input allocations
+    mem_stack
+        .deallocate(allocation_of_input)
+        .expect("deallocate failed");
+    // Done
+    if result.ok {
+        Ok(JsonString::from(result.value).try_into()?)
+    } else {
+        Err(ZomeApiError::from(result.error))
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
 // Helpers
 //--------------------------------------------------------------------------------------------------
 
-pub fn check_for_ribosome_error(encoded_allocation: u32) -> Result<(), ZomeApiError> {
+pub fn check_for_ribosome_error(encoded_allocation: u64) -> Result<(), ZomeApiError> {
     // Check for error from Ribosome
     let rib_result = decode_encoded_allocation(encoded_allocation);
     match rib_result {
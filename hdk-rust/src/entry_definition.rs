@@ -1,7 +1,10 @@
 use holochain_dna::zome::entry_types::EntryTypeDef;
-use holochain_wasm_utils::holochain_core_types::{
-    hash::HashString,
-    validation::{ValidationData, ValidationPackageDefinition},
+use holochain_wasm_utils::{
+    api_serialization::validation::LinkDirection,
+    holochain_core_types::{
+        hash::HashString,
+        validation::{ValidationData, ValidationPackageDefinition},
+    },
 };
 use std::collections::HashMap;
 
@@ -15,7 +18,16 @@ pub struct ValidatingEntryType {
     pub entry_type_definition: EntryTypeDef,
     pub package_creator: PackageCreator,
     pub validator: Validator,
-    pub link_validators: HashMap<String, LinkValidator>,
+    /// Validators for the links declared by this entry type,
+    /// keyed by direction, the entry type on the other end and the tag of the link.
+    pub link_validators: HashMap<(LinkDirection, String, String), LinkValidator>,
+}
+
+pub struct ValidatingLinkDefinition {
+    pub direction: LinkDirection,
+    pub other_entry_type: String,
+    pub tag: String,
+    pub validator: LinkValidator,
 }
 
 /// The `entry` macro is a helper for creating `ValidatingEntryType` definitions
@@ -30,10 +42,15 @@ pub struct ValidatingEntryType {
 /// 5. validation_package: `validation_package` is a special identifier, which declares which data is required from peers
 ///      when attempting to validate entries of this type.
 ///      Possible values are found within [ValidationPackageDefinition](enum.ValidationPackageDefinition.html)
+///      `ValidationPackageDefinition::Selection` takes a [ChainSelector](enum.ChainSelector.html) to send only
+///      the entries of some types, the latest entries or a range of the chain, which keeps packages small on long chains.
 /// 6. validation: `validation` is a callback function which will be called any time that a
 ///      (DHT) node processes or stores this entry, triggered through actions such as [commit_entry](fn.commit_entry.html), [update_entry](fn.update_entry.html), [remove_entry](fn.remove_entry.html).
 ///      It always expects two arguments, the first of which is the entry attempting to be validated,
 ///      the second is the validation `context`, which offers a variety of metadata useful for validation.
+/// 7. links: `links` is an optional list of [link](macro.link.html) definitions.
+///      Only links that are declared here (or by the entry type on the other end) can be added
+///      with [link_entries](fn.link_entries.html).
 /// # Examples
 /// The following is a standalone Rust file that exports a function which can be called
 /// to get a `ValidatingEntryType` of a "post".
@@ -47,6 +64,7 @@ pub struct ValidatingEntryType {
 /// # use boolinator::*;
 /// # use hdk::entry_definition::ValidatingEntryType;
 /// # use hdk::holochain_dna::zome::entry_types::Sharing;
+/// # use hdk::holochain_core_types::cas::content::Address;
 ///
 /// # fn main() {
 ///
@@ -70,7 +88,18 @@ pub struct ValidatingEntryType {
 ///         validation: |post: Post, _ctx: hdk::ValidationData| {
 ///             (post.content.len() < 280)
 ///                 .ok_or_else(|| String::from("Content too long"))
-///         }
+///         },
+///
+///         links: [
+///             link!(
+///                 direction: hdk::LinkDirection::To,
+///                 other_type: "comment",
+///                 tag: "comments",
+///                 validation: |_base: Address, _target: Address, _ctx: hdk::ValidationData| {
+///                     Ok(())
+///                 }
+///             )
+///         ]
 ///     )
 /// }
 ///
@@ -87,6 +116,12 @@ macro_rules! entry {
 
         validation_package: || $package_creator:expr,
         validation: | $entry:ident : $entry_type:ty, $ctx:ident : hdk::ValidationData | $entry_validation:expr
+        $(
+            ,
+            links : [
+                $( $link_expr:expr ),*
+            ]
+        )*
     ) => (
 
         {
@@ -112,13 +147,79 @@ macro_rules! entry {
                 }
             });
 
+            #[allow(unused_mut)]
+            let mut link_validators = std::collections::HashMap::new();
+            $($(
+                {
+                    let link_definition: ::hdk::entry_definition::ValidatingLinkDefinition = $link_expr;
+                    match link_definition.direction {
+                        ::hdk::LinkDirection::To => {
+                            let mut links_to = ::hdk::holochain_dna::zome::entry_types::LinksTo::new();
+                            links_to.target_type = link_definition.other_entry_type.clone();
+                            links_to.tag = link_definition.tag.clone();
+                            entry_type.links_to.push(links_to);
+                        }
+                        ::hdk::LinkDirection::From => {
+                            let mut linked_from = ::hdk::holochain_dna::zome::entry_types::LinkedFrom::new();
+                            linked_from.base_type = link_definition.other_entry_type.clone();
+                            linked_from.tag = link_definition.tag.clone();
+                            entry_type.linked_from.push(linked_from);
+                        }
+                    }
+                    link_validators.insert(
+                        (link_definition.direction, link_definition.other_entry_type, link_definition.tag),
+                        link_definition.validator,
+                    );
+                }
+            )*)*
 
             ::hdk::entry_definition::ValidatingEntryType {
                 name: String::from($name),
                 entry_type_definition: entry_type,
                 package_creator,
                 validator,
-                link_validators: std::collections::HashMap::new(),
+                link_validators,
+            }
+        }
+    );
+}
+
+/// The `link` macro is a helper for creating `ValidatingLinkDefinition`s
+/// for use within the `links` section of the [entry](macro.entry.html) macro.
+/// It has 4 component parts:
+/// 1. direction: `direction` is [LinkDirection::To](enum.LinkDirection.html) for links from this entry type
+///      to another one (`links_to` in the DNA) and `LinkDirection::From` for links from another entry type
+///      to this one (`linked_from` in the DNA).
+/// 2. other_type: `other_type` is the name of the entry type on the other end of the link.
+/// 3. tag: `tag` is the tag the link is created with.
+/// 4. validation: `validation` is a callback function which will be called any time that such a
+///      link is added, both when it gets committed and when a (DHT) node holds it.
+///      It expects the base and the target address of the link and the validation `context`.
+/// See the [entry](macro.entry.html) macro for an example.
+#[macro_export]
+macro_rules! link {
+    (
+        direction: $direction:expr,
+        other_type: $other_type:expr,
+        tag: $tag:expr,
+        validation: | $base:ident : $base_type:ty, $target:ident : $target_type:ty, $ctx:ident : hdk::ValidationData | $link_validation:expr
+    ) => (
+        {
+            let validator = Box::new(|base: ::hdk::holochain_wasm_utils::holochain_core_types::hash::HashString,
+                                      _tag: String,
+                                      target: ::hdk::holochain_wasm_utils::holochain_core_types::hash::HashString,
+                                      ctx: ::hdk::holochain_wasm_utils::holochain_core_types::validation::ValidationData| {
+                let $base: $base_type = base;
+                let $target: $target_type = target;
+                let $ctx = ctx;
+                $link_validation
+            });
+
+            ::hdk::entry_definition::ValidatingLinkDefinition {
+                direction: $direction,
+                other_entry_type: String::from($other_type),
+                tag: String::from($tag),
+                validator,
             }
         }
     );
@@ -5,16 +5,16 @@ use holochain_wasm_utils::{memory_allocation::*, memory_serialization::*};
 use std::convert::TryInto;
 
 /// Init global memory stack
-pub fn init_global_memory(encoded_allocation_of_input: u32) {
+pub fn init_global_memory(encoded_allocation_of_input: u64) {
     unsafe {
         G_MEM_STACK =
-            Some(SinglePageStack::from_encoded_allocation(encoded_allocation_of_input).unwrap());
+            Some(WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap());
     }
 }
 
 /// Serialize output as json in WASM memory
-pub fn store_and_return_output<J: TryInto<JsonString>>(jsonable: J) -> u32 {
+pub fn store_and_return_output<J: TryInto<JsonString>>(jsonable: J) -> u64 {
     unsafe {
-        return store_as_json_into_encoded_allocation(&mut G_MEM_STACK.unwrap(), jsonable) as u32;
+        return store_as_json_into_encoded_allocation(&mut G_MEM_STACK.unwrap(), jsonable) as u64;
     }
 }
@@ -1,11 +1,11 @@
 //! Holds the internal/private globals used by the zome api library.
 //! Also contains the functions declarations of the external functions provided by the Ribosome.
 
-use holochain_wasm_utils::{api_serialization::ZomeApiGlobals, memory_allocation::SinglePageStack};
+use holochain_wasm_utils::{api_serialization::ZomeApiGlobals, memory_allocation::WasmStack};
 use init_globals::init_globals;
 
 // Internal global for memory usage
-pub static mut G_MEM_STACK: Option<SinglePageStack> = None;
+pub static mut G_MEM_STACK: Option<WasmStack> = None;
 
 // Internal global for retrieving all Zome API globals
 lazy_static! {
@@ -16,20 +16,23 @@ lazy_static! {
 // WARNING Names must be in sync with ZomeAPIFunction in holochain-rust
 #[allow(dead_code)]
 extern "C" {
-    pub(crate) fn hc_property(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_hash_entry(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_debug(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_call(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_sign(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_verify_signature(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_commit_entry(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_update_entry(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_remove_entry(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_get_entry(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_link_entries(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_get_links(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_query(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_send(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_start_bundle(encoded_allocation_of_input: u32) -> u32;
-    pub(crate) fn hc_close_bundle(encoded_allocation_of_input: u32) -> u32;
+    pub(crate) fn hc_property(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_hash_entry(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_debug(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_call(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_sign(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_verify_signature(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_commit_entry(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_update_entry(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_remove_entry(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_get_entry(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_link_entries(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_remove_link(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_get_links(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_query(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_send(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_start_bundle(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_close_bundle(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_grant_capability(encoded_allocation_of_input: u64) -> u64;
+    pub(crate) fn hc_revoke_capability(encoded_allocation_of_input: u64) -> u64;
 }
@@ -8,7 +8,7 @@ use holochain_wasm_utils::{api_serialization::ZomeApiGlobals, memory_serializati
 use std::convert::TryInto;
 
 extern "C" {
-    fn hc_init_globals(encoded_allocation_of_input: u32) -> u32;
+    fn hc_init_globals(encoded_allocation_of_input: u64) -> u64;
 }
 
 // HC INIT GLOBALS - Secret Api Function
@@ -17,7 +17,7 @@ pub(crate) fn init_globals() -> ZomeApiResult<ZomeApiGlobals> {
     // Call WASMI-able init_globals
     let encoded_allocation_of_result = unsafe { hc_init_globals(0) };
     // Deserialize complex result stored in memory
-    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result as u32)?;
+    let result: ZomeApiInternalResult = load_json(encoded_allocation_of_result)?;
     // Done
     if result.ok {
         Ok(JsonString::from(result.value).try_into()?)
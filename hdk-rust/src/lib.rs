@@ -17,8 +17,6 @@ extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
-extern crate bitflags;
-#[macro_use]
 extern crate lazy_static;
 pub extern crate holochain_core_types;
 pub extern crate holochain_dna;
@@ -14,12 +14,20 @@ macro_rules! load_json {
 
 /// Every Zome must utilize the `define_zome`
 /// macro in the main library file in their Zome.
-/// The `define_zome` macro has 3 component parts:
+/// The `define_zome` macro has 5 component parts:
 /// 1. entries: an array of [ValidatingEntryType](entry_definition/struct.ValidatingEntryType.html) as returned by using the [entry](macro.entry.html) macro
 /// 2. genesis: `genesis` is a callback called by Holochain to every Zome implemented within a DNA.
 ///     It gets called when a new agent is initializing an instance of the DNA for the first time, and
 ///     should return `Ok` or an `Err`, depending on whether the agent can join the network or not.
-/// 3. functions: `functions` is divided up into `capabilities`, which specify who can access those functions.
+///     The membrane proof the agent joins with, if any, is available as `hdk::MEMBRANE_PROOF`.
+/// 3. receive (optional): `receive` is a callback called by Holochain when another agent sends a
+///     direct message to this Zome with [send](fn.send.html). It gets the address of the sender
+///     and the message, and returns the response that is sent back.
+/// 4. validate_agent (optional): `validate_agent` is a callback called by Holochain when the
+///     agent id of an agent gets validated, which happens when the agent joins the network.
+///     It gets the agent id and the validation data and returns `Ok` to admit the agent
+///     or an `Err` with the reason it is rejected.
+/// 5. functions: `functions` is divided up into `capabilities`, which specify who can access those functions.
 ///     `functions` must be a tree structure where the first children are `capabilities`
 ///     and the children of those `capabilities` are actual function definitions.
 /// # Examples
@@ -89,6 +97,15 @@ macro_rules! load_json {
 ///         Ok(())
 ///     }
 ///
+///     receive: |from, message| {
+///         format!("{} received from {}", message, from)
+///     }
+///
+///     validate_agent: |agent_id, _ctx| {
+///         (!agent_id.is_empty())
+///             .ok_or_else(|| String::from("Agents need a name"))
+///     }
+///
 ///     functions: {
 ///         // "main" is the name of the capability
 ///         // "Public" is the access setting of the capability
@@ -119,6 +136,18 @@ macro_rules! define_zome {
             $genesis_expr:expr
         }
 
+        $(
+            receive : | $receive_from:ident, $receive_payload:ident | {
+                $receive_expr:expr
+            }
+        )*
+
+        $(
+            validate_agent : | $agent_id:ident, $agent_validation_data:ident | {
+                $validate_agent_expr:expr
+            }
+        )*
+
         functions : {
             $(
                 $cap:ident ( $vis:ident ) {
@@ -143,7 +172,7 @@ macro_rules! define_zome {
         }
 
         #[no_mangle]
-        pub extern "C" fn genesis(encoded_allocation_of_input: u32) -> u32 {
+        pub extern "C" fn genesis(encoded_allocation_of_input: u64) -> u64 {
             $crate::global_fns::init_global_memory(encoded_allocation_of_input);
 
             fn execute() -> Result<(), String> {
@@ -156,6 +185,51 @@ macro_rules! define_zome {
             }
         }
 
+        $(
+            #[no_mangle]
+            pub extern "C" fn receive(encoded_allocation_of_input: u64) -> u64 {
+                $crate::global_fns::init_global_memory(encoded_allocation_of_input);
+
+                let maybe_input = load_json!(encoded_allocation_of_input);
+                let input: $crate::holochain_wasm_utils::api_serialization::send::ReceiveParams = maybe_input.unwrap();
+
+                #[allow(unused_variables)]
+                fn execute(
+                    $receive_from: $crate::holochain_wasm_utils::holochain_core_types::cas::content::Address,
+                    $receive_payload: String,
+                ) -> String {
+                    $receive_expr
+                }
+
+                $crate::global_fns::store_and_return_output(
+                    $crate::holochain_wasm_utils::holochain_core_types::json::RawString::from(execute(input.from, input.payload))
+                )
+            }
+        )*
+
+        $(
+            #[no_mangle]
+            pub extern "C" fn __hdk_validate_agent_id(encoded_allocation_of_input: u64) -> u64 {
+                $crate::global_fns::init_global_memory(encoded_allocation_of_input);
+
+                let maybe_input = load_json!(encoded_allocation_of_input);
+                let input: $crate::holochain_wasm_utils::api_serialization::validation::AgentIdValidationArgs = maybe_input.unwrap();
+
+                #[allow(unused_variables)]
+                fn execute(
+                    $agent_id: String,
+                    $agent_validation_data: $crate::ValidationData,
+                ) -> Result<(), String> {
+                    $validate_agent_expr
+                }
+
+                match execute(input.agent_id, input.validation_data) {
+                    Ok(()) => 0,
+                    Err(fail_string) => $crate::global_fns::store_and_return_output(fail_string),
+                }
+            }
+        )*
+
         use $crate::holochain_dna::zome::capabilities::Capability;
         use std::collections::HashMap;
 
@@ -205,7 +279,7 @@ macro_rules! define_zome {
         $(
             $(
                 #[no_mangle]
-                pub extern "C" fn $zome_function_name(encoded_allocation_of_input: u32) -> u32 {
+                pub extern "C" fn $zome_function_name(encoded_allocation_of_input: u64) -> u64 {
                     $crate::global_fns::init_global_memory(encoded_allocation_of_input);
 
                     // Macro'd InputStruct
@@ -2,7 +2,7 @@ use entry_definition::ValidatingEntryType;
 use globals::G_MEM_STACK;
 use holochain_dna::zome::capabilities::Capability;
 use holochain_wasm_utils::{
-    api_serialization::validation::EntryValidationArgs,
+    api_serialization::validation::{EntryValidationArgs, LinkValidationArgs},
     holochain_core_types::error::RibosomeErrorCode,
     memory_serialization::{load_json, load_string, store_string_into_encoded_allocation},
 };
@@ -39,8 +39,8 @@ extern "C" {
 
 #[no_mangle]
 pub extern "C" fn __hdk_get_validation_package_for_entry_type(
-    encoded_allocation_of_input: u32,
-) -> u32 {
+    encoded_allocation_of_input: u64,
+) -> u64 {
     ::global_fns::init_global_memory(encoded_allocation_of_input);
 
     let mut zd = ZomeDefinition::new();
@@ -51,7 +51,7 @@ pub extern "C" fn __hdk_get_validation_package_for_entry_type(
     // Deserialize input
     let maybe_name = load_string(encoded_allocation_of_input);
     if let Err(err_code) = maybe_name {
-        return err_code as u32;
+        return err_code as u64;
     }
     let name: String = maybe_name.unwrap();
 
@@ -60,7 +60,7 @@ pub extern "C" fn __hdk_get_validation_package_for_entry_type(
         .into_iter()
         .find(|ref entry_type| entry_type.name == name)
     {
-        None => RibosomeErrorCode::CallbackFailed as u32,
+        None => RibosomeErrorCode::CallbackFailed as u64,
         Some(mut entry_type_definition) => {
             let package = (*entry_type_definition.package_creator)();
             ::global_fns::store_and_return_output(package)
@@ -69,7 +69,7 @@ pub extern "C" fn __hdk_get_validation_package_for_entry_type(
 }
 
 #[no_mangle]
-pub extern "C" fn __hdk_validate_app_entry(encoded_allocation_of_input: u32) -> u32 {
+pub extern "C" fn __hdk_validate_app_entry(encoded_allocation_of_input: u64) -> u64 {
     ::global_fns::init_global_memory(encoded_allocation_of_input);
 
     let mut zd = ZomeDefinition::new();
@@ -89,7 +89,7 @@ pub extern "C" fn __hdk_validate_app_entry(encoded_allocation_of_input: u32) ->
         .into_iter()
         .find(|ref entry_type| entry_type.name == entry_validation_args.entry_type)
     {
-        None => RibosomeErrorCode::CallbackFailed as u32,
+        None => RibosomeErrorCode::CallbackFailed as u64,
         Some(mut entry_type_definition) => {
             let validation_result = (*entry_type_definition.validator)(
                 entry_validation_args.entry,
@@ -105,7 +105,57 @@ pub extern "C" fn __hdk_validate_app_entry(encoded_allocation_of_input: u32) ->
 }
 
 #[no_mangle]
-pub extern "C" fn __hdk_get_json_definition(encoded_allocation_of_input: u32) -> u32 {
+pub extern "C" fn __hdk_validate_link(encoded_allocation_of_input: u64) -> u64 {
+    ::global_fns::init_global_memory(encoded_allocation_of_input);
+
+    let mut zd = ZomeDefinition::new();
+    unsafe {
+        zome_setup(&mut zd);
+    }
+
+    // Deserialize input
+    let maybe_args = load_json(encoded_allocation_of_input);
+    if let Err(hc_err) = maybe_args {
+        return ::global_fns::store_and_return_output(hc_err);
+    }
+    let link_validation_args: LinkValidationArgs = maybe_args.unwrap();
+    let link = link_validation_args.link;
+
+    match zd
+        .entry_types
+        .into_iter()
+        .find(|ref entry_type| entry_type.name == link_validation_args.entry_type)
+    {
+        None => RibosomeErrorCode::CallbackFailed as u64,
+        Some(mut entry_type_definition) => {
+            let key = (
+                link_validation_args.direction,
+                link_validation_args.other_entry_type,
+                link.tag().clone(),
+            );
+            let validation_result = match entry_type_definition.link_validators.get_mut(&key) {
+                None => Err(format!(
+                    "No validation callback defined for link with tag '{}'",
+                    link.tag()
+                )),
+                Some(validator) => (*validator)(
+                    link.base().clone(),
+                    link.tag().clone(),
+                    link.target().clone(),
+                    link_validation_args.validation_data,
+                ),
+            };
+
+            match validation_result {
+                Ok(()) => 0,
+                Err(fail_string) => ::global_fns::store_and_return_output(fail_string),
+            }
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn __hdk_get_json_definition(encoded_allocation_of_input: u64) -> u64 {
     ::global_fns::init_global_memory(encoded_allocation_of_input);
 
     let mut zd = ZomeDefinition::new();
@@ -125,7 +175,7 @@ pub extern "C" fn __hdk_get_json_definition(encoded_allocation_of_input: u32) ->
         "capabilities": capabilities,
     })).expect("Can't serialize DNA");
 
-    unsafe { store_string_into_encoded_allocation(&mut G_MEM_STACK.unwrap(), &json_string) as u32 }
+    unsafe { store_string_into_encoded_allocation(&mut G_MEM_STACK.unwrap(), &json_string) as u64 }
 }
 
 #[cfg(test)]
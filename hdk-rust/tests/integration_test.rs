@@ -2,25 +2,31 @@ extern crate holochain_core;
 extern crate holochain_core_api;
 extern crate holochain_core_types;
 extern crate holochain_dna;
+extern crate holochain_net;
 extern crate tempfile;
 extern crate test_utils;
 #[macro_use]
 extern crate serde_json;
 
+use holochain_core::context::Context;
 use holochain_core_api::*;
 
 use holochain_core_types::{
-    cas::content::Address,
+    cas::content::{Address, AddressableContent},
     entry::{Entry, SerializedEntry},
     entry_type::test_entry_type,
     error::ZomeApiInternalResult,
     hash::HashString,
     json::JsonString,
 };
-use holochain_dna::zome::{
-    capabilities::{Capability, FnDeclaration, Membrane},
-    entry_types::EntryTypeDef,
+use holochain_dna::{
+    zome::{
+        capabilities::{Capability, FnDeclaration, Membrane},
+        entry_types::{EntryTypeDef, LinksTo},
+    },
+    Dna,
 };
+use holochain_net::in_process::InProcessHub;
 use std::sync::{Arc, Mutex};
 use test_utils::*;
 
@@ -37,7 +43,11 @@ pub fn create_test_cap_with_fn_names(fn_names: Vec<&str>) -> Capability {
 }
 
 fn start_holochain_instance() -> (Holochain, Arc<Mutex<TestLogger>>) {
-    // Setup the holochain instance
+    let (context, test_logger) = test_context_and_logger("alex");
+    (start_holochain_instance_with_context(context), test_logger)
+}
+
+fn test_dna() -> Dna {
     let wasm =
         create_wasm_from_file("wasm-test/target/wasm32-unknown-unknown/release/test_globals.wasm");
     let capabability = create_test_cap_with_fn_names(vec![
@@ -49,27 +59,116 @@ fn start_holochain_instance() -> (Holochain, Arc<Mutex<TestLogger>>) {
         "send_tweet",
         "commit_validation_package_tester",
         "link_two_entries",
+        "link_validation",
         "links_roundtrip",
+        "links_pagination",
+        "remove_link_roundtrip",
+        "remove_locked_link",
+        "update_entry_roundtrip",
+        "update_locked_entry",
+        "large_entry_roundtrip",
+        "remove_entry_roundtrip",
+        "commit_bundle",
+        "grant_property_access",
+        "revoke_property_access",
         "check_query",
         "check_hash_app_entry",
         "check_hash_sys_entry",
         "check_call",
         "check_call_with_args",
+        "check_property",
+        "check_membrane_proof",
+        "send_message",
     ]);
     let mut dna = create_test_dna_with_cap("test_zome", "test_cap", &capabability, &wasm);
+    dna.properties = json!({"language": "en", "limits": {"max_posts": 10}});
 
     dna.zomes.get_mut("test_zome").unwrap().entry_types.insert(
         String::from("validation_package_tester"),
         EntryTypeDef::new(),
     );
 
-    let (context, test_logger) = test_context_and_logger("alex");
+    let mut secret_cap = create_test_cap_with_fn_names(vec!["check_property"]);
+    secret_cap.cap_type.membrane = Membrane::ApiKey;
+    dna.zomes
+        .get_mut("test_zome")
+        .unwrap()
+        .capabilities
+        .insert(String::from("secret_cap"), secret_cap);
+
+    for tag in &["test-tag", "locked-tag"] {
+        let mut links_to = LinksTo::new();
+        links_to.target_type = String::from("testEntryType");
+        links_to.tag = String::from(*tag);
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut("testEntryType")
+            .unwrap()
+            .links_to
+            .push(links_to);
+    }
+    dna
+}
+
+fn start_holochain_instance_with_context(context: Arc<Context>) -> Holochain {
+    // Setup the holochain instance
     let mut hc =
-        Holochain::new(dna.clone(), context).expect("could not create new Holochain instance.");
+        Holochain::new(test_dna(), context).expect("could not create new Holochain instance.");
 
     // Run the holochain instance
     hc.start().expect("couldn't start");
-    (hc, test_logger)
+    hc
+}
+
+#[test]
+fn rejects_agents_not_admitted_by_zome() {
+    let (context, _) = test_context_and_logger("blocked_agent");
+    let result = Holochain::new(test_dna(), context);
+    assert!(result.is_err());
+    let error = format!("{:?}", result.err().unwrap());
+    assert!(
+        error.contains("Blocked agents are not admitted"),
+        "error = {}",
+        error
+    );
+}
+
+#[test]
+fn passes_membrane_proof_to_zomes() {
+    let (context, _) = test_context_and_logger("alex");
+    let mut hc = Holochain::new_with_membrane_proof(
+        test_dna(),
+        Some(JsonString::from("{\"invite\":\"welcome\"}")),
+        context,
+    ).expect("could not create new Holochain instance.");
+    hc.start().expect("couldn't start");
+
+    let result = hc.call("test_zome", "test_cap", "check_membrane_proof", r#"{}"#);
+    assert_eq!(
+        result,
+        Ok(JsonString::from(
+            "{\"membrane_proof\":\"{\\\"invite\\\":\\\"welcome\\\"}\"}"
+        )),
+    );
+}
+
+#[test]
+fn rejects_agents_with_invalid_membrane_proof() {
+    let (context, _) = test_context_and_logger("alex");
+    let result = Holochain::new_with_membrane_proof(
+        test_dna(),
+        Some(JsonString::from("{\"invite\":\"revoked\"}")),
+        context,
+    );
+    assert!(result.is_err());
+    let error = format!("{:?}", result.err().unwrap());
+    assert!(
+        error.contains("Invite code is not valid"),
+        "error = {}",
+        error
+    );
 }
 
 #[test]
@@ -178,7 +277,7 @@ fn can_get_entry() {
     assert_eq!(
         result.unwrap(),
         JsonString::from(
-            "{\"value\":\"{\\\"stuff\\\": \\\"non fail\\\"}\",\"entry_type\":\"testEntryType\"}"
+            "{\"address\":\"Qmf7HGMHTZSb4zPB2wvrJnkgmURJ9VuTnEi4xG6QguB36v\",\"entry\":{\"value\":\"{\\\"stuff\\\": \\\"non fail\\\"}\",\"entry_type\":\"testEntryType\"},\"header\":null,\"sources\":null,\"meta\":null}"
         )
     );
 
@@ -210,7 +309,12 @@ fn can_get_entry() {
     );
     println!("\t can_get_entry_result result = {:?}", result);
     assert!(result.is_ok(), "\t result = {:?}", result);
-    assert_eq!(result.unwrap(), JsonString::null());
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from(
+            "{\"address\":null,\"entry\":null,\"header\":null,\"sources\":null,\"meta\":null}"
+        )
+    );
 
     // test the case with a bad hash
     let result = hc.call(
@@ -229,22 +333,22 @@ fn can_get_entry() {
 #[test]
 fn can_invalidate_invalid_commit() {
     let (mut hc, _) = start_holochain_instance();
+    let entry = Entry::new(test_entry_type(), JsonString::from("{\"stuff\":\"FAIL\"}"));
     // Call the exposed wasm function that calls the Commit API function
     let result = hc.call(
         "test_zome",
         "test_cap",
         "check_commit_entry_macro",
-        &String::from(JsonString::from(SerializedEntry::from(Entry::new(
-            test_entry_type(),
-            JsonString::from("{\"stuff\":\"FAIL\"}"),
-        )))),
+        &String::from(JsonString::from(SerializedEntry::from(entry.clone()))),
     );
     println!("\t result = {:?}", result);
     assert!(result.is_ok(), "result = {:?}", result);
     assert_eq!(
         result.unwrap(),
-        JsonString::from("{\"error\":{\"Internal\":\"{\\\"kind\\\":{\\\"ValidationFailed\\\":\\\"FAIL content is not allowed\\\"},\\\"file\\\":\\\"core/src/nucleus/ribosome/runtime.rs\\\",\\\"line\\\":\\\"83\\\"}\"}}"),
+        JsonString::from("{\"error\":{\"Internal\":\"{\\\"kind\\\":{\\\"ValidationFailed\\\":\\\"FAIL content is not allowed\\\"},\\\"file\\\":\\\"core/src/nucleus/ribosome/runtime.rs\\\",\\\"line\\\":\\\"94\\\"}\"}}"),
     );
+    // the entry is now in the registry of rejected entries
+    assert_eq!(vec![entry.address()], hc.rejected_entries().unwrap());
 }
 
 #[test]
@@ -312,6 +416,18 @@ fn can_link_entries() {
     assert_eq!(result.unwrap(), JsonString::from(r#"{"Ok":null}"#));
 }
 
+#[test]
+fn can_validate_links() {
+    let (mut hc, _) = start_holochain_instance();
+
+    let result = hc.call("test_zome", "test_cap", "link_validation", r#"{}"#);
+    assert!(result.is_ok(), "\t result = {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from("{\"Err\":{\"Internal\":\"{\\\"kind\\\":{\\\"ValidationFailed\\\":\\\"Entries can not link to themselves\\\"},\\\"file\\\":\\\"core/src/nucleus/ribosome/runtime.rs\\\",\\\"line\\\":\\\"94\\\"}\"}}"),
+    );
+}
+
 #[test]
 fn can_roundtrip_links() {
     let (mut hc, _) = start_holochain_instance();
@@ -329,6 +445,186 @@ fn can_roundtrip_links() {
     assert!(ordering1 || ordering2, "result = {:?}", result_string);
 }
 
+#[test]
+fn can_paginate_links() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "links_pagination", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(result.unwrap(), JsonString::from(r#"{"Ok":true}"#));
+}
+
+#[test]
+fn can_get_property() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "check_property", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(result.unwrap(), JsonString::from(r#"{"Ok":true}"#));
+}
+
+#[test]
+fn can_send_and_receive_messages() {
+    let hub = InProcessHub::new();
+    let mut alex = start_holochain_instance_with_context(test_context_on_network("alex", &hub));
+    let billy_context = test_context_on_network("billy", &hub);
+    let billy_address = billy_context.agent.address();
+    let _billy = start_holochain_instance_with_context(billy_context);
+
+    let result = alex.call(
+        "test_zome",
+        "test_cap",
+        "send_message",
+        &format!(r#"{{"to_agent": "{}", "message": "ping"}}"#, billy_address),
+    );
+    assert!(result.is_ok(), "result = {:?}", result);
+
+    let alex_address = test_context("alex").agent.address();
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from(format!(r#"{{"Ok":"Received: ping from {}"}}"#, alex_address))
+    );
+}
+
+#[test]
+fn can_remove_link() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "remove_link_roundtrip", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(result.unwrap(), JsonString::from(r#"{"Ok":true}"#));
+}
+
+#[test]
+fn can_validate_link_removal() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "remove_locked_link", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from("{\"Err\":{\"Internal\":\"{\\\"kind\\\":{\\\"ValidationFailed\\\":\\\"Locked links can not be removed\\\"},\\\"file\\\":\\\"core/src/nucleus/ribosome/runtime.rs\\\",\\\"line\\\":\\\"94\\\"}\"}}"),
+    );
+}
+
+#[test]
+fn can_update_entry() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "update_entry_roundtrip", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from(
+            "{\"Ok\":{\"value\":\"{\\\"stuff\\\":\\\"entry2\\\"}\",\"entry_type\":\"testEntryType\"}}"
+        ),
+    );
+}
+
+#[test]
+fn can_round_trip_large_entry() {
+    let (mut hc, _) = start_holochain_instance();
+    // more than a page of wasm memory, in a pattern that shows any overwritten part
+    let stuff = (0..100_000)
+        .map(|i| (b'a' + (i % 26) as u8) as char)
+        .collect::<String>();
+    let result = hc.call(
+        "test_zome",
+        "test_cap",
+        "large_entry_roundtrip",
+        &json!({ "stuff": stuff }).to_string(),
+    );
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from(format!(
+            "{{\"Ok\":{{\"value\":\"{{\\\"stuff\\\":\\\"{}\\\"}}\",\"entry_type\":\"testEntryType\"}}}}",
+            stuff
+        )),
+    );
+}
+
+#[test]
+fn validates_updates_against_old_entry() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "update_locked_entry", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    let result = String::from(result.unwrap());
+    assert!(
+        result.contains("Locked entries can not be changed"),
+        "result = {}",
+        result
+    );
+}
+
+#[test]
+fn can_remove_entry() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "test_cap", "remove_entry_roundtrip", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(result.unwrap(), JsonString::from(r#"{"Ok":null}"#));
+}
+
+#[test]
+fn can_commit_bundle() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call(
+        "test_zome",
+        "test_cap",
+        "commit_bundle",
+        r#"{"first": "bundle1", "second": "bundle2"}"#,
+    );
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from(r#"{"closed":true,"first_committed":true}"#)
+    );
+}
+
+#[test]
+fn can_call_with_capability_token() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call("test_zome", "secret_cap", "check_property", r#"{}"#);
+    assert!(result.is_err(), "result = {:?}", result);
+
+    let result = hc.call("test_zome", "test_cap", "grant_property_access", r#"{}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    let token: Result<Address, String> =
+        serde_json::from_str(&String::from(result.unwrap())).unwrap();
+    let token = token.expect("grant should succeed");
+
+    let result = hc.call_with_token(
+        "test_zome",
+        "secret_cap",
+        token.clone(),
+        "check_property",
+        r#"{}"#,
+    );
+    assert!(result.is_ok(), "result = {:?}", result);
+
+    let result = hc.call(
+        "test_zome",
+        "test_cap",
+        "revoke_property_access",
+        &format!(r#"{{"token": "{}"}}"#, token),
+    );
+    assert_eq!(result, Ok(JsonString::from(r#"{"Ok":null}"#)));
+
+    let result = hc.call_with_token("test_zome", "secret_cap", token, "check_property", r#"{}"#);
+    assert!(result.is_err(), "result = {:?}", result);
+}
+
+#[test]
+fn invalid_entry_fails_whole_bundle() {
+    let (mut hc, _) = start_holochain_instance();
+    let result = hc.call(
+        "test_zome",
+        "test_cap",
+        "commit_bundle",
+        r#"{"first": "bundle1", "second": "FAIL"}"#,
+    );
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        JsonString::from(r#"{"closed":false,"first_committed":false}"#)
+    );
+}
+
 #[test]
 fn can_check_query() {
     let (mut hc, _) = start_holochain_instance();
@@ -28,6 +28,7 @@ use holochain_wasm_utils::{
 };
 use holochain_wasm_utils::api_serialization::get_entry::GetEntryOptions;
 use hdk::holochain_dna::zome::entry_types::Sharing;
+use hdk::BundleOnClose;
 use holochain_wasm_utils::holochain_core_types::cas::content::Address;
 use holochain_wasm_utils::holochain_core_types::error::HolochainError;
 
@@ -50,17 +51,17 @@ impl From<CommitOutputStruct> for JsonString {
 }
 
 #[no_mangle]
-pub extern "C" fn check_commit_entry(encoded_allocation_of_input: u32) -> u32 {
+pub extern "C" fn check_commit_entry(encoded_allocation_of_input: u64) -> u64 {
     unsafe {
         G_MEM_STACK =
-            Some(SinglePageStack::from_encoded_allocation(encoded_allocation_of_input).unwrap());
+            Some(WasmStack::from_encoded_allocation(encoded_allocation_of_input).unwrap());
     }
 
     // Deserialize and check for an encoded error
-    let result = load_json(encoded_allocation_of_input as u32);
+    let result = load_json(encoded_allocation_of_input);
     if let Err(hc_err) = result {
         hdk::debug(format!("ERROR: {:?}", hc_err.to_string())).expect("debug() must work");
-        return RibosomeErrorCode::ArgumentDeserializationFailed as u32;
+        return RibosomeErrorCode::ArgumentDeserializationFailed as u64;
     }
 
     let serialized_entry: SerializedEntry = result.unwrap();
@@ -73,7 +74,7 @@ pub extern "C" fn check_commit_entry(encoded_allocation_of_input: u32) -> u32 {
     };
 
     unsafe {
-        return store_as_json_into_encoded_allocation(&mut G_MEM_STACK.unwrap(), res_obj) as u32;
+        return store_as_json_into_encoded_allocation(&mut G_MEM_STACK.unwrap(), res_obj) as u64;
     }
 }
 
@@ -91,7 +92,7 @@ fn handle_check_commit_entry_macro(entry_type: String, value: String) -> JsonStr
 }
 
 fn handle_check_get_entry_result(entry_address: Address) -> JsonString {
-    match hdk::get_entry_result(entry_address, GetEntryOptions{}) {
+    match hdk::get_entry_result(entry_address, GetEntryOptions::default()) {
         Ok(result) => result.into(),
         Err(e) => e.into(),
     }
@@ -128,6 +129,19 @@ fn handle_link_two_entries()-> JsonString {
     hdk::link_entries(&entry1_result.unwrap(), &entry2_result.unwrap(), "test-tag").into()
 }
 
+fn handle_link_validation() -> JsonString {
+    let entry1_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry1".into(),
+    }));
+    let entry1_hash = match entry1_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry1_hash_result.into(),
+    };
+
+    // rejected by the link validator of testEntryType
+    hdk::link_entries(&entry1_hash, &entry1_hash, "test-tag").into()
+}
+
 fn handle_links_roundtrip() -> JsonString {
     let entry1_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
         stuff: "entry1".into(),
@@ -173,6 +187,212 @@ fn handle_links_roundtrip() -> JsonString {
     hdk::get_links(&entry1_hash, "test-tag").into()
 }
 
+fn handle_links_pagination() -> JsonString {
+    let mut hashes = Vec::new();
+    for stuff in &["entry1", "entry2", "entry3"] {
+        let hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+            stuff: stuff.to_string(),
+        }));
+        match hash_result {
+            Ok(hash) => hashes.push(hash),
+            Err(_) => return hash_result.into(),
+        }
+    }
+    for target in &hashes[1..] {
+        let link_result = hdk::link_entries(&hashes[0], target, "test-tag");
+        if link_result.is_err() {
+            return link_result.into();
+        }
+    }
+
+    let mut options = hdk::GetLinksOptions::default();
+    options.limit = Some(1);
+    let first_page_result = hdk::get_links_result(&hashes[0], "test-tag", options.clone());
+    let first_page = match first_page_result {
+        Ok(page) => page,
+        Err(_) => return first_page_result.into(),
+    };
+
+    // both pages together hold all links, and the last page has no cursor
+    options.cursor = first_page.next_cursor.clone();
+    let mut expected = hashes[1..].to_vec();
+    expected.sort();
+    hdk::get_links_result(&hashes[0], "test-tag", options)
+        .map(|second_page| {
+            let mut all_links = first_page.addresses();
+            all_links.extend(second_page.addresses());
+            all_links == expected && second_page.next_cursor.is_none()
+        })
+        .into()
+}
+
+fn handle_remove_link_roundtrip() -> JsonString {
+    let entry1_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry1".into(),
+    }));
+    let entry1_hash = match entry1_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry1_hash_result.into(),
+    };
+
+    let entry2_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry2".into(),
+    }));
+    let entry2_hash = match entry2_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry2_hash_result.into(),
+    };
+
+    let entry3_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry3".into(),
+    }));
+    let entry3_hash = match entry3_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry3_hash_result.into(),
+    };
+
+    for target in &[&entry2_hash, &entry3_hash] {
+        let link_result = hdk::link_entries(&entry1_hash, target, "test-tag");
+        if link_result.is_err() {
+            return link_result.into();
+        }
+    }
+
+    let remove_result = hdk::remove_link(&entry1_hash, &entry2_hash, "test-tag");
+    if remove_result.is_err() {
+        return remove_result.into();
+    }
+
+    // only the link that was not removed is still returned
+    hdk::get_links(&entry1_hash, "test-tag")
+        .map(|links| links == vec![entry3_hash])
+        .into()
+}
+
+fn handle_remove_locked_link() -> JsonString {
+    let entry1_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry1".into(),
+    }));
+    let entry1_hash = match entry1_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry1_hash_result.into(),
+    };
+
+    let entry2_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry2".into(),
+    }));
+    let entry2_hash = match entry2_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry2_hash_result.into(),
+    };
+
+    let link_result = hdk::link_entries(&entry1_hash, &entry2_hash, "locked-tag");
+    if link_result.is_err() {
+        return link_result.into();
+    }
+
+    // rejected by the link validator of testEntryType
+    hdk::remove_link(&entry1_hash, &entry2_hash, "locked-tag").into()
+}
+
+fn handle_update_entry_roundtrip() -> JsonString {
+    let entry1_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry1".into(),
+    }));
+    let entry1_hash = match entry1_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry1_hash_result.into(),
+    };
+
+    let update_result = hdk::update_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry2".into(),
+    }), &entry1_hash);
+    if update_result.is_err() {
+        return update_result.into();
+    }
+
+    // the old address now resolves to the new version
+    hdk::get_entry(entry1_hash)
+        .map(|maybe_entry| maybe_entry.and_then(|entry| Some(entry.serialize())))
+        .into()
+}
+
+fn handle_large_entry_roundtrip(stuff: String) -> JsonString {
+    let entry = Entry::new("testEntryType".into(), EntryStruct{
+        stuff,
+    });
+    hdk::commit_entry(&entry)
+        .and_then(hdk::get_entry)
+        .map(|maybe_entry| maybe_entry.and_then(|entry| Some(entry.serialize())))
+        .into()
+}
+
+fn handle_update_locked_entry() -> JsonString {
+    let locked_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "locked".into(),
+    }));
+    let locked_hash = match locked_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return locked_hash_result.into(),
+    };
+
+    hdk::update_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "unlocked".into(),
+    }), &locked_hash).into()
+}
+
+fn handle_remove_entry_roundtrip() -> JsonString {
+    let entry1_hash_result = hdk::commit_entry(&Entry::new("testEntryType".into(), EntryStruct{
+        stuff: "entry1".into(),
+    }));
+    let entry1_hash = match entry1_hash_result {
+        Ok(hash) => hash,
+        Err(_) => return entry1_hash_result.into(),
+    };
+
+    let remove_result = hdk::remove_entry(&entry1_hash);
+    if remove_result.is_err() {
+        return remove_result.into();
+    }
+
+    // removed entries are not returned anymore
+    hdk::get_entry(entry1_hash)
+        .map(|maybe_entry| maybe_entry.and_then(|entry| Some(entry.serialize())))
+        .into()
+}
+
+fn handle_commit_bundle(first: String, second: String) -> JsonString {
+    let first_entry = Entry::new("testEntryType".into(), EntryStruct{
+        stuff: first,
+    });
+    let second_entry = Entry::new("testEntryType".into(), EntryStruct{
+        stuff: second,
+    });
+
+    let closed = hdk::start_bundle(5000, json!({"test": "bundle"}))
+        .and_then(|_| hdk::commit_entry(&first_entry))
+        .and_then(|_| hdk::commit_entry(&second_entry))
+        .and_then(|_| hdk::close_bundle(BundleOnClose::Commit));
+
+    // the first entry only ends up on the chain if the whole bundle is valid
+    let first_committed = hdk::hash_entry(&first_entry)
+        .and_then(hdk::get_entry)
+        .map(|maybe_entry| maybe_entry.is_some());
+
+    json!({
+        "closed": closed.is_ok(),
+        "first_committed": first_committed.unwrap_or(false),
+    }).into()
+}
+
+fn handle_grant_property_access() -> ZomeApiResult<Address> {
+    hdk::grant_capability("secret_cap", vec!["check_property".to_string()], None)
+}
+
+fn handle_revoke_property_access(token: Address) -> ZomeApiResult<()> {
+    hdk::revoke_capability(token)
+}
+
 fn handle_check_query() -> JsonString {
     fn err(s: &str) -> ZomeApiResult<Address> {
         Err(ZomeApiError::Internal(s.to_owned()))
@@ -262,6 +482,29 @@ fn handle_check_hash_sys_entry() -> JsonString {
     json!({"result": "FIXME"}).into()
 }
 
+fn handle_check_membrane_proof() -> JsonString {
+    json!({ "membrane_proof": *hdk::MEMBRANE_PROOF }).into()
+}
+
+fn handle_check_property() -> JsonString {
+    let language = hdk::property("language");
+    let max_posts = hdk::property("/limits/max_posts");
+    let missing = hdk::property("missing");
+    let result: ZomeApiResult<bool> = match (language, max_posts) {
+        (Ok(language), Ok(max_posts)) => Ok(
+            language == JsonString::from("\"en\"")
+                && max_posts == JsonString::from("10")
+                && missing.is_err()
+        ),
+        (Err(err), _) | (_, Err(err)) => Err(err),
+    };
+    result.into()
+}
+
+fn handle_send_message(to_agent: Address, message: String) -> JsonString {
+    hdk::send(to_agent, message).into()
+}
+
 fn handle_check_call() -> JsonString {
     let empty_dumpty = json!({});
     hdk::debug(format!("empty_dumpty = {:?}", empty_dumpty)).ok();
@@ -326,10 +569,54 @@ define_zome! {
                 hdk::ValidationPackageDefinition::ChainFull
             },
 
-            validation: |entry: TestEntryType, _ctx: hdk::ValidationData| {
-                (entry.stuff != "FAIL")
-                    .ok_or_else(|| "FAIL content is not allowed".to_string())
-            }
+            validation: |entry: TestEntryType, ctx: hdk::ValidationData| {
+                match ctx.action {
+                    // only the author can change an entry, unless it is locked
+                    hdk::EntryAction::Modify | hdk::EntryAction::Delete => ctx
+                        .old_entry_header
+                        .as_ref()
+                        .ok_or_else(|| "Only the author can change an entry".to_string())
+                        .and_then(|_| {
+                            let locked = ctx
+                                .old_entry
+                                .as_ref()
+                                .map(|old_entry| old_entry.value().contains("\"locked\""))
+                                .unwrap_or(false);
+                            (!locked).ok_or_else(|| "Locked entries can not be changed".to_string())
+                        })
+                        .and_then(|_| {
+                            (entry.stuff != "FAIL")
+                                .ok_or_else(|| "FAIL content is not allowed".to_string())
+                        }),
+                    _ => (entry.stuff != "FAIL")
+                        .ok_or_else(|| "FAIL content is not allowed".to_string()),
+                }
+            },
+
+            links: [
+                link!(
+                    direction: hdk::LinkDirection::To,
+                    other_type: "testEntryType",
+                    tag: "test-tag",
+                    validation: |base: Address, target: Address, _ctx: hdk::ValidationData| {
+                        (base != target)
+                            .ok_or_else(|| "Entries can not link to themselves".to_string())
+                    }
+                ),
+                link!(
+                    direction: hdk::LinkDirection::To,
+                    other_type: "testEntryType",
+                    tag: "locked-tag",
+                    validation: |_base: Address, _target: Address, ctx: hdk::ValidationData| {
+                        match ctx.link_action {
+                            Some(hdk::LinkAction::Delete) => {
+                                Err("Locked links can not be removed".to_string())
+                            }
+                            _ => Ok(()),
+                        }
+                    }
+                )
+            ]
         ),
 
         entry!(
@@ -350,6 +637,22 @@ define_zome! {
 
     genesis: || { Ok(()) }
 
+    receive: |from, message| {
+        format!("Received: {} from {}", message, from)
+    }
+
+    validate_agent: |agent_id, ctx| {
+        (!agent_id.contains("blocked"))
+            .ok_or_else(|| "Blocked agents are not admitted".to_string())
+            .and_then(|_| {
+                let revoked = ctx
+                    .membrane_proof
+                    .map(|proof| proof.contains("revoked"))
+                    .unwrap_or(false);
+                (!revoked).ok_or_else(|| "Invite code is not valid".to_string())
+            })
+    }
+
     functions: {
         test (Public) {
             check_global: {
@@ -388,12 +691,96 @@ define_zome! {
                 handler: handle_link_two_entries
             }
 
+            link_validation: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_link_validation
+            }
+
             links_roundtrip: {
                 inputs: | |,
                 outputs: |result: JsonString|,
                 handler: handle_links_roundtrip
             }
 
+            links_pagination: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_links_pagination
+            }
+
+            check_property: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_check_property
+            }
+
+            check_membrane_proof: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_check_membrane_proof
+            }
+
+            send_message: {
+                inputs: |to_agent: Address, message: String|,
+                outputs: |response: ZomeApiResult<String>|,
+                handler: handle_send_message
+            }
+
+            remove_link_roundtrip: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_remove_link_roundtrip
+            }
+
+            remove_locked_link: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_remove_locked_link
+            }
+
+            update_entry_roundtrip: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_update_entry_roundtrip
+            }
+
+            large_entry_roundtrip: {
+                inputs: |stuff: String|,
+                outputs: |result: JsonString|,
+                handler: handle_large_entry_roundtrip
+            }
+
+            update_locked_entry: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_update_locked_entry
+            }
+
+            remove_entry_roundtrip: {
+                inputs: | |,
+                outputs: |result: JsonString|,
+                handler: handle_remove_entry_roundtrip
+            }
+
+            commit_bundle: {
+                inputs: |first: String, second: String|,
+                outputs: |result: JsonString|,
+                handler: handle_commit_bundle
+            }
+
+            grant_property_access: {
+                inputs: | |,
+                outputs: |token: ZomeApiResult<Address>|,
+                handler: handle_grant_property_access
+            }
+
+            revoke_property_access: {
+                inputs: |token: Address|,
+                outputs: |result: ZomeApiResult<()>|,
+                handler: handle_revoke_property_access
+            }
+
             check_call: {
                 inputs: | |,
                 outputs: |result: JsonString|,
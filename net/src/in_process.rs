@@ -0,0 +1,316 @@
+//! An in-process network that connects holochain instances running in the same process.
+//! Meant for tests and local development: every instance connects to a shared hub
+//! which routes direct messages between them without touching a real transport.
+
+use base64;
+use failure::Error;
+use p2p_network::{ApiFnBin, ApiFnJson, GenomeHash, MessageCallback, P2pNetwork};
+use serde_json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// message callbacks registered on the hub, by genome hash and agent
+type MessageHandlers = HashMap<(String, String), Arc<MessageCallback>>;
+
+/// The shared routing table of all networks connected to it
+#[derive(Clone, Default)]
+pub struct InProcessHub {
+    handlers: Arc<Mutex<MessageHandlers>>,
+}
+
+impl InProcessHub {
+    pub fn new() -> Self {
+        InProcessHub::default()
+    }
+
+    /// create a new network connected to this hub
+    pub fn connect(&self) -> InProcessNetwork {
+        InProcessNetwork { hub: self.clone() }
+    }
+
+    fn register(&self, genome_hash: String, agent: String, cb: MessageCallback) {
+        self.handlers
+            .lock()
+            .expect("hub handlers should not be poisoned")
+            .insert((genome_hash, agent), Arc::new(cb));
+    }
+
+    fn unregister(&self, genome_hash: String, agent: String) {
+        self.handlers
+            .lock()
+            .expect("hub handlers should not be poisoned")
+            .remove(&(genome_hash, agent));
+    }
+
+    /// Hands the message to the callback of the receiving agent.
+    /// No lock is held while the callback runs, so it can send messages itself,
+    /// even to its own agent.
+    fn route(
+        &self,
+        genome_hash: String,
+        from: &str,
+        to: &str,
+        payload: &str,
+    ) -> Result<String, Error> {
+        let maybe_handler = self
+            .handlers
+            .lock()
+            .expect("hub handlers should not be poisoned")
+            .get(&(genome_hash, to.to_string()))
+            .cloned();
+        match maybe_handler {
+            Some(handler) => (*handler)(from, payload),
+            None => bail!("agent '{}' is not reachable", to),
+        }
+    }
+}
+
+/// A connection to an in-process hub.
+/// It is always running and needs no configuration.
+#[derive(Clone)]
+pub struct InProcessNetwork {
+    hub: InProcessHub,
+}
+
+/// helper to grab a string property from a json request
+fn request_str(request: &serde_json::value::Value, property: &str) -> Result<String, Error> {
+    match request[property].as_str() {
+        Some(value) => Ok(value.to_string()),
+        None => bail!("missing '{}' in request: {}", property, request),
+    }
+}
+
+impl P2pNetwork for InProcessNetwork {
+    fn exec_raw_json(&mut self, input: &str, cb: Option<ApiFnJson>) -> Result<String, Error> {
+        let request: serde_json::value::Value = serde_json::from_str(input)?;
+        match request_str(&request, "method")?.as_str() {
+            "getState" => Ok("running".to_string()),
+            "getDefaultConfig" => Ok("{}".to_string()),
+            "setConfig" | "dhtOnHoldCallback" | "dhtPublish" => Ok("undefined".to_string()),
+            "onMessageCallback" => {
+                let cb = match cb {
+                    Some(cb) => Mutex::new(cb),
+                    None => bail!("onMessageCallback requires a callback"),
+                };
+                // raw json callbacks are FnMut, so they handle one message at a time
+                self.hub.register(
+                    request_str(&request, "genomeHash")?,
+                    request_str(&request, "agent")?,
+                    Box::new(move |from, payload| {
+                        let message = json!({
+                            "from": from,
+                            "payload": payload,
+                        });
+                        let mut cb = cb.lock().expect("message callback should not be poisoned");
+                        (*cb)(&message.to_string())
+                    }),
+                );
+                Ok("undefined".to_string())
+            }
+            "removeMessageCallback" => {
+                self.hub.unregister(
+                    request_str(&request, "genomeHash")?,
+                    request_str(&request, "agent")?,
+                );
+                Ok("undefined".to_string())
+            }
+            "sendMessage" => self.hub.route(
+                request_str(&request, "genomeHash")?,
+                &request_str(&request, "from")?,
+                &request_str(&request, "to")?,
+                &request_str(&request, "payload")?,
+            ),
+            method => bail!("unknown method: '{}'", method),
+        }
+    }
+
+    fn exec_raw_bin(&mut self, _input: &[u8], _cb: Option<ApiFnBin>) -> Result<Vec<u8>, Error> {
+        // apps are always tracked, there is nothing to sign or encrypt in process
+        Ok(Vec::new())
+    }
+
+    fn try_clone(&self) -> Result<Box<P2pNetwork + Send>, Error> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn set_on_message_callback(
+        &mut self,
+        genome_hash: &GenomeHash,
+        agent: &str,
+        cb: MessageCallback,
+    ) -> Result<(), Error> {
+        self.hub
+            .register(base64::encode(genome_hash), agent.to_string(), cb);
+        Ok(())
+    }
+
+    fn remove_on_message_callback(
+        &mut self,
+        genome_hash: &GenomeHash,
+        agent: &str,
+    ) -> Result<(), Error> {
+        self.hub
+            .unregister(base64::encode(genome_hash), agent.to_string());
+        Ok(())
+    }
+
+    fn send_message(
+        &mut self,
+        genome_hash: &GenomeHash,
+        from: &str,
+        to: &str,
+        payload: &str,
+    ) -> Result<String, Error> {
+        self.hub
+            .route(base64::encode(genome_hash), from, to, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p2p_network::P2pNetworkState;
+
+    #[test]
+    fn it_should_be_running() {
+        let mut net = InProcessHub::new().connect();
+        match net.get_state().unwrap() {
+            P2pNetworkState::Running => (),
+            _ => panic!("unexpected get_state return value"),
+        };
+    }
+
+    #[test]
+    fn it_should_route_messages_between_networks() {
+        let hub = InProcessHub::new();
+        let mut alice = hub.connect();
+        let mut bob = hub.connect();
+
+        alice
+            .set_on_message_callback(
+                &[1_u8; 32],
+                "alice",
+                Box::new(|from, payload| Ok(format!("{} from {}", payload, from))),
+            )
+            .unwrap();
+
+        assert_eq!(
+            "ping from bob".to_string(),
+            bob.send_message(&[1_u8; 32], "bob", "alice", "ping")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_route_messages_sent_from_a_message_callback() {
+        let hub = InProcessHub::new();
+        let mut alice = hub.connect();
+        let callback_hub = hub.clone();
+
+        // alice answers a ping by asking herself for a pong
+        alice
+            .set_on_message_callback(
+                &[1_u8; 32],
+                "alice",
+                Box::new(move |_, payload| match payload {
+                    "ping" => {
+                        callback_hub
+                            .connect()
+                            .send_message(&[1_u8; 32], "alice", "alice", "pong")
+                    }
+                    _ => Ok(payload.to_string()),
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(
+            "pong".to_string(),
+            alice
+                .send_message(&[1_u8; 32], "bob", "alice", "ping")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_route_messages_over_cloned_networks() {
+        let hub = InProcessHub::new();
+        let mut alice = hub.connect();
+        let bob = hub.connect();
+
+        alice
+            .set_on_message_callback(
+                &[1_u8; 32],
+                "alice",
+                Box::new(|from, payload| Ok(format!("{} from {}", payload, from))),
+            )
+            .unwrap();
+
+        assert_eq!(
+            "ping from bob".to_string(),
+            bob.try_clone()
+                .unwrap()
+                .send_message(&[1_u8; 32], "bob", "alice", "ping")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_fail_on_unreachable_agent() {
+        let hub = InProcessHub::new();
+        let mut alice = hub.connect();
+        let mut bob = hub.connect();
+
+        alice
+            .set_on_message_callback(&[1_u8; 32], "alice", Box::new(|_, _| Ok(String::new())))
+            .unwrap();
+
+        // same agent but another app
+        assert_eq!(
+            "agent 'alice' is not reachable".to_string(),
+            bob.send_message(&[2_u8; 32], "bob", "alice", "ping")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn it_should_not_route_to_removed_callbacks() {
+        let hub = InProcessHub::new();
+        let mut alice = hub.connect();
+        let mut bob = hub.connect();
+
+        alice
+            .set_on_message_callback(&[1_u8; 32], "alice", Box::new(|_, _| Ok(String::new())))
+            .unwrap();
+        alice
+            .remove_on_message_callback(&[1_u8; 32], "alice")
+            .unwrap();
+
+        assert_eq!(
+            "agent 'alice' is not reachable".to_string(),
+            bob.send_message(&[1_u8; 32], "bob", "alice", "ping")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn it_should_pass_on_callback_errors() {
+        let hub = InProcessHub::new();
+        let mut alice = hub.connect();
+
+        alice
+            .set_on_message_callback(&[1_u8; 32], "alice", Box::new(|_, _| bail!("busy")))
+            .unwrap();
+
+        assert_eq!(
+            "busy".to_string(),
+            alice
+                .send_message(&[1_u8; 32], "bob", "alice", "ping")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
@@ -9,4 +9,5 @@ extern crate failure;
 extern crate serde_json;
 
 pub mod error;
+pub mod in_process;
 pub mod p2p_network;
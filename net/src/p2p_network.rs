@@ -9,14 +9,20 @@ use failure::Error;
 use serde_json;
 
 /// callback function type for api methods transporting json data
-pub type ApiFnJson = Box<FnMut(&str) -> Result<String, Error>>;
+pub type ApiFnJson = Box<FnMut(&str) -> Result<String, Error> + Send>;
 
 /// callback function type for api methods transporting binary data
-pub type ApiFnBin = Box<FnMut(&[u8]) -> Result<Vec<u8>, Error>>;
+pub type ApiFnBin = Box<FnMut(&[u8]) -> Result<Vec<u8>, Error> + Send>;
 
 /// when the network is requesting we store data
 /// this callback will be invoked, expecting the data to be validated
-pub type DhtHoldCallback = Box<FnMut(&str) -> Result<bool, Error>>;
+pub type DhtHoldCallback = Box<FnMut(&str) -> Result<bool, Error> + Send>;
+
+/// when another agent sends us a direct message
+/// this callback will be invoked with the sender and the message,
+/// expecting the response to send back.
+/// It may be invoked for several messages at once, e.g. if handling one sends another.
+pub type MessageCallback = Box<Fn(&str, &str) -> Result<String, Error> + Send + Sync>;
 
 /// the identifier for an application
 pub type GenomeHash = [u8; 32];
@@ -59,6 +65,13 @@ pub trait P2pNetwork {
     /// This is similar to `exec_raw_json`, but permits binary data transfer
     fn exec_raw_bin(&mut self, input: &[u8], cb: Option<ApiFnBin>) -> Result<Vec<u8>, Error>;
 
+    /// Opens another connection to the same network, that can be used independently,
+    /// e.g. to wait for the response to a message without blocking this one.
+    /// Not every network supports this.
+    fn try_clone(&self) -> Result<Box<P2pNetwork + Send>, Error> {
+        bail!("this network connection can't be cloned")
+    }
+
     // -- All following functions are default implementations
     // -- making use of the above two functions
 
@@ -180,6 +193,70 @@ pub trait P2pNetwork {
         self.exec_raw_json(&(v.to_string()), None)?;
         Ok(())
     }
+
+    /// when another agent of an app sends a direct message to `agent`
+    /// the callback is invoked and its response is sent back
+    fn set_on_message_callback(
+        &mut self,
+        genome_hash: &GenomeHash,
+        agent: &str,
+        cb: MessageCallback,
+    ) -> Result<(), Error> {
+        self.exec_raw_json(
+            &(json!({
+                "method": "onMessageCallback",
+                "genomeHash": base64::encode(genome_hash),
+                "agent": agent,
+            }).to_string()),
+            Some(Box::new(move |input| {
+                let v: serde_json::value::Value = serde_json::from_str(input)?;
+                match (v["from"].as_str(), v["payload"].as_str()) {
+                    (Some(from), Some(payload)) => cb(from, payload),
+                    _ => bail!("malformed message: '{}'", input),
+                }
+            })),
+        )?;
+        Ok(())
+    }
+
+    /// stop handing direct messages to `agent` of an app to the callback
+    /// set with `set_on_message_callback`, they become unreachable
+    fn remove_on_message_callback(
+        &mut self,
+        genome_hash: &GenomeHash,
+        agent: &str,
+    ) -> Result<(), Error> {
+        self.exec_raw_json(
+            &(json!({
+                "method": "removeMessageCallback",
+                "genomeHash": base64::encode(genome_hash),
+                "agent": agent,
+            }).to_string()),
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// send a direct message to the agent `to` of an app
+    /// and return the response of its message callback
+    fn send_message(
+        &mut self,
+        genome_hash: &GenomeHash,
+        from: &str,
+        to: &str,
+        payload: &str,
+    ) -> Result<String, Error> {
+        self.exec_raw_json(
+            &(json!({
+                "method": "sendMessage",
+                "genomeHash": base64::encode(genome_hash),
+                "from": from,
+                "to": to,
+                "payload": payload,
+            }).to_string()),
+            None,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -491,4 +568,62 @@ mod tests {
             .dht_publish(&[9_u8; 32], "{\"test\":\"holo\"}")
             .unwrap();
     }
+
+    #[test]
+    fn it_should_call_on_message_callback() {
+        let mut node = NodeStub::new();
+        node.net.json_handler_queue.push(Box::new(|input, cb| {
+            assert_some!(cb);
+            let v = setup_handler!(input, "onMessageCallback");
+            assert_eq!("alice".to_string(), json_obj_str(&v, "agent")?);
+            let res = cb.unwrap()(
+                &(json!({
+                "from": "bob",
+                "payload": "ping",
+            }).to_string()),
+            )?;
+            assert_eq!("ping from bob".to_string(), res);
+            Ok("undefined".to_string())
+        }));
+        node.net
+            .set_on_message_callback(
+                &[7_u8; 32],
+                "alice",
+                Box::new(|from, payload| Ok(format!("{} from {}", payload, from))),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn it_should_remove_on_message_callback() {
+        let mut node = NodeStub::new();
+        node.net.json_handler_queue.push(Box::new(|input, cb| {
+            assert_none!(cb);
+            let v = setup_handler!(input, "removeMessageCallback");
+            assert_eq!("alice".to_string(), json_obj_str(&v, "agent")?);
+            Ok("undefined".to_string())
+        }));
+        node.net
+            .remove_on_message_callback(&[7_u8; 32], "alice")
+            .unwrap();
+    }
+
+    #[test]
+    fn it_should_send_message() {
+        let mut node = NodeStub::new();
+        node.net.json_handler_queue.push(Box::new(|input, cb| {
+            assert_none!(cb);
+            let v = setup_handler!(input, "sendMessage");
+            assert_eq!("bob".to_string(), json_obj_str(&v, "from")?);
+            assert_eq!("alice".to_string(), json_obj_str(&v, "to")?);
+            assert_eq!("ping".to_string(), json_obj_str(&v, "payload")?);
+            Ok("pong".to_string())
+        }));
+        assert_eq!(
+            "pong".to_string(),
+            node.net
+                .send_message(&[7_u8; 32], "bob", "alice", "ping")
+                .unwrap()
+        );
+    }
 }
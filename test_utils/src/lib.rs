@@ -4,6 +4,7 @@ extern crate holochain_core;
 extern crate holochain_core_api;
 extern crate holochain_core_types;
 extern crate holochain_dna;
+extern crate holochain_net;
 extern crate tempfile;
 extern crate wabt;
 
@@ -21,6 +22,7 @@ use holochain_dna::{
     },
     Dna,
 };
+use holochain_net::in_process::InProcessHub;
 use std::{
     collections::{hash_map::DefaultHasher, HashMap},
     fmt,
@@ -46,8 +48,8 @@ pub fn create_test_dna_with_wat(zome_name: &str, cap_name: &str, wat: Option<&st
     let default_wat = r#"
             (module
                 (memory (;0;) 17)
-                (func (export "main") (param $p0 i32) (result i32)
-                    i32.const 6
+                (func (export "main") (param $p0 i64) (result i64)
+                    i64.const 6
                 )
                 (data (i32.const 0)
                     "1337.0"
@@ -67,6 +69,33 @@ pub fn create_test_dna_with_wat(zome_name: &str, cap_name: &str, wat: Option<&st
     create_test_dna_with_wasm(zome_name, cap_name, wasm_binary.as_ref().to_vec())
 }
 
+/// Prepare valid DNA struct whose "main" function never returns by itself,
+/// the zome aborts it once the given timeout has passed
+pub fn create_test_dna_with_blocking_main(zome_name: &str, cap_name: &str, timeout_ms: u64) -> Dna {
+    let mut dna = create_test_dna_with_wat(
+        zome_name,
+        cap_name,
+        Some(
+            r#"
+            (module
+                (memory (;0;) 17)
+                (func (export "main") (param $p0 i64) (result i64)
+                    (loop (br 0))
+                    i64.const 0
+                )
+                (export "memory" (memory 0))
+            )
+        "#,
+        ),
+    );
+    dna.zomes
+        .get_mut(zome_name)
+        .expect("test zome should exist")
+        .config
+        .timeout_ms = Some(timeout_ms);
+    dna
+}
+
 /// Prepare valid DNA struct with that WASM in a zome's capability
 pub fn create_test_dna_with_wasm(zome_name: &str, cap_name: &str, wasm: Vec<u8>) -> Dna {
     let mut dna = Dna::new();
@@ -189,6 +218,13 @@ pub fn test_context(agent_name: &str) -> Arc<Context> {
     context
 }
 
+/// create a test context connected to the given in-process network
+pub fn test_context_on_network(agent_name: &str, hub: &InProcessHub) -> Arc<Context> {
+    let mut context = (*test_context(agent_name)).clone();
+    context.set_network(Arc::new(Mutex::new(hub.connect())));
+    Arc::new(context)
+}
+
 /// calculates the native Rust hash
 /// has nothing to do with our hashing e.g. multihash
 /// @see https://doc.rust-lang.org/std/hash/index.html
@@ -0,0 +1,20 @@
+use holochain_core_types::{error::HolochainError, json::*};
+use serde_json::Value;
+
+/// Struct for input data received when Zome API function start_bundle() is invoked
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, DefaultJson)]
+pub struct StartBundleArgs {
+    /// Discard the bundle if it is not closed within this many milliseconds
+    pub timeout_ms: u64,
+    /// Handed to the validation of every entry in the bundle
+    pub user_param: Value,
+}
+
+/// Allowed input for close_bundle()
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
+pub enum BundleOnClose {
+    /// Validate all staged entries together and commit them if they are all valid
+    Commit,
+    /// Throw all staged entries away
+    Discard,
+}
@@ -0,0 +1,12 @@
+use holochain_core_types::{cas::content::Address, error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function grant_capability() is invoked
+#[derive(Deserialize, Default, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
+pub struct GrantCapabilityArgs {
+    /// Capability of the calling zome to grant access to
+    pub capability: String,
+    /// Functions of the capability the token can be used for
+    pub functions: Vec<String>,
+    /// Agents the token is good for, anyone holding it if None
+    pub assignees: Option<Vec<Address>>,
+}
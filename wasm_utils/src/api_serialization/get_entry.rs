@@ -1,3 +1,73 @@
-// empty for now, need to implement get options
-#[derive(Deserialize, Debug, Serialize)]
-pub struct GetEntryOptions {}
+use holochain_core_types::{
+    cas::content::Address, chain_header::ChainHeader, crud_status::CrudStatus,
+    entry::SerializedEntry, error::HolochainError, json::*,
+};
+
+/// Options for retrieving an entry with hc_get_entry
+#[derive(Deserialize, Debug, Serialize, DefaultJson, Clone, PartialEq)]
+pub struct GetEntryOptions {
+    /// Only return the entry if its CRUD status is contained in this mask
+    pub status_mask: CrudStatus,
+    /// Follow the crud-link of updated entries to their latest version
+    pub follow_updates: bool,
+    /// Include the chain header of the entry, if it was committed locally
+    pub include_header: bool,
+    /// Include the addresses of the agents that committed the entry
+    pub include_sources: bool,
+    /// Include the CRUD metadata of the entry
+    pub include_meta: bool,
+}
+
+impl Default for GetEntryOptions {
+    fn default() -> Self {
+        GetEntryOptions {
+            status_mask: CrudStatus::LIVE,
+            follow_updates: true,
+            include_header: false,
+            include_sources: false,
+            include_meta: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Serialize, DefaultJson)]
+pub struct GetEntryArgs {
+    pub address: Address,
+    pub options: GetEntryOptions,
+}
+
+/// The CRUD metadata the DHT holds about an entry
+#[derive(Deserialize, Debug, Serialize, DefaultJson, Clone, PartialEq)]
+pub struct EntryMeta {
+    pub crud_status: CrudStatus,
+    /// Address of the new version of a modified entry,
+    /// or of the deletion entry of a removed one
+    pub crud_link: Option<Address>,
+}
+
+#[derive(Deserialize, Debug, Serialize, DefaultJson, Clone, PartialEq)]
+pub struct GetEntryResult {
+    /// Address of the returned entry.
+    /// Differs from the requested address if an update was followed.
+    pub address: Option<Address>,
+    pub entry: Option<SerializedEntry>,
+    pub header: Option<ChainHeader>,
+    pub sources: Option<Vec<Address>>,
+    pub meta: Option<EntryMeta>,
+}
+
+impl GetEntryResult {
+    pub fn not_found() -> Self {
+        GetEntryResult {
+            address: None,
+            entry: None,
+            header: None,
+            sources: None,
+            meta: None,
+        }
+    }
+
+    pub fn found(&self) -> bool {
+        self.entry.is_some()
+    }
+}
@@ -1,7 +1,62 @@
-use holochain_core_types::{cas::content::Address, error::HolochainError, json::*};
+use holochain_core_types::{
+    cas::content::Address, crud_status::CrudStatus, entry::SerializedEntry, error::HolochainError,
+    json::*,
+};
+
+/// Options for retrieving links with hc_get_links
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
+pub struct GetLinksOptions {
+    /// Maximum number of links to return, all links if None.
+    /// Must be greater than 0.
+    pub limit: Option<usize>,
+    /// Only return links whose target comes after this address,
+    /// as returned in `next_cursor` of the previous page
+    pub cursor: Option<Address>,
+    /// Only return links whose target is an entry of this type
+    pub target_type: Option<String>,
+    /// Only return links whose status is contained in this mask.
+    /// Links are either LIVE or, once removed, DELETED; no other status is ever returned.
+    pub status_mask: CrudStatus,
+    /// Include the target entries in the result
+    pub include_entries: bool,
+}
+
+impl Default for GetLinksOptions {
+    fn default() -> Self {
+        GetLinksOptions {
+            limit: None,
+            cursor: None,
+            target_type: None,
+            status_mask: CrudStatus::LIVE,
+            include_entries: false,
+        }
+    }
+}
 
 #[derive(Deserialize, Default, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
 pub struct GetLinksArgs {
     pub entry_address: Address,
     pub tag: String,
+    pub options: GetLinksOptions,
+}
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, DefaultJson)]
+pub struct LinkResult {
+    pub target: Address,
+    pub status: CrudStatus,
+    pub entry: Option<SerializedEntry>,
+}
+
+/// A page of links, ordered by target address
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, DefaultJson)]
+pub struct GetLinksResult {
+    pub links: Vec<LinkResult>,
+    /// Cursor for requesting the next page, None if this is the last one
+    pub next_cursor: Option<Address>,
+}
+
+impl GetLinksResult {
+    pub fn addresses(&self) -> Vec<Address> {
+        self.links.iter().map(|link| link.target.clone()).collect()
+    }
 }
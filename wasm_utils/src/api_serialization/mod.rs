@@ -9,10 +9,15 @@ mod call;
 ///
 /// For the case of HDK-rust we can use the exact same types by
 /// importing this module.
+pub mod bundle;
+pub mod cap_grant;
 pub mod get_entry;
 pub mod get_links;
 pub mod link_entries;
+pub mod property;
 pub mod query;
+pub mod send;
+pub mod update_entry;
 pub mod validation;
 mod zome_api_globals;
 
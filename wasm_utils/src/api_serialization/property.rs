@@ -0,0 +1,9 @@
+use holochain_core_types::{error::HolochainError, json::*};
+
+/// Name of the DNA property to look up.
+/// Either a top level key of the DNA's properties,
+/// or a JSON pointer into them when it starts with a '/' (e.g. "/limits/max_posts").
+#[derive(Deserialize, Default, Debug, Serialize, DefaultJson)]
+pub struct PropertyArgs {
+    pub name: String,
+}
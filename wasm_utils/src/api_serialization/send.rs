@@ -0,0 +1,35 @@
+use holochain_core_types::{cas::content::Address, error::HolochainError, json::*};
+
+/// Default time to wait for the response of the receiving agent, in milliseconds
+pub const DEFAULT_SEND_TIMEOUT_MS: u64 = 60_000;
+
+#[derive(Deserialize, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
+pub struct SendOptions {
+    /// Give up on the response after this many milliseconds
+    pub timeout_ms: u64,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        SendOptions {
+            timeout_ms: DEFAULT_SEND_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Struct for input data received when Zome API function send() is invoked
+#[derive(Deserialize, Default, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
+pub struct SendArgs {
+    /// Address of the receiving agent
+    pub to_agent: Address,
+    pub payload: String,
+    pub options: SendOptions,
+}
+
+/// Input of the receive() callback of the receiving zome
+#[derive(Deserialize, Default, Debug, Serialize, Clone, PartialEq, Eq, Hash, DefaultJson)]
+pub struct ReceiveParams {
+    /// Address of the sending agent
+    pub from: Address,
+    pub payload: String,
+}
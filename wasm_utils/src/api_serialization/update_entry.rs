@@ -0,0 +1,9 @@
+use holochain_core_types::{
+    cas::content::Address, entry::SerializedEntry, error::HolochainError, json::*,
+};
+
+#[derive(Deserialize, Debug, Serialize, DefaultJson)]
+pub struct UpdateEntryArgs {
+    pub new_entry: SerializedEntry,
+    pub address: Address,
+}
@@ -1,4 +1,6 @@
-use holochain_core_types::{error::HolochainError, json::*, validation::ValidationData};
+use holochain_core_types::{
+    error::HolochainError, json::*, links_entry::Link, validation::ValidationData,
+};
 
 #[derive(Deserialize, Debug, Serialize, DefaultJson)]
 pub struct EntryValidationArgs {
@@ -6,3 +8,28 @@ pub struct EntryValidationArgs {
     pub entry: String,
     pub validation_data: ValidationData,
 }
+
+/// Direction of a link as seen from the entry type that declares it:
+/// `To` for links declared in `links_to`, `From` for links declared in `linked_from`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum LinkDirection {
+    To,
+    From,
+}
+
+#[derive(Deserialize, Debug, Serialize, DefaultJson)]
+pub struct LinkValidationArgs {
+    /// entry type that declares the link
+    pub entry_type: String,
+    pub direction: LinkDirection,
+    /// entry type on the other end of the link
+    pub other_entry_type: String,
+    pub link: Link,
+    pub validation_data: ValidationData,
+}
+
+#[derive(Deserialize, Debug, Serialize, DefaultJson)]
+pub struct AgentIdValidationArgs {
+    pub agent_id: String,
+    pub validation_data: ValidationData,
+}
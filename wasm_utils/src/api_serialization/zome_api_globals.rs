@@ -8,4 +8,7 @@ pub struct ZomeApiGlobals {
     pub agent_address: HashString,
     pub agent_initial_hash: HashString,
     pub agent_latest_hash: HashString,
+    /// The membrane proof the agent joined with, if any
+    #[serde(default)]
+    pub membrane_proof: Option<String>,
 }
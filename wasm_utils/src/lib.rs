@@ -2,6 +2,8 @@
 //! or more generally for making rust code that the Ribosome can run.
 //! Must not have any dependency with any other Holochain crates.
 #![feature(try_from)]
+// memory intrinsics for zomes to grow their memory
+#![cfg_attr(target_arch = "wasm32", feature(stdsimd))]
 extern crate serde;
 extern crate serde_json;
 #[macro_use]
@@ -17,7 +17,7 @@ macro_rules! zome_assert {
 #[macro_export]
 macro_rules! ribosome_success {
     () => {
-        Ok(Some(RuntimeValue::I32(0 as i32)))
+        Ok(Some(RuntimeValue::I64(0 as i64)))
     };
 }
 
@@ -25,8 +25,8 @@ macro_rules! ribosome_success {
 #[macro_export]
 macro_rules! ribosome_error_code {
     ($s:ident) => {
-        Ok(Some(RuntimeValue::I32(
-            ::holochain_wasm_utils::holochain_core_types::error::RibosomeErrorCode::$s as i32,
+        Ok(Some(RuntimeValue::I64(
+            ::holochain_wasm_utils::holochain_core_types::error::RibosomeErrorCode::$s as i64,
         )))
     };
 }
@@ -4,37 +4,72 @@ use holochain_core_types::error::{RibosomeErrorCode, RibosomeReturnCode};
 // Helpers
 //--------------------------------------------------------------------------------------------------
 
-pub const U16_MAX: u32 = <u16>::max_value() as u32;
+pub const U32_MAX: u64 = <u32>::max_value() as u64;
 
-/// returns the u16 high bits from a u32
-pub fn u32_high_bits(i: u32) -> u16 {
-    (i >> 16) as u16
+/// Size in bytes of a WASM memory page
+pub const WASM_PAGE_SIZE: u64 = 1 << 16;
+
+/// returns the u32 high bits from a u64
+pub fn u64_high_bits(i: u64) -> u32 {
+    (i >> 32) as u32
+}
+
+/// returns the u32 low bits from a u64 by doing a lossy cast
+pub fn u64_low_bits(i: u64) -> u32 {
+    (i as u32)
+}
+
+/// splits the high and low bits of u64 into a tuple of u32, for destructuring convenience
+pub fn u64_split_bits(i: u64) -> (u32, u32) {
+    (u64_high_bits(i), u64_low_bits(i))
+}
+
+/// merges 2x u32 into a single u64
+pub fn u64_merge_bits(high: u32, low: u32) -> u64 {
+    (u64::from(high) << 32) | u64::from(low)
 }
 
-/// returns the u16 low bits from a u32 by doing a lossy cast
-pub fn u32_low_bits(i: u32) -> u16 {
-    (i as u16)
+/// rounds a number of bytes up to whole WASM memory pages
+pub fn round_up_to_page(bytes: u64) -> u64 {
+    (bytes + WASM_PAGE_SIZE - 1) / WASM_PAGE_SIZE * WASM_PAGE_SIZE
 }
 
-/// splits the high and low bits of u32 into a tuple of u16, for destructuring convenience
-pub fn u32_split_bits(i: u32) -> (u16, u16) {
-    (u32_high_bits(i), u32_low_bits(i))
+/// Size in bytes of the memory of the running WASM module.
+/// Only meaningful inside of a WASM module, e.g. a zome, outside of one there is no memory.
+#[cfg(target_arch = "wasm32")]
+pub fn wasm_memory_size() -> u64 {
+    unsafe { ::std::arch::wasm32::memory::size(0) as u64 * WASM_PAGE_SIZE }
 }
 
-/// merges 2x u16 into a single u32
-pub fn u32_merge_bits(high: u16, low: u16) -> u32 {
-    (u32::from(high) << 16) | u32::from(low)
+#[cfg(not(target_arch = "wasm32"))]
+pub fn wasm_memory_size() -> u64 {
+    0
+}
+
+/// Grow the memory of the running WASM module by the given number of pages.
+/// Only possible inside of a WASM module, fails with OutOfMemory outside of one.
+#[cfg(target_arch = "wasm32")]
+pub fn grow_wasm_memory(pages: u32) -> Result<(), RibosomeErrorCode> {
+    if unsafe { ::std::arch::wasm32::memory::grow(0, pages as i32) } < 0 {
+        return Err(RibosomeErrorCode::OutOfMemory);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn grow_wasm_memory(_pages: u32) -> Result<(), RibosomeErrorCode> {
+    Err(RibosomeErrorCode::OutOfMemory)
 }
 
 pub fn decode_encoded_allocation(
-    encoded_allocation: u32,
-) -> Result<SinglePageAllocation, RibosomeReturnCode> {
-    let (offset, length) = u32_split_bits(encoded_allocation);
+    encoded_allocation: u64,
+) -> Result<WasmAllocation, RibosomeReturnCode> {
+    let (offset, length) = u64_split_bits(encoded_allocation);
     // zero length allocation = RibosomeReturnCode
     if length == 0 {
         return Err(RibosomeReturnCode::from_offset(offset));
     }
-    let res = SinglePageAllocation::new(offset, length);
+    let res = WasmAllocation::new(offset, length);
     match res {
         Ok(alloc) => Ok(alloc),
         Err(err_code) => Err(RibosomeReturnCode::Failure(err_code)),
@@ -42,21 +77,22 @@ pub fn decode_encoded_allocation(
 }
 
 //--------------------------------------------------------------------------------------------------
-// Single Page Memory Allocation
+// WASM Memory Allocation
 //--------------------------------------------------------------------------------------------------
 
 #[derive(Copy, Clone, Debug)]
-/// SinglePageAllocation is a memory allocation garanteed to fit in a WASM 64KiB Memory Page
-pub struct SinglePageAllocation {
-    offset: u16,
-    length: u16,
+/// WasmAllocation is a memory allocation garanteed to fit in the 4GiB address space of
+/// WASM linear memory. It can span any number of 64KiB memory pages.
+pub struct WasmAllocation {
+    offset: u32,
+    length: u32,
 }
 
 #[allow(unknown_lints)]
 #[allow(cast_lossless)]
-impl SinglePageAllocation {
-    pub fn new(offset: u16, length: u16) -> Result<Self, RibosomeErrorCode> {
-        if (offset as u32 + length as u32) > U16_MAX {
+impl WasmAllocation {
+    pub fn new(offset: u32, length: u32) -> Result<Self, RibosomeErrorCode> {
+        if (offset as u64 + length as u64) > U32_MAX {
             return Err(RibosomeErrorCode::OutOfMemory);
         }
         if (offset + length) == 0 {
@@ -65,13 +101,13 @@ impl SinglePageAllocation {
         if length == 0 {
             return Err(RibosomeErrorCode::NotAnAllocation);
         }
-        Ok(SinglePageAllocation { offset, length })
+        Ok(WasmAllocation { offset, length })
     }
 
-    /// An Encoded Allocation is a u32 where 'offset' is first 16-bits and 'length' last 16-bits
+    /// An Encoded Allocation is a u64 where 'offset' is first 32-bits and 'length' last 32-bits
     /// A valid allocation must not have a length of zero
     /// An Encoded Allocation with an offset but no length is actually an encoding of an ErrorCode
-    pub fn from_encoded_allocation(encoded_allocation: u32) -> Result<Self, RibosomeErrorCode> {
+    pub fn from_encoded_allocation(encoded_allocation: u64) -> Result<Self, RibosomeErrorCode> {
         let maybe_allocation = decode_encoded_allocation(encoded_allocation);
         match maybe_allocation {
             Err(_) => Err(RibosomeErrorCode::NotAnAllocation),
@@ -79,60 +115,94 @@ impl SinglePageAllocation {
         }
     }
 
-    /// returns a single u32 value encoding both the u16 offset and length values
-    pub fn encode(self) -> u32 {
-        u32_merge_bits(self.offset, self.length)
+    /// returns a single u64 value encoding both the u32 offset and length values
+    pub fn encode(self) -> u64 {
+        u64_merge_bits(self.offset, self.length)
     }
 
     // getters
-    pub fn offset(self) -> u16 {
+    pub fn offset(self) -> u32 {
         self.offset
     }
-    pub fn length(self) -> u16 {
+    pub fn length(self) -> u32 {
         self.length
     }
 }
 
 //--------------------------------------------------------------------------------------------------
-// Single Page Memory Stack Manager
+// WASM Memory Stack Manager
 //--------------------------------------------------------------------------------------------------
 
 #[derive(Copy, Clone, Default, Debug)]
-/// Struct for managing a WASM 64KiB memory page as a stack
-pub struct SinglePageStack {
-    top: u16,
+/// Struct for managing WASM linear memory as a stack spanning as many 64KiB pages as needed
+pub struct WasmStack {
+    top: u32,
 }
 
 #[allow(unknown_lints)]
 #[allow(cast_lossless)]
-impl SinglePageStack {
+impl WasmStack {
     // A stack can be initialized by giving the last know allocation on this stack
-    pub fn new(last_allocation: SinglePageAllocation) -> Self {
-        assert!(last_allocation.offset as u32 + last_allocation.length as u32 <= U16_MAX);
-        SinglePageStack {
+    pub fn new(last_allocation: WasmAllocation) -> Self {
+        assert!(last_allocation.offset as u64 + last_allocation.length as u64 <= U32_MAX);
+        WasmStack {
             top: last_allocation.offset + last_allocation.length,
         }
     }
 
-    /// Create a SinglePageStack from a valid encoded allocation
+    /// Create a WasmStack from a valid encoded allocation
     pub fn from_encoded_allocation(
-        encoded_last_allocation: u32,
+        encoded_last_allocation: u64,
     ) -> Result<Self, RibosomeErrorCode> {
-        let maybe_allocation = decode_encoded_allocation(encoded_last_allocation as u32);
+        let maybe_allocation = decode_encoded_allocation(encoded_last_allocation);
         if let Err(_) = maybe_allocation {
             return Err(RibosomeErrorCode::NotAnAllocation);
         }
-        return Ok(SinglePageStack::new(maybe_allocation.unwrap()));
+        return Ok(WasmStack::new(maybe_allocation.unwrap()));
     }
 
-    pub fn allocate(&mut self, size: u16) -> u16 {
-        assert!(self.top as u32 + size as u32 <= U16_MAX);
+    pub fn allocate(&mut self, size: u32) -> u32 {
+        assert!(self.top as u64 + size as u64 <= U32_MAX);
         let offset = self.top;
         self.top += size;
         offset
     }
 
-    pub fn deallocate(&mut self, allocation: SinglePageAllocation) -> Result<(), ()> {
+    /// Allocate on the stack in WASM memory of the given size in bytes, growing it with `grow`,
+    /// which gets the number of pages to add, if the allocation does not fit.
+    /// The stack only uses pages it grew the memory by itself, so it never overwrites the data,
+    /// shadow stack or heap of the module: the stack owns the rest of the page its top is in,
+    /// and it continues in the pages it adds if those are the last ones of the memory.
+    /// If the memory got grown by the module in the meantime, the allocation goes to new
+    /// pages after the module's.
+    pub fn allocate_in_memory<F>(
+        &mut self,
+        size: u32,
+        memory_size: u64,
+        grow: F,
+    ) -> Result<u32, RibosomeErrorCode>
+    where
+        F: FnOnce(u32) -> Result<(), RibosomeErrorCode>,
+    {
+        let top = self.top as u64;
+        let owned_end = round_up_to_page(top);
+        let offset = if top + size as u64 <= owned_end || owned_end == memory_size {
+            top
+        } else {
+            memory_size
+        };
+        let end = offset + size as u64;
+        if end > U32_MAX {
+            return Err(RibosomeErrorCode::OutOfMemory);
+        }
+        if end > memory_size {
+            grow(((round_up_to_page(end) - memory_size) / WASM_PAGE_SIZE) as u32)?;
+        }
+        self.top = end as u32;
+        Ok(offset as u32)
+    }
+
+    pub fn deallocate(&mut self, allocation: WasmAllocation) -> Result<(), ()> {
         if self.top == allocation.offset + allocation.length {
             self.top = allocation.offset;
             return Ok(());
@@ -141,7 +211,7 @@ impl SinglePageStack {
     }
 
     // Getters
-    pub fn top(self) -> u16 {
+    pub fn top(self) -> u32 {
         self.top
     }
 }
@@ -152,53 +222,122 @@ pub mod tests {
     use super::*;
     use holochain_core_types::error::RibosomeReturnCode;
 
-    pub fn test_single_page_allocation() -> SinglePageAllocation {
-        SinglePageAllocation::new(0, 20).expect("could not create test SinglePageAllocation")
+    pub fn test_wasm_allocation() -> WasmAllocation {
+        WasmAllocation::new(0, 20).expect("could not create test WasmAllocation")
     }
 
-    pub fn test_single_page_stack() -> SinglePageStack {
-        SinglePageStack::new(test_single_page_allocation())
+    pub fn test_wasm_stack() -> WasmStack {
+        WasmStack::new(test_wasm_allocation())
     }
 
     #[test]
-    /// smoke test single_page_allocation
-    fn single_page_allocation_smoke_test() {
-        test_single_page_allocation();
+    /// smoke test wasm_allocation
+    fn wasm_allocation_smoke_test() {
+        test_wasm_allocation();
     }
 
     #[test]
-    /// smoke test single_page_stack
-    fn single_page_stack_smoke_test() {
-        test_single_page_stack();
+    /// smoke test wasm_stack
+    fn wasm_stack_smoke_test() {
+        test_wasm_stack();
     }
 
     #[test]
-    /// tests construction and encoding in a new single page allocation
-    fn single_page_allocation_from_encoded_allocation() {
-        let i = 0b1010101010101010_0101010101010101;
-        let single_page_allocation = SinglePageAllocation::from_encoded_allocation(i).unwrap();
+    /// tests construction and encoding in a new wasm allocation
+    fn wasm_allocation_from_encoded_allocation() {
+        let i = 0b1010101010101010_1010101010101010_0101010101010101_0101010101010101;
+        let wasm_allocation = WasmAllocation::from_encoded_allocation(i).unwrap();
 
-        assert_eq!(0b1010101010101010, single_page_allocation.offset);
-        assert_eq!(0b0101010101010101, single_page_allocation.length);
+        assert_eq!(0b1010101010101010_1010101010101010, wasm_allocation.offset);
+        assert_eq!(0b0101010101010101_0101010101010101, wasm_allocation.length);
     }
 
     #[test]
-    fn single_page_stack_from_encoded_test() {
-        let i = 0b1010101010101010_0101010101010101;
-        let single_page_stack = SinglePageStack::from_encoded_allocation(i);
+    fn wasm_stack_from_encoded_test() {
+        let i = 0b1010101010101010_1010101010101010_0101010101010101_0101010101010101;
+        let wasm_stack = WasmStack::from_encoded_allocation(i);
         // stack top is offset + length
-        assert_eq!(0b1111111111111111, single_page_stack.unwrap().top());
-        let single_page_stack = SinglePageStack::from_encoded_allocation(0);
+        assert_eq!(<u32>::max_value(), wasm_stack.unwrap().top());
+        let wasm_stack = WasmStack::from_encoded_allocation(0);
         // stack top is 0
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            single_page_stack.err().unwrap()
+            wasm_stack.err().unwrap()
         );
-        let i = 0b0000000000000001_0000000000000000;
-        let single_page_stack = SinglePageStack::from_encoded_allocation(i);
+        let i = 0b0000000000000000_0000000000000001_0000000000000000_0000000000000000;
+        let wasm_stack = WasmStack::from_encoded_allocation(i);
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            single_page_stack.err().unwrap()
+            wasm_stack.err().unwrap()
+        );
+    }
+
+    #[test]
+    /// tests that a stack can grow beyond a single 64KiB memory page
+    fn wasm_stack_spans_pages_test() {
+        let mut wasm_stack = WasmStack::default();
+        let page_size = WASM_PAGE_SIZE as u32;
+        assert_eq!(0, wasm_stack.allocate(page_size));
+        assert_eq!(page_size, wasm_stack.allocate(3 * page_size));
+        assert_eq!(4 * page_size, wasm_stack.top());
+
+        let allocation = WasmAllocation::new(page_size, 3 * page_size).unwrap();
+        assert_eq!(Ok(()), wasm_stack.deallocate(allocation));
+        assert_eq!(page_size, wasm_stack.top());
+    }
+
+    #[test]
+    /// tests that allocations in memory stay clear of the pages of the module
+    fn wasm_stack_allocates_in_own_pages_test() {
+        let page_size = WASM_PAGE_SIZE as u32;
+        let mut memory_size = 2 * WASM_PAGE_SIZE;
+        let mut wasm_stack = WasmStack::default();
+        let mut grown = 0;
+
+        // the first allocation goes to a new page after the module's
+        assert_eq!(
+            Ok(2 * page_size),
+            wasm_stack.allocate_in_memory(10, memory_size, |pages| {
+                grown = pages;
+                Ok(())
+            })
+        );
+        assert_eq!(1, grown);
+        memory_size += WASM_PAGE_SIZE;
+
+        // the stack continues in its own page, and grows it as needed
+        assert_eq!(
+            Ok(2 * page_size + 10),
+            wasm_stack.allocate_in_memory(100, memory_size, |_| Ok(()))
+        );
+        assert_eq!(
+            Ok(2 * page_size + 110),
+            wasm_stack.allocate_in_memory(page_size, memory_size, |pages| {
+                grown = pages;
+                Ok(())
+            })
+        );
+        assert_eq!(1, grown);
+        memory_size += WASM_PAGE_SIZE;
+
+        // the module grew its memory, so the stack moves past it
+        memory_size += WASM_PAGE_SIZE;
+        assert_eq!(
+            Ok(5 * page_size),
+            wasm_stack.allocate_in_memory(page_size, memory_size, |pages| {
+                grown = pages;
+                Ok(())
+            })
+        );
+        assert_eq!(1, grown);
+        assert_eq!(6 * page_size, wasm_stack.top());
+
+        // failing to grow fails the allocation
+        assert_eq!(
+            Err(RibosomeErrorCode::OutOfMemory),
+            wasm_stack.allocate_in_memory(1, 6 * WASM_PAGE_SIZE, |_| {
+                Err(RibosomeErrorCode::OutOfMemory)
+            })
         );
     }
 
@@ -207,149 +346,168 @@ pub mod tests {
     fn can_decode_encoded_allocation() {
         assert_eq!(
             // offset 0 = Success
-            decode_encoded_allocation(0b0000000000000000_0000000000000000).unwrap_err(),
+            decode_encoded_allocation(0).unwrap_err(),
             RibosomeReturnCode::Success,
         );
         assert_eq!(
             // offset 1 = generic error
-            decode_encoded_allocation(0b0000000000000001_0000000000000000).unwrap_err(),
+            decode_encoded_allocation(1 << 32).unwrap_err(),
             RibosomeReturnCode::Failure(RibosomeErrorCode::Unspecified),
         );
         assert_eq!(
             // offset 2 = serde json error
-            decode_encoded_allocation(0b0000000000000010_0000000000000000).unwrap_err(),
+            decode_encoded_allocation(2 << 32).unwrap_err(),
             RibosomeReturnCode::Failure(RibosomeErrorCode::ArgumentDeserializationFailed),
         );
         assert_eq!(
             // offset 3 = page overflow error
-            decode_encoded_allocation(0b0000000000000011_0000000000000000).unwrap_err(),
+            decode_encoded_allocation(3 << 32).unwrap_err(),
             RibosomeReturnCode::Failure(RibosomeErrorCode::OutOfMemory),
         );
         assert_eq!(
             // offset 4 = page overflow error
-            decode_encoded_allocation(0b0000000000000100_0000000000000000).unwrap_err(),
+            decode_encoded_allocation(4 << 32).unwrap_err(),
             RibosomeReturnCode::Failure(RibosomeErrorCode::ReceivedWrongActionResult),
         );
 
         assert_eq!(
             // nonsense offset = generic error
-            decode_encoded_allocation(0b1010101010101010_0000000000000000).unwrap_err(),
+            decode_encoded_allocation(0b1010101010101010 << 32).unwrap_err(),
             RibosomeReturnCode::Failure(RibosomeErrorCode::Unspecified),
         );
     }
 
     #[test]
-    /// tests that a SinglePageAllocation returns its encoded offset/length pair as u32
-    fn can_single_page_allocation_encode() {
-        let i = 0b1010101010101010_0101010101010101;
-        let spa = SinglePageAllocation::from_encoded_allocation(i).unwrap();
+    /// tests that the error codes are encoded allocations with a zero length
+    fn can_decode_error_codes() {
+        assert_eq!(
+            decode_encoded_allocation(RibosomeErrorCode::OutOfMemory as u64).unwrap_err(),
+            RibosomeReturnCode::Failure(RibosomeErrorCode::OutOfMemory),
+        );
+        assert_eq!(
+            decode_encoded_allocation(RibosomeErrorCode::UnknownEntryType as u64).unwrap_err(),
+            RibosomeReturnCode::Failure(RibosomeErrorCode::UnknownEntryType),
+        );
+    }
+
+    #[test]
+    /// tests that a WasmAllocation returns its encoded offset/length pair as u64
+    fn can_wasm_allocation_encode() {
+        let i = 0b1010101010101010_1010101010101010_0101010101010101_0101010101010101;
+        let allocation = WasmAllocation::from_encoded_allocation(i).unwrap();
 
-        assert_eq!(i, spa.encode());
+        assert_eq!(i, allocation.encode());
     }
 
     #[test]
-    fn can_single_page_allocation_new_fail() {
+    fn can_wasm_allocation_new_fail() {
         assert_eq!(
             RibosomeErrorCode::ZeroSizedAllocation,
-            SinglePageAllocation::new(0, 0).err().unwrap()
+            WasmAllocation::new(0, 0).err().unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            SinglePageAllocation::new(1, 0).err().unwrap()
+            WasmAllocation::new(1, 0).err().unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::OutOfMemory,
-            SinglePageAllocation::new(<u16>::max_value(), <u16>::max_value())
+            WasmAllocation::new(<u32>::max_value(), <u32>::max_value())
                 .err()
                 .unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::OutOfMemory,
-            SinglePageAllocation::new(<u16>::max_value(), 1)
-                .err()
-                .unwrap()
+            WasmAllocation::new(<u32>::max_value(), 1).err().unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            SinglePageAllocation::new(<u16>::max_value(), 0)
-                .err()
-                .unwrap()
+            WasmAllocation::new(<u32>::max_value(), 0).err().unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::OutOfMemory,
-            SinglePageAllocation::new(1, <u16>::max_value())
-                .err()
-                .unwrap()
+            WasmAllocation::new(1, <u32>::max_value()).err().unwrap()
         );
-        assert!(SinglePageAllocation::new(0, <u16>::max_value()).is_ok());
+        assert!(WasmAllocation::new(0, <u32>::max_value()).is_ok());
+        // allocations are not limited to a single page anymore
+        assert!(WasmAllocation::new(<u16>::max_value() as u32, 1 << 20).is_ok());
     }
 
     #[test]
-    /// tests that a SinglePageAllocation returns its encoded offset/length pair as u32
-    fn can_single_page_allocation_from_fail() {
+    /// tests that a WasmAllocation can not be built from an error code
+    fn can_wasm_allocation_from_fail() {
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            SinglePageAllocation::from_encoded_allocation(0b0000000000000000_0000000000000000)
-                .err()
-                .unwrap()
+            WasmAllocation::from_encoded_allocation(0).err().unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            SinglePageAllocation::from_encoded_allocation(0b0000000000000100_0000000000000000)
+            WasmAllocation::from_encoded_allocation(4 << 32)
                 .err()
                 .unwrap()
         );
         assert_eq!(
             RibosomeErrorCode::NotAnAllocation,
-            SinglePageAllocation::from_encoded_allocation(<u32>::max_value())
+            WasmAllocation::from_encoded_allocation(<u64>::max_value())
                 .err()
                 .unwrap()
         );
     }
 
     #[test]
-    fn test_u32_max_bits() {
-        assert_eq!(<u16>::max_value(), super::u32_high_bits(<u32>::max_value()),);
-        assert_eq!(<u16>::max_value(), super::u32_low_bits(<u32>::max_value()),);
-        let upper_16: u32 = u32::from(<u16>::max_value());
-        let upper_16 = upper_16 << 16;
-        assert_eq!(<u16>::max_value(), super::u32_high_bits(upper_16),);
-        assert_eq!(0, super::u32_low_bits(upper_16),);
+    fn test_u64_max_bits() {
+        assert_eq!(<u32>::max_value(), super::u64_high_bits(<u64>::max_value()),);
+        assert_eq!(<u32>::max_value(), super::u64_low_bits(<u64>::max_value()),);
+        let upper_32: u64 = u64::from(<u32>::max_value());
+        let upper_32 = upper_32 << 32;
+        assert_eq!(<u32>::max_value(), super::u64_high_bits(upper_32),);
+        assert_eq!(0, super::u64_low_bits(upper_32),);
     }
 
     #[test]
-    /// tests that we can extract the high bits from a u32 into the correct u16
-    fn u32_high_bits() {
+    /// tests that we can extract the high bits from a u64 into the correct u32
+    fn u64_high_bits() {
         assert_eq!(
-            0b1010101010101010,
-            super::u32_high_bits(0b1010101010101010_0101010101010101),
+            0b1010101010101010_1010101010101010,
+            super::u64_high_bits(
+                0b1010101010101010_1010101010101010_0101010101010101_0101010101010101
+            ),
         );
     }
 
     #[test]
-    /// tests that we can extract the high bits from a u32 into the correct u16
-    fn u32_low_bits() {
+    /// tests that we can extract the low bits from a u64 into the correct u32
+    fn u64_low_bits() {
         assert_eq!(
-            0b0101010101010101,
-            super::u32_low_bits(0b1010101010101010_0101010101010101),
+            0b0101010101010101_0101010101010101,
+            super::u64_low_bits(
+                0b1010101010101010_1010101010101010_0101010101010101_0101010101010101
+            ),
         );
     }
 
     #[test]
-    /// tests that we can split a u32 into a tuple of high/low bits
-    fn u32_split_bits() {
+    /// tests that we can split a u64 into a tuple of high/low bits
+    fn u64_split_bits() {
         assert_eq!(
-            (0b1010101010101010, 0b0101010101010101),
-            super::u32_split_bits(0b1010101010101010_0101010101010101),
+            (
+                0b1010101010101010_1010101010101010,
+                0b0101010101010101_0101010101010101
+            ),
+            super::u64_split_bits(
+                0b1010101010101010_1010101010101010_0101010101010101_0101010101010101
+            ),
         );
     }
 
     #[test]
-    /// tests that we can merge a u16 tuple into a u32
-    fn u32_merge_bits() {
+    /// tests that we can merge a u32 tuple into a u64
+    fn u64_merge_bits() {
         assert_eq!(
-            0b1010101010101010_0101010101010101,
-            super::u32_merge_bits(0b1010101010101010, 0b0101010101010101),
+            0b1010101010101010_1010101010101010_0101010101010101_0101010101010101,
+            super::u64_merge_bits(
+                0b1010101010101010_1010101010101010,
+                0b0101010101010101_0101010101010101
+            ),
         );
     }
 
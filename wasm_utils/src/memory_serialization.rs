@@ -3,7 +3,8 @@ use holochain_core_types::{
     json::JsonString,
 };
 use memory_allocation::{
-    decode_encoded_allocation, SinglePageAllocation, SinglePageStack, U16_MAX,
+    decode_encoded_allocation, grow_wasm_memory, wasm_memory_size, WasmAllocation, WasmStack,
+    U32_MAX,
 };
 use serde::Deserialize;
 use serde_json;
@@ -20,20 +21,18 @@ fn load_str_from_raw<'a>(ptr_data: *mut c_char) -> &'a str {
 }
 
 /// Write in wasm memory according to stack state.
+/// Grows the memory of the module if the allocation does not fit in the pages of the stack.
 fn write_in_wasm_memory(
-    stack: &mut SinglePageStack,
+    stack: &mut WasmStack,
     bytes: &[u8],
-    len: u16,
-) -> Result<SinglePageAllocation, RibosomeErrorCode> {
-    if u32::from(len) + u32::from(stack.top()) > U16_MAX {
-        return Err(RibosomeErrorCode::OutOfMemory);
-    }
-    let ptr = stack.allocate(len) as *mut c_char;
+    len: u32,
+) -> Result<WasmAllocation, RibosomeErrorCode> {
+    let ptr = stack.allocate_in_memory(len, wasm_memory_size(), grow_wasm_memory)? as *mut c_char;
     let ptr_safe = unsafe { slice::from_raw_parts_mut(ptr, len as usize) };
     for (i, byte) in bytes.iter().enumerate() {
         ptr_safe[i] = *byte as i8;
     }
-    SinglePageAllocation::new(ptr as u16, len)
+    WasmAllocation::new(ptr as u32, len)
 }
 
 //-------------------------------------------------------------------------------------------------
@@ -41,26 +40,23 @@ fn write_in_wasm_memory(
 //-------------------------------------------------------------------------------------------------
 
 /// Write a string in wasm memory according to stack state.
-pub fn store_string(
-    stack: &mut SinglePageStack,
-    s: &str,
-) -> Result<SinglePageAllocation, RibosomeErrorCode> {
+pub fn store_string(stack: &mut WasmStack, s: &str) -> Result<WasmAllocation, RibosomeErrorCode> {
     let bytes = s.as_bytes();
-    let len = bytes.len() as u32;
-    if len > U16_MAX {
+    let len = bytes.len() as u64;
+    if len > U32_MAX {
         return Err(RibosomeErrorCode::OutOfMemory);
     }
-    return write_in_wasm_memory(stack, bytes, len as u16);
+    return write_in_wasm_memory(stack, bytes, len as u32);
 }
 
 // Sugar
-pub fn store_string_into_encoded_allocation(stack: &mut SinglePageStack, s: &str) -> i32 {
-    store_string(stack, s).unwrap().encode() as i32
+pub fn store_string_into_encoded_allocation(stack: &mut WasmStack, s: &str) -> i64 {
+    store_string(stack, s).unwrap().encode() as i64
 }
 
 /// Retrieve a stored string from an encoded allocation.
 /// Return error code if encoded_allocation is invalid.
-pub fn load_string(encoded_allocation: u32) -> Result<String, RibosomeErrorCode> {
+pub fn load_string(encoded_allocation: u64) -> Result<String, RibosomeErrorCode> {
     let maybe_allocation = decode_encoded_allocation(encoded_allocation);
     match maybe_allocation {
         Err(return_code) => match return_code {
@@ -77,31 +73,31 @@ pub fn load_string(encoded_allocation: u32) -> Result<String, RibosomeErrorCode>
 
 /// Write a data struct as a json string in wasm memory according to stack state.
 pub fn store_as_json<J: TryInto<JsonString>>(
-    stack: &mut SinglePageStack,
+    stack: &mut WasmStack,
     jsonable: J,
-) -> Result<SinglePageAllocation, RibosomeErrorCode> {
+) -> Result<WasmAllocation, RibosomeErrorCode> {
     let j: JsonString = jsonable
         .try_into()
         .map_err(|_| RibosomeErrorCode::ArgumentDeserializationFailed)?;
     let json_bytes = j.into_bytes();
-    let json_bytes_len = json_bytes.len() as u32;
-    if json_bytes_len > U16_MAX {
+    let json_bytes_len = json_bytes.len() as u64;
+    if json_bytes_len > U32_MAX {
         return Err(RibosomeErrorCode::OutOfMemory);
     }
-    write_in_wasm_memory(stack, &json_bytes, json_bytes_len as u16)
+    write_in_wasm_memory(stack, &json_bytes, json_bytes_len as u32)
 }
 
 // Sugar
 pub fn store_as_json_into_encoded_allocation<J: TryInto<JsonString>>(
-    stack: &mut SinglePageStack,
+    stack: &mut WasmStack,
     jsonable: J,
-) -> i32 {
-    store_as_json(stack, jsonable).unwrap().encode() as i32
+) -> i64 {
+    store_as_json(stack, jsonable).unwrap().encode() as i64
 }
 
 /// Retrieve a stored data struct from an encoded allocation.
 /// Return error string if encoded_allocation is invalid.
-pub fn load_json<'s, T: Deserialize<'s>>(encoded_allocation: u32) -> Result<T, HolochainError> {
+pub fn load_json<'s, T: Deserialize<'s>>(encoded_allocation: u64) -> Result<T, HolochainError> {
     let maybe_allocation = decode_encoded_allocation(encoded_allocation);
     match maybe_allocation {
         Err(return_code) => match return_code {
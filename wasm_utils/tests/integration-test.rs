@@ -56,6 +56,12 @@ fn call_store_string_ok() {
     assert_eq!(JsonString::from("fish"), call_result.unwrap());
 }
 
+#[test]
+fn call_store_large_string_ok() {
+    let call_result = call_zome_function_with_hc("test_store_large_string_ok");
+    assert_eq!(JsonString::from("x".repeat(100_000)), call_result.unwrap());
+}
+
 #[test]
 fn call_store_as_json_str_ok() {
     let call_result = call_zome_function_with_hc("test_store_as_json_str_ok");
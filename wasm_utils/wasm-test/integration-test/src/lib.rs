@@ -27,8 +27,8 @@ struct OtherTestStruct {
 }
 
 #[no_mangle]
-pub extern "C" fn test_error_report(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_error_report(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     zome_assert!(stack, false);
     0
 }
@@ -37,8 +37,8 @@ pub extern "C" fn test_error_report(_: u32) -> u32 {
 //// Can't do zome_assert!() while testing store_json() since it internally uses store_json() !
 //// so using normal assert! even if we get unhelpful Trap::Unreachable error message.
 //#[no_mangle]
-//pub extern "C" fn test_store_string_ok(_: u32) -> u32 {
-//    let mut stack = SinglePageStack::default();
+//pub extern "C" fn test_store_string_ok(_: u64) -> u64 {
+//    let mut stack = WasmStack::default();
 //    let s = "some string";
 //    assert_eq!(0, stack.top());
 //    let res = store_string(&mut stack, s);
@@ -48,8 +48,8 @@ pub extern "C" fn test_error_report(_: u32) -> u32 {
 //}
 
 #[no_mangle]
-pub extern "C" fn test_store_string_ok(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_store_string_ok(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let s = "fish";
     assert_eq!(0, stack.top());
     let res = store_string(&mut stack, s);
@@ -58,8 +58,18 @@ pub extern "C" fn test_store_string_ok(_: u32) -> u32 {
 }
 
 #[no_mangle]
-pub extern "C" fn test_store_as_json_str_ok(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_store_large_string_ok(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
+    // bigger than a single 64KiB memory page
+    let s = "x".repeat(100_000);
+    let res = store_string(&mut stack, &s);
+    assert_eq!(s.len(), stack.top() as usize);
+    res.unwrap().encode()
+}
+
+#[no_mangle]
+pub extern "C" fn test_store_as_json_str_ok(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let s = "fish";
     assert_eq!(0, stack.top());
 
@@ -70,8 +80,8 @@ pub extern "C" fn test_store_as_json_str_ok(_: u32) -> u32 {
 }
 
 #[no_mangle]
-pub extern "C" fn test_store_as_json_obj_ok(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_store_as_json_obj_ok(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let obj = TestStruct {
         value: "fish".to_string(),
     };
@@ -82,21 +92,21 @@ pub extern "C" fn test_store_as_json_obj_ok(_: u32) -> u32 {
 }
 
 #[no_mangle]
-pub extern "C" fn test_store_string_err(_: u32) -> u32 {
-    let allmost_full_alloc = 0b1111111111111101_0000000000000010;
-    let maybe_stack = SinglePageStack::from_encoded_allocation(allmost_full_alloc);
+pub extern "C" fn test_store_string_err(_: u64) -> u64 {
+    let allmost_full_alloc = 0b11111111111111111111111111111101_00000000000000000000000000000010;
+    let maybe_stack = WasmStack::from_encoded_allocation(allmost_full_alloc);
     assert!(maybe_stack.is_ok());
     let mut stack = maybe_stack.unwrap();
     let s = "fish";
     let res = store_string(&mut stack, s);
     assert!(res.is_err());
-    res.err().unwrap() as u32
+    res.err().unwrap() as u64
 }
 
 #[no_mangle]
-pub extern "C" fn test_store_as_json_err(_: u32) -> u32 {
-    let allmost_full_alloc = 0b1111111111111101_0000000000000010;
-    let maybe_stack = SinglePageStack::from_encoded_allocation(allmost_full_alloc);
+pub extern "C" fn test_store_as_json_err(_: u64) -> u64 {
+    let allmost_full_alloc = 0b11111111111111111111111111111101_00000000000000000000000000000010;
+    let maybe_stack = WasmStack::from_encoded_allocation(allmost_full_alloc);
     assert!(maybe_stack.is_ok());
     let mut stack = maybe_stack.unwrap();
     let obj = TestStruct {
@@ -104,12 +114,12 @@ pub extern "C" fn test_store_as_json_err(_: u32) -> u32 {
     };
     let res = store_as_json(&mut stack, obj.clone());
     assert!(res.is_err());
-    res.err().unwrap() as u32
+    res.err().unwrap() as u64
 }
 
 #[no_mangle]
-pub extern "C" fn test_load_json_from_raw_ok(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_load_json_from_raw_ok(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let obj = TestStruct {
         value: "fish".to_string(),
     };
@@ -121,8 +131,8 @@ pub extern "C" fn test_load_json_from_raw_ok(_: u32) -> u32 {
 }
 
 #[no_mangle]
-pub extern "C" fn test_load_json_from_raw_err(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_load_json_from_raw_err(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let obj = TestStruct {
         value: "fish".to_string(),
     };
@@ -136,72 +146,72 @@ pub extern "C" fn test_load_json_from_raw_err(_: u32) -> u32 {
 }
 
 #[no_mangle]
-pub extern "C" fn test_load_json_ok(_: u32) -> u32 {
+pub extern "C" fn test_load_json_ok(_: u64) -> u64 {
     let encoded = test_store_as_json_obj_ok(0);
-    let mut stack = SinglePageStack::from_encoded_allocation(encoded).unwrap();
+    let mut stack = WasmStack::from_encoded_allocation(encoded).unwrap();
     let res: Result<TestStruct, HolochainError> = load_json(encoded);
     let res = store_as_json(&mut stack, res.unwrap().clone());
     res.unwrap().encode()
 }
 
 #[no_mangle]
-pub extern "C" fn test_load_json_err(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
-    let res: Result<TestStruct, HolochainError> = load_json(1 << 16);
+pub extern "C" fn test_load_json_err(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
+    let res: Result<TestStruct, HolochainError> = load_json(1 << 32);
     zome_assert!(stack, res.is_err());
     let res = store_as_json(&mut stack, res);
     res.unwrap().encode()
 }
 
 #[no_mangle]
-pub extern "C" fn test_load_string_ok(_: u32) -> u32 {
+pub extern "C" fn test_load_string_ok(_: u64) -> u64 {
     let encoded = test_store_string_ok(0);
-    let mut stack = SinglePageStack::from_encoded_allocation(encoded).unwrap();
+    let mut stack = WasmStack::from_encoded_allocation(encoded).unwrap();
     let res = load_string(encoded);
     let res = store_string(&mut stack, &res.unwrap());
     res.unwrap().encode()
 }
 
 #[no_mangle]
-pub extern "C" fn test_load_string_err(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
-    let res = load_string(1 << 16);
+pub extern "C" fn test_load_string_err(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
+    let res = load_string(1 << 32);
     zome_assert!(stack, res.is_err());
     let res = store_string(&mut stack, &res.err().unwrap().to_string());
     res.unwrap().encode()
 }
 
 #[no_mangle]
-pub extern "C" fn test_stacked_strings(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_stacked_strings(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let first = store_string_into_encoded_allocation(&mut stack, "first");
     let _second = store_string_into_encoded_allocation(&mut stack, "second");
-    first as u32
+    first as u64
 }
 
 #[no_mangle]
-pub extern "C" fn test_stacked_json_str(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_stacked_json_str(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let first = store_as_json_into_encoded_allocation(&mut stack, "first");
     let _second = store_as_json_into_encoded_allocation(&mut stack, "second");
-    first as u32
+    first as u64
 }
 
 #[no_mangle]
-pub extern "C" fn test_stacked_json_obj(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_stacked_json_obj(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let first = store_as_json_into_encoded_allocation(&mut stack, TestStruct {
         value: "first".to_string(),
     });
     let _second = store_as_json_into_encoded_allocation(&mut stack, TestStruct {
         value: "second".to_string(),
     });
-    first as u32
+    first as u64
 }
 
 #[no_mangle]
-pub extern "C" fn test_stacked_mix(_: u32) -> u32 {
-    let mut stack = SinglePageStack::default();
+pub extern "C" fn test_stacked_mix(_: u64) -> u64 {
+    let mut stack = WasmStack::default();
     let _first = store_as_json_into_encoded_allocation(&mut stack, TestStruct {
         value: "first".to_string(),
     });
@@ -211,5 +221,5 @@ pub extern "C" fn test_stacked_mix(_: u32) -> u32 {
     let _fifth = store_as_json_into_encoded_allocation(&mut stack, TestStruct {
         value: "fifth".to_string(),
     });
-    third as u32
+    third as u64
 }